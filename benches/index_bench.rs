@@ -0,0 +1,107 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ifs_parser::parser::ast::*;
+use ifs_parser::Index;
+
+const SYMBOL_COUNTS: &[usize] = &[100, 1_000];
+
+fn span_at(line: usize) -> Span {
+    Span {
+        start: Position { line, column: 1, offset: line * 80 },
+        end: Position { line, column: 40, offset: line * 80 + 39 },
+    }
+}
+
+fn identifier(name: String, line: usize) -> Identifier {
+    Identifier { name, span: span_at(line) }
+}
+
+fn parameter(line: usize) -> Parameter {
+    Parameter {
+        name: identifier("value_".to_string(), line),
+        param_type: Type { name: "VARCHAR2".to_string(), parameters: Vec::new(), span: span_at(line) },
+        mode: ParameterMode::In,
+        default_value: None,
+        span: span_at(line),
+    }
+}
+
+/// A package with `count` procedures, each forward declared so every procedure has a reference
+fn synthetic_package(count: usize) -> AstNode {
+    let mut declarations = Vec::with_capacity(count * 2);
+    for i in 0..count {
+        declarations.push(PlSqlDeclaration::SubprogramSpec {
+            name: identifier(format!("Proc_{}", i), i + 2),
+            parameters: vec![parameter(i + 2)],
+            return_type: None,
+            span: span_at(i + 2),
+        });
+    }
+    for i in 0..count {
+        let line = count + i + 2;
+        declarations.push(PlSqlDeclaration::Subprogram(Box::new(PlSqlNode::Procedure {
+            name: identifier(format!("Proc_{}", i), line),
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: vec![parameter(line)],
+            body: Vec::new(),
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+            span: span_at(line),
+        })));
+    }
+
+    AstNode::PlSql(PlSqlNode::Package {
+        name: identifier("Bench_API".to_string(), 1),
+        is_body: true,
+        component: None,
+        annotations: Vec::new(),
+        declarations,
+        body: None,
+        span: span_at(1),
+    })
+}
+
+fn benchmark_indexing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("index synthetic package");
+    for &count in SYMBOL_COUNTS {
+        let ast = synthetic_package(count);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &ast, |b, ast| {
+            b.iter(|| {
+                let mut index = Index::in_memory().unwrap();
+                index.index_file(black_box("bench.plsql"), black_box(ast)).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn benchmark_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search");
+    group.throughput(Throughput::Elements(1));
+    for &count in SYMBOL_COUNTS {
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("bench.plsql", &synthetic_package(count)).unwrap();
+
+        let target = format!("Proc_{}", count / 2);
+        group.bench_with_input(BenchmarkId::new("search_symbols", count), &target, |b, target| {
+            b.iter(|| index.search_symbols(black_box(target)).unwrap())
+        });
+
+        let declaration = index
+            .search_symbols(&target)
+            .unwrap()
+            .into_iter()
+            .find(|symbol| symbol.name == target)
+            .unwrap();
+        group.bench_with_input(BenchmarkId::new("find_references", count), &declaration, |b, symbol| {
+            b.iter(|| index.find_references(black_box(symbol)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_indexing, benchmark_search);
+criterion_main!(benches);
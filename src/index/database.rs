@@ -1,11 +1,94 @@
 // Database interface for the index
 
-use crate::parser::Language;
+use crate::parser::{ast::Span, normalize_identifier, Language};
 use crate::Result;
-use rusqlite::{params, Connection, Row};
+use rusqlite::{params, Connection, DatabaseName, Row};
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Current version of the index schema, stored in `PRAGMA user_version`
+pub const SCHEMA_VERSION: i64 = 8;
+
+/// Migrations that upgrade a database to the version they are listed with.
+/// Databases created before versioning existed are treated as version 1.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 2,
+        rename_tables: &[],
+        add_columns: &[("files", "mtime", "INTEGER")],
+        sql: "",
+    },
+    Migration {
+        version: 3,
+        rename_tables: &[],
+        add_columns: &[("symbols", "normalized_name", "TEXT")],
+        sql: "UPDATE symbols SET normalized_name = UPPER(name) WHERE normalized_name IS NULL;",
+    },
+    Migration {
+        version: 4,
+        rename_tables: &[],
+        add_columns: &[
+            ("references", "caller_id", "INTEGER"),
+            ("pending_references", "caller_id", "INTEGER"),
+        ],
+        sql: "",
+    },
+    Migration {
+        version: 5,
+        rename_tables: &[],
+        add_columns: &[("symbols", "annotations", "TEXT")],
+        // Annotations used to be stored in the signature; reindex every file to move them
+        sql: "UPDATE files SET hash = NULL, mtime = NULL;",
+    },
+    Migration {
+        version: 6,
+        rename_tables: &[],
+        add_columns: &[("symbols", "label", "TEXT")],
+        // Enumeration value labels used to be stored as documentation
+        sql: "UPDATE symbols SET label = documentation, documentation = NULL WHERE kind = 'EnumerationValue';",
+    },
+    Migration {
+        version: 7,
+        rename_tables: &[],
+        add_columns: &[("symbols", "visibility", "TEXT")],
+        // View visibility used to be a `PRIVATE` prefix on the signature; reindex views to move it
+        sql: "UPDATE files SET hash = NULL, mtime = NULL WHERE language = 'Views';",
+    },
+    Migration {
+        version: 8,
+        // REFERENCES is a keyword, so the table had to be quoted wherever it was used
+        rename_tables: &[("references", "symbol_references")],
+        add_columns: &[],
+        sql: "",
+    },
+];
+
+/// A schema change, written so it can be applied to a database that already has part of it
+struct Migration {
+    version: i64,
+    /// Tables to rename as `(old, new)`, skipped unless only the old table exists
+    rename_tables: &'static [(&'static str, &'static str)],
+    /// Columns to add as `(table, column, definition)`. Columns that already exist, e.g.
+    /// in databases written before versioning, and tables `create_tables` has yet to
+    /// create are skipped.
+    add_columns: &'static [(&'static str, &'static str, &'static str)],
+    /// SQL run once the columns are in place
+    sql: &'static str,
+}
+
+/// Column names of `table`, empty if the table does not exist
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+    let rows = stmt.query_map(params![table], |row| row.get(0))?;
+    
+    let mut columns = Vec::new();
+    for row in rows {
+        columns.push(row?);
+    }
+    
+    Ok(columns)
+}
+
 /// Database wrapper for storing indexed information
 pub struct Database {
     conn: Connection,
@@ -28,8 +111,81 @@ impl Database {
         Ok(db)
     }
     
-    /// Initialize the database schema
+    /// Load a database file into memory, upgrading an older schema
+    pub fn restore_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut conn = Connection::open_in_memory()?;
+        conn.restore(DatabaseName::Main, path, None::<fn(rusqlite::backup::Progress)>)?;
+        let mut db = Self { conn };
+        db.initialize_schema()?;
+        Ok(db)
+    }
+    
+    /// Copy the whole database to a file, replacing its contents
+    pub fn backup_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.conn.backup(DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+    
+    /// Initialize the database schema, upgrading an older schema first
     fn initialize_schema(&mut self) -> Result<()> {
+        let version = self.schema_version()?;
+        if version > SCHEMA_VERSION {
+            return Err(anyhow::anyhow!(
+                "Index schema version {} is newer than supported version {}",
+                version,
+                SCHEMA_VERSION
+            ));
+        }
+        
+        if version == 0 && !self.table_exists("files")? {
+            self.create_tables()?;
+        } else {
+            self.migrate(version.max(1))?;
+        }
+        
+        self.conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+    
+    /// Get the schema version recorded in the database, 0 if none
+    pub fn schema_version(&self) -> Result<i64> {
+        Ok(self.conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+    
+    fn table_exists(&self, table: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+    
+    /// Apply every migration newer than `from_version` in a single transaction
+    fn migrate(&mut self, from_version: i64) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for migration in MIGRATIONS.iter().filter(|migration| migration.version > from_version) {
+            log::info!("Migrating index schema to version {}", migration.version);
+            for (old, new) in migration.rename_tables {
+                if !table_columns(&tx, old)?.is_empty() && table_columns(&tx, new)?.is_empty() {
+                    tx.execute_batch(&format!("ALTER TABLE \"{}\" RENAME TO {};", old, new))?;
+                }
+            }
+            for (table, column, definition) in migration.add_columns {
+                let columns = table_columns(&tx, table)?;
+                if !columns.is_empty() && !columns.iter().any(|existing| existing.eq_ignore_ascii_case(column)) {
+                    tx.execute_batch(&format!("ALTER TABLE \"{}\" ADD COLUMN {} {};", table, column, definition))?;
+                }
+            }
+            tx.execute_batch(migration.sql)?;
+        }
+        tx.commit()?;
+        
+        // Tables and indexes added since the database was created
+        self.create_tables()
+    }
+    
+    fn create_tables(&mut self) -> Result<()> {
         self.conn.execute_batch(
             r#"
             CREATE TABLE IF NOT EXISTS files (
@@ -38,7 +194,8 @@ impl Database {
                 language TEXT NOT NULL,
                 indexed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 file_size INTEGER,
-                hash TEXT
+                hash TEXT,
+                mtime INTEGER
             );
             
             CREATE TABLE IF NOT EXISTS symbols (
@@ -55,11 +212,15 @@ impl Database {
                 parent_id INTEGER,
                 signature TEXT,
                 documentation TEXT,
+                normalized_name TEXT,
+                annotations TEXT,
+                label TEXT,
+                visibility TEXT,
                 FOREIGN KEY (file_id) REFERENCES files (id) ON DELETE CASCADE,
                 FOREIGN KEY (parent_id) REFERENCES symbols (id) ON DELETE CASCADE
             );
             
-            CREATE TABLE IF NOT EXISTS references (
+            CREATE TABLE IF NOT EXISTS symbol_references (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 symbol_id INTEGER NOT NULL,
                 file_id INTEGER NOT NULL,
@@ -70,15 +231,33 @@ impl Database {
                 start_offset INTEGER NOT NULL,
                 end_offset INTEGER NOT NULL,
                 reference_kind TEXT NOT NULL,
+                caller_id INTEGER,
                 FOREIGN KEY (symbol_id) REFERENCES symbols (id) ON DELETE CASCADE,
                 FOREIGN KEY (file_id) REFERENCES files (id) ON DELETE CASCADE
             );
             
+            CREATE TABLE IF NOT EXISTS pending_references (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                file_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                qualifier TEXT,
+                start_line INTEGER NOT NULL,
+                start_column INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                end_column INTEGER NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                reference_kind TEXT NOT NULL,
+                caller_id INTEGER,
+                FOREIGN KEY (file_id) REFERENCES files (id) ON DELETE CASCADE
+            );
+            
             CREATE INDEX IF NOT EXISTS idx_symbols_name ON symbols (name);
+            CREATE INDEX IF NOT EXISTS idx_symbols_normalized_name ON symbols (normalized_name);
             CREATE INDEX IF NOT EXISTS idx_symbols_kind ON symbols (kind);
             CREATE INDEX IF NOT EXISTS idx_symbols_file ON symbols (file_id);
-            CREATE INDEX IF NOT EXISTS idx_references_symbol ON references (symbol_id);
-            CREATE INDEX IF NOT EXISTS idx_references_file ON references (file_id);
+            CREATE INDEX IF NOT EXISTS idx_references_symbol ON symbol_references (symbol_id);
+            CREATE INDEX IF NOT EXISTS idx_references_file ON symbol_references (file_id);
             CREATE INDEX IF NOT EXISTS idx_files_path ON files (path);
             "#,
         )?;
@@ -99,6 +278,96 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
     
+    /// Store the size, content hash and modification time of an indexed file
+    pub fn store_file_fingerprint<P: AsRef<Path>>(&mut self, path: P, fingerprint: &FileFingerprint) -> Result<()> {
+        let path_str = path.as_ref().to_string_lossy();
+        self.conn.execute(
+            "UPDATE files SET file_size = ?1, hash = ?2, mtime = ?3 WHERE path = ?4",
+            params![fingerprint.file_size as i64, fingerprint.hash, fingerprint.mtime, path_str],
+        )?;
+        Ok(())
+    }
+    
+    /// Get the stored fingerprint of a file, if it has been indexed
+    pub fn get_file_fingerprint<P: AsRef<Path>>(&self, path: P) -> Result<Option<FileFingerprint>> {
+        let path_str = path.as_ref().to_string_lossy();
+        let mut stmt = self.conn.prepare(
+            "SELECT file_size, hash, mtime FROM files WHERE path = ?1 AND hash IS NOT NULL",
+        )?;
+        
+        let mut rows = stmt.query_map(params![path_str], |row| {
+            Ok(FileFingerprint {
+                file_size: row.get::<_, i64>(0)? as u64,
+                hash: row.get(1)?,
+                mtime: row.get(2)?,
+            })
+        })?;
+        
+        if let Some(row) = rows.next() {
+            Ok(Some(row?))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Get the paths of all indexed files
+    pub fn get_file_paths(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT path FROM files ORDER BY path")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        
+        let mut paths = Vec::new();
+        for row in rows {
+            paths.push(row?);
+        }
+        
+        Ok(paths)
+    }
+    
+    /// Remove a file together with its symbols and references. References from other files
+    /// to its symbols are queued again, keyed by the target's name and parent, so they can be
+    /// linked to the symbols of the file's next version. Returns the queued references.
+    pub fn remove_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<PendingReferenceRow>> {
+        let mut requeued = Vec::new();
+        if let Some(file_id) = self.get_file_id(&path)? {
+            let mut stmt = self.conn.prepare(
+                r#"
+                INSERT INTO pending_references
+                (file_id, name, qualifier, start_line, start_column, end_line, end_column,
+                 start_offset, end_offset, reference_kind, caller_id)
+                SELECT r.file_id, s.normalized_name, p.normalized_name, r.start_line, r.start_column,
+                       r.end_line, r.end_column, r.start_offset, r.end_offset, r.reference_kind, r.caller_id
+                FROM symbol_references r
+                JOIN symbols s ON s.id = r.symbol_id
+                LEFT JOIN symbols p ON p.id = s.parent_id
+                WHERE s.file_id = ?1 AND r.file_id != ?1
+                ORDER BY r.id
+                RETURNING id, file_id, name, qualifier
+                "#,
+            )?;
+            let rows = stmt.query_map(params![file_id], |row| {
+                Ok(PendingReferenceRow {
+                    id: row.get(0)?,
+                    file_id: row.get(1)?,
+                    name: row.get(2)?,
+                    qualifier: row.get(3)?,
+                })
+            })?;
+            for row in rows {
+                requeued.push(row?);
+            }
+            drop(stmt);
+
+            self.conn.execute(
+                "DELETE FROM symbol_references WHERE file_id = ?1 OR symbol_id IN (SELECT id FROM symbols WHERE file_id = ?1)",
+                params![file_id],
+            )?;
+            self.conn.execute("DELETE FROM pending_references WHERE file_id = ?1", params![file_id])?;
+            self.conn.execute("DELETE FROM symbols WHERE file_id = ?1", params![file_id])?;
+            self.conn.execute("DELETE FROM files WHERE id = ?1", params![file_id])?;
+        }
+        Ok(requeued)
+    }
+    
     /// Get file ID by path
     pub fn get_file_id<P: AsRef<Path>>(&self, path: P) -> Result<Option<i64>> {
         let path_str = path.as_ref().to_string_lossy();
@@ -135,8 +404,8 @@ impl Database {
             r#"
             INSERT INTO symbols 
             (file_id, name, kind, start_line, start_column, end_line, end_column, 
-             start_offset, end_offset, parent_id, signature, documentation)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+             start_offset, end_offset, parent_id, signature, documentation, normalized_name)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
             "#,
             params![
                 file_id,
@@ -150,14 +419,43 @@ impl Database {
                 end_offset as i64,
                 parent_id,
                 signature,
-                documentation
+                documentation,
+                normalize_identifier(name)
             ],
         )?;
         
         Ok(self.conn.last_insert_rowid())
     }
     
-    /// Store a reference
+    /// Record a symbol's annotations, one per line
+    pub fn set_symbol_annotations(&mut self, symbol_id: i64, annotations: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE symbols SET annotations = ?1 WHERE id = ?2",
+            params![annotations, symbol_id],
+        )?;
+        Ok(())
+    }
+    
+    /// Record the label text of an enumeration value
+    pub fn set_symbol_label(&mut self, symbol_id: i64, label: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE symbols SET label = ?1 WHERE id = ?2",
+            params![label, symbol_id],
+        )?;
+        Ok(())
+    }
+    
+    /// Record whether a view or view column is `Public` or `Private`
+    pub fn set_symbol_visibility(&mut self, symbol_id: i64, visibility: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE symbols SET visibility = ?1 WHERE id = ?2",
+            params![visibility, symbol_id],
+        )?;
+        Ok(())
+    }
+    
+    /// Store a reference. `caller_id` is the subprogram or package whose body
+    /// contains the reference, if it is made from one.
     pub fn store_reference(
         &mut self,
         symbol_id: i64,
@@ -169,13 +467,14 @@ impl Database {
         start_offset: usize,
         end_offset: usize,
         reference_kind: &str,
+        caller_id: Option<i64>,
     ) -> Result<i64> {
         self.conn.execute(
             r#"
-            INSERT INTO references 
+            INSERT INTO symbol_references 
             (symbol_id, file_id, start_line, start_column, end_line, end_column,
-             start_offset, end_offset, reference_kind)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             start_offset, end_offset, reference_kind, caller_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             "#,
             params![
                 symbol_id,
@@ -186,20 +485,119 @@ impl Database {
                 end_column as i64,
                 start_offset as i64,
                 end_offset as i64,
-                reference_kind
+                reference_kind,
+                caller_id
+            ],
+        )?;
+        
+        Ok(self.conn.last_insert_rowid())
+    }
+    
+    /// Queue a reference whose target is not indexed yet, keyed by its normalized name
+    pub fn store_pending_reference(
+        &mut self,
+        file_id: i64,
+        normalized_name: &str,
+        qualifier: Option<&str>,
+        span: &Span,
+        reference_kind: &str,
+        caller_id: Option<i64>,
+    ) -> Result<i64> {
+        self.conn.execute(
+            r#"
+            INSERT INTO pending_references
+            (file_id, name, qualifier, start_line, start_column, end_line, end_column,
+             start_offset, end_offset, reference_kind, caller_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
+            params![
+                file_id,
+                normalized_name,
+                qualifier,
+                span.start.line as i64,
+                span.start.column as i64,
+                span.end.line as i64,
+                span.end.column as i64,
+                span.start.offset as i64,
+                span.end.offset as i64,
+                reference_kind,
+                caller_id
             ],
         )?;
         
         Ok(self.conn.last_insert_rowid())
     }
     
+    /// Get every queued reference that has not been resolved
+    pub fn get_pending_references(&self) -> Result<Vec<PendingReferenceRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, file_id, name, qualifier FROM pending_references ORDER BY id",
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            Ok(PendingReferenceRow {
+                id: row.get(0)?,
+                file_id: row.get(1)?,
+                name: row.get(2)?,
+                qualifier: row.get(3)?,
+            })
+        })?;
+        
+        let mut references = Vec::new();
+        for row in rows {
+            references.push(row?);
+        }
+        
+        Ok(references)
+    }
+    
+    /// Turn a queued reference into a stored reference to `symbol_id`
+    pub fn resolve_pending_reference(&mut self, pending_id: i64, symbol_id: i64) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            r#"
+            INSERT INTO symbol_references
+            (symbol_id, file_id, start_line, start_column, end_line, end_column,
+             start_offset, end_offset, reference_kind, caller_id)
+            SELECT ?2, file_id, start_line, start_column, end_line, end_column,
+                   start_offset, end_offset, reference_kind, caller_id
+            FROM pending_references WHERE id = ?1
+            "#,
+            params![pending_id, symbol_id],
+        )?;
+        tx.execute("DELETE FROM pending_references WHERE id = ?1", params![pending_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+    
+    /// Resolve a normalized name to a symbol id. With a qualifier, the symbol's parent must
+    /// have that normalized name. Symbols in `file_id` are preferred over other files.
+    pub fn resolve_symbol(&self, normalized_name: &str, qualifier: Option<&str>, file_id: i64) -> Result<Option<i64>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id
+            FROM symbols s
+            LEFT JOIN symbols p ON s.parent_id = p.id
+            WHERE s.normalized_name = ?1 AND (?2 IS NULL OR p.normalized_name = ?2)
+            ORDER BY s.file_id = ?3 DESC, s.id
+            LIMIT 1
+            "#,
+        )?;
+        
+        let mut rows = stmt.query_map(params![normalized_name, qualifier, file_id], |row| row.get(0))?;
+        match rows.next() {
+            Some(row) => Ok(Some(row?)),
+            None => Ok(None),
+        }
+    }
+    
     /// Search symbols by name pattern
     pub fn search_symbols(&self, pattern: &str) -> Result<Vec<SymbolRow>> {
         let mut stmt = self.conn.prepare(
             r#"
             SELECT s.id, s.file_id, f.path, s.name, s.kind, 
                    s.start_line, s.start_column, s.end_line, s.end_column,
-                   s.start_offset, s.end_offset, s.signature, s.documentation
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
             FROM symbols s
             JOIN files f ON s.file_id = f.id
             WHERE s.name LIKE ?1
@@ -216,9 +614,292 @@ impl Database {
             symbols.push(row?);
         }
         
+        self.with_parents(symbols)
+    }
+    
+    /// Search symbols by name pattern, returning the `limit` matches after skipping `offset`
+    /// together with the total number of matches
+    pub fn search_symbols_paged(&self, pattern: &str, limit: usize, offset: usize) -> Result<(Vec<SymbolRow>, usize)> {
+        let pattern = format!("%{}%", pattern);
+        let total: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM symbols s JOIN files f ON s.file_id = f.id WHERE s.name LIKE ?1",
+            params![pattern],
+            |row| row.get(0),
+        )?;
+        
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.name LIKE ?1
+            ORDER BY s.name, s.id
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )?;
+        
+        let limit = limit.min(i64::MAX as usize) as i64;
+        let offset = offset.min(i64::MAX as usize) as i64;
+        let rows = stmt.query_map(params![pattern, limit, offset], SymbolRow::from_row)?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        Ok((self.with_parents(symbols)?, total as usize))
+    }
+    
+    /// Find up to `limit` symbols whose normalized name starts with `normalized_prefix`,
+    /// ordered by name. An empty `kinds` slice matches every kind.
+    pub fn search_symbols_by_prefix(&self, normalized_prefix: &str, kinds: &[String], limit: usize) -> Result<Vec<SymbolRow>> {
+        // A range over normalized_name uses idx_symbols_normalized_name, unlike LIKE
+        let upper_bound = format!("{}\u{10FFFF}", normalized_prefix);
+        let kind_filter = if kinds.is_empty() {
+            String::new()
+        } else {
+            let placeholders: Vec<String> = (0..kinds.len()).map(|i| format!("?{}", i + 4)).collect();
+            format!("AND s.kind IN ({})", placeholders.join(", "))
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.normalized_name >= ?1 AND s.normalized_name < ?2 {}
+            ORDER BY s.name
+            LIMIT ?3
+            "#,
+            kind_filter
+        ))?;
+        
+        let mut values: Vec<&dyn rusqlite::ToSql> = vec![&normalized_prefix, &upper_bound];
+        let limit = limit.min(i64::MAX as usize) as i64;
+        values.push(&limit);
+        values.extend(kinds.iter().map(|kind| kind as &dyn rusqlite::ToSql));
+        let rows = stmt.query_map(values.as_slice(), SymbolRow::from_row)?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
+    }
+    
+    /// Search symbols of `kind` whose label contains `text`, ignoring case
+    pub fn search_symbols_by_label(&self, text: &str, kind: &str) -> Result<Vec<SymbolRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE instr(LOWER(s.label), LOWER(?1)) > 0 AND s.kind = ?2
+            ORDER BY s.name
+            "#,
+        )?;
+        
+        let rows = stmt.query_map(params![text, kind], SymbolRow::from_row)?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
+    }
+    
+    /// Find symbols with exactly this name (ignoring case) and kind, declared in files of `language`
+    pub fn find_symbols_exact(&self, name: &str, kind: &str, language: Language) -> Result<Vec<SymbolRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.name = ?1 COLLATE NOCASE AND s.kind = ?2 AND f.language = ?3
+            ORDER BY f.path
+            "#,
+        )?;
+        
+        let rows = stmt.query_map(params![name, kind, format!("{:?}", language)], SymbolRow::from_row)?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
+    }
+    
+    /// Find the PL/SQL procedures and functions with a normalized name. With a qualifier,
+    /// the subprogram's parent must have that normalized name.
+    pub fn find_subprograms(&self, normalized_name: &str, qualifier: Option<&str>) -> Result<Vec<SymbolRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            LEFT JOIN symbols p ON s.parent_id = p.id
+            JOIN files f ON s.file_id = f.id
+            WHERE s.normalized_name = ?1 AND (?2 IS NULL OR p.normalized_name = ?2)
+              AND s.kind IN ('Procedure', 'Function') AND f.language = ?3
+            ORDER BY f.path, s.start_offset
+            "#,
+        )?;
+        
+        let rows = stmt.query_map(
+            params![normalized_name, qualifier, format!("{:?}", Language::PlSql)],
+            SymbolRow::from_row,
+        )?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
+    }
+    
+    /// Find the PL/SQL type declarations with a normalized name. With a qualifier, the
+    /// type's parent must have that normalized name.
+    pub fn find_types(&self, normalized_name: &str, qualifier: Option<&str>) -> Result<Vec<SymbolRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            LEFT JOIN symbols p ON s.parent_id = p.id
+            JOIN files f ON s.file_id = f.id
+            WHERE s.normalized_name = ?1 AND (?2 IS NULL OR p.normalized_name = ?2)
+              AND s.kind = 'Type' AND f.language = ?3
+            ORDER BY f.path, s.start_offset
+            "#,
+        )?;
+        
+        let rows = stmt.query_map(
+            params![normalized_name, qualifier, format!("{:?}", Language::PlSql)],
+            SymbolRow::from_row,
+        )?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
+    }
+    
+    /// Get the subprograms in other files with the same name and kind as `symbol_id`
+    /// whose parent has the same name as its parent. Forward declarations and subprograms
+    /// annotated `@Override` or `@Overtake` themselves are left out.
+    pub fn find_override_candidates(&self, symbol_id: i64) -> Result<Vec<SymbolRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols o
+            LEFT JOIN symbols op ON op.id = o.parent_id
+            JOIN symbols s ON s.normalized_name = o.normalized_name AND s.kind = o.kind AND s.file_id != o.file_id
+            LEFT JOIN symbols sp ON sp.id = s.parent_id
+            JOIN files f ON s.file_id = f.id
+            WHERE o.id = ?1
+              AND sp.normalized_name IS op.normalized_name
+              AND NOT EXISTS (
+                  SELECT 1 FROM symbol_references d
+                  WHERE d.symbol_id = s.id AND d.reference_kind = 'Declaration'
+              )
+              AND COALESCE(s.annotations, '') NOT LIKE '%@Override%'
+              AND COALESCE(s.annotations, '') NOT LIKE '%@Overtake%'
+            ORDER BY f.path
+            "#,
+        )?;
+        
+        let rows = stmt.query_map(params![symbol_id], SymbolRow::from_row)?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
+    }
+    
+    /// Get a symbol by id
+    pub fn get_symbol(&self, symbol_id: i64) -> Result<Option<SymbolRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.id = ?1
+            "#,
+        )?;
+        
+        let mut rows = stmt.query_map(params![symbol_id], SymbolRow::from_row)?;
+        
+        match rows.next() {
+            Some(row) => Ok(self.with_parents(vec![row?])?.pop()),
+            None => Ok(None),
+        }
+    }
+    
+    /// Attach the chain of enclosing symbols to each row, loading every parent once
+    fn with_parents(&self, mut symbols: Vec<SymbolRow>) -> Result<Vec<SymbolRow>> {
+        let mut parents: HashMap<i64, Option<SymbolRow>> = HashMap::new();
+        for symbol in &mut symbols {
+            let Some(parent_id) = symbol.parent_id else {
+                continue;
+            };
+            let parent = match parents.get(&parent_id) {
+                Some(parent) => parent.clone(),
+                None => {
+                    let parent = self.get_symbol(parent_id)?;
+                    parents.insert(parent_id, parent.clone());
+                    parent
+                }
+            };
+            symbol.parent = parent.map(Box::new);
+        }
         Ok(symbols)
     }
     
+    /// Get the symbols whose parent is the given symbol
+    pub fn get_child_symbols(&self, parent_id: i64) -> Result<Vec<SymbolRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.parent_id = ?1
+            ORDER BY s.start_offset
+            "#,
+        )?;
+        
+        let rows = stmt.query_map(params![parent_id], SymbolRow::from_row)?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
+    }
+    
     /// Find references for a symbol
     pub fn find_references(&self, symbol_id: i64) -> Result<Vec<ReferenceRow>> {
         let mut stmt = self.conn.prepare(
@@ -226,7 +907,7 @@ impl Database {
             SELECT r.id, r.symbol_id, r.file_id, f.path,
                    r.start_line, r.start_column, r.end_line, r.end_column,
                    r.start_offset, r.end_offset, r.reference_kind
-            FROM references r
+            FROM symbol_references r
             JOIN files f ON r.file_id = f.id
             WHERE r.symbol_id = ?1
             ORDER BY f.path, r.start_line, r.start_column
@@ -252,7 +933,7 @@ impl Database {
             r#"
             SELECT s.id, s.file_id, f.path, s.name, s.kind,
                    s.start_line, s.start_column, s.end_line, s.end_column,
-                   s.start_offset, s.end_offset, s.signature, s.documentation
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
             FROM symbols s
             JOIN files f ON s.file_id = f.id
             WHERE f.path = ?1
@@ -269,7 +950,37 @@ impl Database {
             symbols.push(row?);
         }
         
-        Ok(symbols)
+        self.with_parents(symbols)
+    }
+    
+    /// Get the symbols of a file that start and end within `start_line..=end_line`
+    pub fn get_file_symbols_in_range<P: AsRef<Path>>(
+        &self,
+        path: P,
+        start_line: usize,
+        end_line: usize,
+    ) -> Result<Vec<SymbolRow>> {
+        let path_str = path.as_ref().to_string_lossy();
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.file_id, f.path, s.name, s.kind,
+                   s.start_line, s.start_column, s.end_line, s.end_column,
+                   s.start_offset, s.end_offset, s.signature, s.documentation, s.annotations, s.label, s.visibility, s.parent_id
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE f.path = ?1 AND s.start_line >= ?2 AND s.end_line <= ?3
+            ORDER BY s.start_line, s.start_column
+            "#,
+        )?;
+        
+        let rows = stmt.query_map(params![path_str, start_line, end_line], SymbolRow::from_row)?;
+        
+        let mut symbols = Vec::new();
+        for row in rows {
+            symbols.push(row?);
+        }
+        
+        self.with_parents(symbols)
     }
     
     /// Get statistics about the index
@@ -287,7 +998,7 @@ impl Database {
         )?;
         
         let total_references: usize = self.conn.query_row(
-            "SELECT COUNT(*) FROM references",
+            "SELECT COUNT(*) FROM symbol_references",
             [],
             |row| Ok(row.get::<_, i64>(0)? as usize),
         )?;
@@ -319,11 +1030,332 @@ impl Database {
         })
     }
     
+    /// Get the number of symbols in each file, largest first
+    pub fn stats_by_file(&self) -> Result<Vec<(String, usize)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT f.path, COUNT(s.id) AS symbol_count
+            FROM files f
+            JOIN symbols s ON s.file_id = f.id
+            GROUP BY s.file_id
+            ORDER BY symbol_count DESC, f.path
+            "#,
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            let path: String = row.get(0)?;
+            let count: i64 = row.get(1)?;
+            Ok((path, count as usize))
+        })?;
+        
+        let mut stats = Vec::new();
+        for row in rows {
+            stats.push(row?);
+        }
+        
+        Ok(stats)
+    }
+    
+    /// Get the distinct calls between subprograms as `(caller, callee)` pairs of names
+    /// qualified with their enclosing symbols
+    pub fn get_call_edges(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            WITH RECURSIVE qualified(id, name) AS (
+                SELECT id, name FROM symbols WHERE parent_id IS NULL
+                UNION ALL
+                SELECT s.id, q.name || '.' || s.name
+                FROM symbols s
+                JOIN qualified q ON s.parent_id = q.id
+            )
+            SELECT DISTINCT caller_name.name, callee_name.name
+            FROM symbol_references r
+            JOIN symbols caller ON caller.id = r.caller_id
+            JOIN qualified caller_name ON caller_name.id = caller.id
+            JOIN qualified callee_name ON callee_name.id = r.symbol_id
+            WHERE r.reference_kind = 'Call' AND caller.kind IN ('Procedure', 'Function')
+            ORDER BY caller_name.name, callee_name.name
+            "#,
+        )?;
+        
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        
+        let mut edges = Vec::new();
+        for row in rows {
+            edges.push(row?);
+        }
+        
+        Ok(edges)
+    }
+    
+    /// Get every indexed file with its symbol count, including files without symbols
+    pub fn symbol_counts_for_all_files(&self) -> Result<Vec<(String, usize)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT f.path, COUNT(s.id)
+            FROM files f
+            LEFT JOIN symbols s ON s.file_id = f.id
+            GROUP BY f.id
+            ORDER BY f.path
+            "#,
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            let path: String = row.get(0)?;
+            let count: i64 = row.get(1)?;
+            Ok((path, count as usize))
+        })?;
+        
+        let mut counts = Vec::new();
+        for row in rows {
+            counts.push(row?);
+        }
+        
+        Ok(counts)
+    }
+    
+    /// Copy files, symbols, references and queued references from another database,
+    /// remapping their ids.
+    /// Files whose path is already present here are skipped along with their rows.
+    pub fn merge_from(&mut self, other: &Database) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let mut file_ids: HashMap<i64, i64> = HashMap::new();
+        let mut symbol_ids: HashMap<i64, i64> = HashMap::new();
+        
+        {
+            let mut stmt = other.conn.prepare(
+                "SELECT id, path, language, indexed_at, file_size, hash, mtime FROM files ORDER BY id",
+            )?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let path: String = row.get(1)?;
+                let inserted = tx.execute(
+                    r#"
+                    INSERT OR IGNORE INTO files (path, language, indexed_at, file_size, hash, mtime)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    "#,
+                    params![
+                        path,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                        row.get::<_, Option<i64>>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                        row.get::<_, Option<i64>>(6)?
+                    ],
+                )?;
+                if inserted > 0 {
+                    file_ids.insert(row.get(0)?, tx.last_insert_rowid());
+                }
+            }
+        }
+        
+        {
+            let mut stmt = other.conn.prepare(
+                r#"
+                SELECT id, file_id, name, kind, start_line, start_column, end_line, end_column,
+                       start_offset, end_offset, parent_id, signature, documentation, normalized_name,
+                       annotations, label, visibility
+                FROM symbols ORDER BY id
+                "#,
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut parents = Vec::new();
+            while let Some(row) = rows.next()? {
+                let Some(&file_id) = file_ids.get(&row.get::<_, i64>(1)?) else {
+                    continue;
+                };
+                tx.execute(
+                    r#"
+                    INSERT INTO symbols
+                    (file_id, name, kind, start_line, start_column, end_line, end_column,
+                     start_offset, end_offset, signature, documentation, normalized_name, annotations, label,
+                     visibility)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                    "#,
+                    params![
+                        file_id,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, i64>(4)?,
+                        row.get::<_, i64>(5)?,
+                        row.get::<_, i64>(6)?,
+                        row.get::<_, i64>(7)?,
+                        row.get::<_, i64>(8)?,
+                        row.get::<_, i64>(9)?,
+                        row.get::<_, Option<String>>(11)?,
+                        row.get::<_, Option<String>>(12)?,
+                        row.get::<_, Option<String>>(13)?,
+                        row.get::<_, Option<String>>(14)?,
+                        row.get::<_, Option<String>>(15)?,
+                        row.get::<_, Option<String>>(16)?
+                    ],
+                )?;
+                let symbol_id = tx.last_insert_rowid();
+                symbol_ids.insert(row.get(0)?, symbol_id);
+                if let Some(parent_id) = row.get::<_, Option<i64>>(10)? {
+                    parents.push((symbol_id, parent_id));
+                }
+            }
+            
+            // Parents are linked once every symbol has its new id
+            for (symbol_id, parent_id) in parents {
+                tx.execute(
+                    "UPDATE symbols SET parent_id = ?1 WHERE id = ?2",
+                    params![symbol_ids.get(&parent_id), symbol_id],
+                )?;
+            }
+        }
+        
+        {
+            let mut stmt = other.conn.prepare(
+                r#"
+                SELECT symbol_id, file_id, start_line, start_column, end_line, end_column,
+                       start_offset, end_offset, reference_kind, caller_id
+                FROM symbol_references ORDER BY id
+                "#,
+            )?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let (Some(&symbol_id), Some(&file_id)) = (
+                    symbol_ids.get(&row.get::<_, i64>(0)?),
+                    file_ids.get(&row.get::<_, i64>(1)?),
+                ) else {
+                    continue;
+                };
+                tx.execute(
+                    r#"
+                    INSERT INTO symbol_references
+                    (symbol_id, file_id, start_line, start_column, end_line, end_column,
+                     start_offset, end_offset, reference_kind, caller_id)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                    "#,
+                    params![
+                        symbol_id,
+                        file_id,
+                        row.get::<_, i64>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, i64>(4)?,
+                        row.get::<_, i64>(5)?,
+                        row.get::<_, i64>(6)?,
+                        row.get::<_, i64>(7)?,
+                        row.get::<_, String>(8)?,
+                        row.get::<_, Option<i64>>(9)?.and_then(|caller_id| symbol_ids.get(&caller_id))
+                    ],
+                )?;
+            }
+        }
+        
+        {
+            // References still waiting for their target may resolve against the merged files
+            let mut stmt = other.conn.prepare(
+                r#"
+                SELECT file_id, name, qualifier, start_line, start_column, end_line, end_column,
+                       start_offset, end_offset, reference_kind, caller_id
+                FROM pending_references ORDER BY id
+                "#,
+            )?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let Some(&file_id) = file_ids.get(&row.get::<_, i64>(0)?) else {
+                    continue;
+                };
+                tx.execute(
+                    r#"
+                    INSERT INTO pending_references
+                    (file_id, name, qualifier, start_line, start_column, end_line, end_column,
+                     start_offset, end_offset, reference_kind, caller_id)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                    params![
+                        file_id,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, i64>(4)?,
+                        row.get::<_, i64>(5)?,
+                        row.get::<_, i64>(6)?,
+                        row.get::<_, i64>(7)?,
+                        row.get::<_, i64>(8)?,
+                        row.get::<_, String>(9)?,
+                        row.get::<_, Option<i64>>(10)?.and_then(|caller_id| symbol_ids.get(&caller_id))
+                    ],
+                )?;
+            }
+        }
+        
+        tx.commit()?;
+        Ok(())
+    }
+    
+    /// Check the index for rows left behind by interrupted or buggy updates:
+    /// foreign key violations, symbols whose file is gone, references whose
+    /// symbol or file is gone and queued references whose file is gone
+    pub fn health_check(&self) -> Result<super::HealthReport> {
+        let mut stmt = self.conn.prepare("PRAGMA foreign_key_check")?;
+        let mut foreign_key_violations = 0;
+        let mut rows = stmt.query([])?;
+        while rows.next()?.is_some() {
+            foreign_key_violations += 1;
+        }
+        
+        let orphaned_symbols: usize = self.conn.query_row(
+            "SELECT COUNT(*) FROM symbols WHERE file_id NOT IN (SELECT id FROM files)",
+            [],
+            |row| Ok(row.get::<_, i64>(0)? as usize),
+        )?;
+        
+        let orphaned_references: usize = self.conn.query_row(
+            r#"
+            SELECT COUNT(*) FROM symbol_references
+            WHERE symbol_id NOT IN (SELECT id FROM symbols)
+               OR file_id NOT IN (SELECT id FROM files)
+            "#,
+            [],
+            |row| Ok(row.get::<_, i64>(0)? as usize),
+        )?;
+        
+        let orphaned_pending_references: usize = self.conn.query_row(
+            "SELECT COUNT(*) FROM pending_references WHERE file_id NOT IN (SELECT id FROM files)",
+            [],
+            |row| Ok(row.get::<_, i64>(0)? as usize),
+        )?;
+        
+        Ok(super::HealthReport {
+            foreign_key_violations,
+            orphaned_symbols,
+            orphaned_references,
+            orphaned_pending_references,
+        })
+    }
+    
+    /// Delete orphaned symbols, references and queued references, returning the
+    /// number of rows removed
+    pub fn repair(&mut self) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        // Symbols first, so references to them are caught as orphans below
+        let symbols = tx.execute("DELETE FROM symbols WHERE file_id NOT IN (SELECT id FROM files)", [])?;
+        let references = tx.execute(
+            r#"
+            DELETE FROM symbol_references
+            WHERE symbol_id NOT IN (SELECT id FROM symbols)
+               OR file_id NOT IN (SELECT id FROM files)
+            "#,
+            [],
+        )?;
+        let pending_references = tx.execute(
+            "DELETE FROM pending_references WHERE file_id NOT IN (SELECT id FROM files)",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(symbols + references + pending_references)
+    }
+    
     /// Clear all data
     pub fn clear_all(&mut self) -> Result<()> {
         self.conn.execute_batch(
             r#"
-            DELETE FROM references;
+            DELETE FROM symbol_references;
+            DELETE FROM pending_references;
             DELETE FROM symbols;
             DELETE FROM files;
             "#,
@@ -350,6 +1382,15 @@ impl std::str::FromStr for Language {
     }
 }
 
+/// Change-detection data stored for each indexed file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileFingerprint {
+    pub file_size: u64,
+    pub hash: String,
+    /// Modification time in nanoseconds since the Unix epoch
+    pub mtime: Option<i64>,
+}
+
 /// Symbol data from database
 #[derive(Debug, Clone)]
 pub struct SymbolRow {
@@ -366,6 +1407,15 @@ pub struct SymbolRow {
     pub end_offset: usize,
     pub signature: Option<String>,
     pub documentation: Option<String>,
+    /// Annotations such as `@Override`, one per line
+    pub annotations: Option<String>,
+    /// Label text of an enumeration value
+    pub label: Option<String>,
+    /// `Public` or `Private` for views and view columns
+    pub visibility: Option<String>,
+    pub parent_id: Option<i64>,
+    /// The enclosing symbol, with its own parent attached
+    pub parent: Option<Box<SymbolRow>>,
 }
 
 impl SymbolRow {
@@ -384,6 +1434,11 @@ impl SymbolRow {
             end_offset: row.get::<_, i64>(10)? as usize,
             signature: row.get(11)?,
             documentation: row.get(12)?,
+            annotations: row.get(13)?,
+            label: row.get(14)?,
+            visibility: row.get(15)?,
+            parent_id: row.get(16)?,
+            parent: None,
         })
     }
 }
@@ -421,3 +1476,207 @@ impl ReferenceRow {
         })
     }
 }
+
+/// A reference queued until its target symbol is indexed
+#[derive(Debug, Clone)]
+pub struct PendingReferenceRow {
+    pub id: i64,
+    pub file_id: i64,
+    /// Normalized name of the referenced symbol
+    pub name: String,
+    /// Normalized name of the symbol's parent, for qualified references
+    pub qualifier: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_new_database_records_schema_version() {
+        let db = Database::in_memory().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+    }
+    
+    #[test]
+    fn test_open_migrates_unversioned_schema_without_data_loss() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("old.db");
+        
+        // Schema as it was before the mtime column and versioning were added
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE files (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT UNIQUE NOT NULL,
+                language TEXT NOT NULL,
+                indexed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                file_size INTEGER,
+                hash TEXT
+            );
+            CREATE TABLE symbols (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                file_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                start_column INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                end_column INTEGER NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                parent_id INTEGER,
+                signature TEXT,
+                documentation TEXT
+            );
+            INSERT INTO files (path, language) VALUES ('Customer.plsql', 'PlSql');
+            INSERT INTO symbols (file_id, name, kind, start_line, start_column, end_line, end_column, start_offset, end_offset)
+                VALUES (1, 'Customer_API', 'Package', 1, 1, 1, 20, 0, 19);
+            INSERT INTO symbols (file_id, name, kind, start_line, start_column, end_line, end_column, start_offset, end_offset, documentation)
+                VALUES (1, 'Planned', 'EnumerationValue', 2, 1, 2, 8, 20, 27, 'Planned for Delivery');
+            "#,
+        )
+        .unwrap();
+        drop(conn);
+        
+        let mut db = Database::new(&db_path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        
+        let symbols = db.search_symbols("Customer_API").unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].file_path, "Customer.plsql");
+        assert_eq!(db.resolve_symbol("CUSTOMER_API", None, 1).unwrap(), Some(symbols[0].id));
+        
+        // Enumeration value labels move out of the documentation column
+        let values = db.search_symbols_by_label("delivery", "EnumerationValue").unwrap();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].label.as_deref(), Some("Planned for Delivery"));
+        assert_eq!(values[0].documentation, None);
+        
+        let fingerprint = FileFingerprint {
+            file_size: 19,
+            hash: "abc".to_string(),
+            mtime: Some(42),
+        };
+        db.store_file_fingerprint("Customer.plsql", &fingerprint).unwrap();
+        assert_eq!(db.get_file_fingerprint("Customer.plsql").unwrap(), Some(fingerprint));
+        
+        // Reopening an up-to-date database is a no-op
+        drop(db);
+        let db = Database::new(&db_path).unwrap();
+        assert_eq!(db.search_symbols("Customer_API").unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_open_rejects_newer_schema() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("future.db");
+        
+        let conn = Connection::open(&db_path).unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        drop(conn);
+        
+        assert!(Database::new(&db_path).is_err());
+    }
+    
+    #[test]
+    fn test_open_unversioned_schema_that_already_has_new_columns() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("old.db");
+        
+        // Written by a build that added mtime before the schema was versioned
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE files (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT UNIQUE NOT NULL,
+                language TEXT NOT NULL,
+                indexed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                file_size INTEGER,
+                hash TEXT,
+                mtime INTEGER
+            );
+            CREATE TABLE symbols (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                file_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                start_column INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                end_column INTEGER NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                parent_id INTEGER,
+                signature TEXT,
+                documentation TEXT
+            );
+            INSERT INTO files (path, language, mtime) VALUES ('Customer.plsql', 'PlSql', 7);
+            "#,
+        )
+        .unwrap();
+        drop(conn);
+        
+        let db = Database::new(&db_path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        let path: String = db.conn.query_row("SELECT path FROM files", [], |row| row.get(0)).unwrap();
+        assert_eq!(path, "Customer.plsql");
+        let columns = table_columns(&db.conn, "files").unwrap();
+        assert_eq!(columns.iter().filter(|column| *column == "mtime").count(), 1);
+    }
+    
+    #[test]
+    fn test_open_renames_references_table_keeping_its_rows() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("old.db");
+        
+        // A version 7 database, which stored references in a quoted `references` table
+        let mut db = Database::new(&db_path).unwrap();
+        let file_id = db.store_file("Order.plsql", Language::PlSql).unwrap();
+        let symbol_id = db.store_symbol(file_id, "Order_API", "Package", 1, 1, 1, 10, 0, 9, None, None, None).unwrap();
+        db.store_reference(symbol_id, file_id, 2, 1, 2, 10, 20, 29, "Call", None).unwrap();
+        db.conn.execute_batch(r#"ALTER TABLE symbol_references RENAME TO "references""#).unwrap();
+        db.conn.pragma_update(None, "user_version", 7).unwrap();
+        drop(db);
+        
+        let db = Database::new(&db_path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        assert!(!db.table_exists("references").unwrap());
+        assert_eq!(db.find_references(symbol_id).unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_health_check_detects_and_repair_removes_orphans() {
+        let mut db = Database::in_memory().unwrap();
+        let kept = db.store_file("Order.plsql", Language::PlSql).unwrap();
+        let removed = db.store_file("Customer.plsql", Language::PlSql).unwrap();
+        let order = db.store_symbol(kept, "Order_API", "Package", 1, 1, 1, 10, 0, 9, None, None, None).unwrap();
+        db.store_symbol(removed, "Customer_API", "Package", 1, 1, 1, 13, 0, 12, None, None, None).unwrap();
+        db.store_reference(order, removed, 2, 1, 2, 10, 20, 29, "Call", None).unwrap();
+        let span = Span::from([3, 1, 3, 10, 30, 39]);
+        db.store_pending_reference(removed, "MISSING_API", None, &span, "Call", None).unwrap();
+        db.store_pending_reference(kept, "MISSING_API", None, &span, "Call", None).unwrap();
+        assert!(db.health_check().unwrap().is_healthy());
+        
+        // Delete the file row the way a writer without foreign key enforcement
+        // would, e.g. the sqlite3 shell, so the cascade does not run
+        db.conn.execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+        db.conn.execute("DELETE FROM files WHERE id = ?1", params![removed]).unwrap();
+        db.conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        
+        let report = db.health_check().unwrap();
+        assert_eq!(report.orphaned_symbols, 1);
+        assert_eq!(report.orphaned_references, 1);
+        assert_eq!(report.orphaned_pending_references, 1);
+        assert!(report.foreign_key_violations >= 1);
+        assert!(!report.is_healthy());
+        
+        assert_eq!(db.repair().unwrap(), 3);
+        assert!(db.health_check().unwrap().is_healthy());
+        assert_eq!(db.search_symbols("Order_API").unwrap().len(), 1);
+        let pending: i64 = db.conn.query_row("SELECT COUNT(*) FROM pending_references", [], |row| row.get(0)).unwrap();
+        assert_eq!(pending, 1);
+    }
+}
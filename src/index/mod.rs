@@ -13,10 +13,18 @@ pub mod symbols;
 pub use database::*;
 pub use search::*;
 pub use symbols::*;
+pub use crate::parser::CancellationToken;
 
-use crate::parser::{ast::*, Language};
+use crate::parser::{ast::*, parse_source_with_options, Language, ParseError, ParseOptions};
+use crate::utils::file_utils::{
+    content_hash, find_files, get_file_size, get_modified_nanos, read_source,
+};
+use crate::refactor::TextEdit;
 use crate::Result;
-use std::path::Path;
+use std::collections::{BTreeSet, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// The main index for storing parsed information
 pub struct Index {
@@ -36,11 +44,31 @@ impl Index {
         Ok(Self { database })
     }
 
+    /// Load an index file saved with [`Index::backup_to`] into memory
+    pub fn restore_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let database = Database::restore_from(path)?;
+        Ok(Self { database })
+    }
+
+    /// Save the index to a file so it can be restored later
+    pub fn backup_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.database.backup_to(path)
+    }
+
+    /// Merge another index into this one, e.g. one built over a separate shard
+    /// of the codebase. Files already indexed here keep their existing data.
+    pub fn merge_from(&mut self, other: &Index) -> Result<()> {
+        self.database.merge_from(&other.database)
+    }
+
     /// Index a parsed AST from a file
     pub fn index_file<P: AsRef<Path>>(&mut self, file_path: P, ast: &AstNode) -> Result<()> {
         let file_path = file_path.as_ref();
         let language = detect_language_from_path(file_path);
 
+        // Replace anything indexed for this file before
+        let requeued = self.database.remove_file(file_path)?;
+
         // Store file metadata
         self.database.store_file(file_path, language)?;
 
@@ -48,21 +76,260 @@ impl Index {
         let mut symbol_indexer = SymbolIndexer::new(&mut self.database);
         symbol_indexer.index_ast(file_path, ast)?;
 
+        // Relink references from other files that were queued when the old version was removed
+        for pending in requeued {
+            let qualifier = pending.qualifier.as_deref();
+            if let Some(symbol_id) = self.database.resolve_symbol(&pending.name, qualifier, pending.file_id)? {
+                self.database.resolve_pending_reference(pending.id, symbol_id)?;
+            }
+        }
+
         Ok(())
     }
 
+    /// Parse and index every supported source file under a directory
+    pub fn index_directory<P: AsRef<Path>>(
+        &mut self,
+        root: P,
+        options: &IndexOptions,
+    ) -> Result<DirectoryIndexSummary> {
+        self.index_directory_with(root.as_ref(), options, false, &mut |_| {})
+    }
+
+    /// Like `index_directory`, but skip files that have not changed since they were last indexed.
+    /// Files are unchanged when their modification time matches the stored one; when the
+    /// modification time is missing or too recent to trust, the content hash is compared instead.
+    pub fn index_directory_incremental<P: AsRef<Path>>(
+        &mut self,
+        root: P,
+        options: &IndexOptions,
+    ) -> Result<DirectoryIndexSummary> {
+        self.index_directory_with(root.as_ref(), options, true, &mut |_| {})
+    }
+
+    fn index_directory_with(
+        &mut self,
+        root: &Path,
+        options: &IndexOptions,
+        incremental: bool,
+        on_result: &mut dyn FnMut(&FileIndexResult),
+    ) -> Result<DirectoryIndexSummary> {
+        let extensions: Vec<&str> = Language::all()
+            .iter()
+            .flat_map(|language| language.extensions().iter().copied())
+            .collect();
+        let files = find_files(root, &extensions)?;
+
+        let mut summary = DirectoryIndexSummary::default();
+        for path in files {
+            // Files already indexed stay committed; only new work is abandoned
+            if options.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                return Err(IndexError::Cancelled.into());
+            }
+
+            let status = self.index_directory_file(&path, options, incremental);
+            let result = FileIndexResult { path, status };
+            on_result(&result);
+            summary.results.push(result);
+        }
+
+        // Calls into files indexed later in the run can be linked now
+        let resolved = self.resolve_pending_references()?;
+        log::debug!("Resolved {} deferred references", resolved);
+
+        Ok(summary)
+    }
+
+    fn index_directory_file(
+        &mut self,
+        path: &Path,
+        options: &IndexOptions,
+        incremental: bool,
+    ) -> FileIndexStatus {
+        if let Some(max_file_bytes) = options.max_file_bytes {
+            match get_file_size(path) {
+                Ok(size) if size > max_file_bytes => {
+                    log::warn!(
+                        "Skipping {}: {} bytes exceeds limit of {} bytes",
+                        path.display(),
+                        size,
+                        max_file_bytes
+                    );
+                    return FileIndexStatus::Skipped {
+                        reason: format!("file size {} exceeds limit {}", size, max_file_bytes),
+                    };
+                }
+                Ok(_) => {}
+                Err(e) => return FileIndexStatus::Failed { error: e.to_string() },
+            }
+        }
+
+        let mtime = get_modified_nanos(path);
+        if incremental && mtime.is_some() && self.mtime_unchanged(path, mtime) {
+            return FileIndexStatus::Unchanged;
+        }
+
+        let content = match read_source(path) {
+            Ok(content) => content,
+            Err(e) => return FileIndexStatus::Failed { error: e.to_string() },
+        };
+        let fingerprint = FileFingerprint {
+            file_size: content.len() as u64,
+            hash: content_hash(content.as_bytes()),
+            mtime,
+        };
+
+        if incremental && self.hash_unchanged(path, &fingerprint) {
+            // Record the new modification time so the next run can skip on mtime alone
+            return match self.database.store_file_fingerprint(path, &fingerprint) {
+                Ok(()) => FileIndexStatus::Unchanged,
+                Err(e) => FileIndexStatus::Failed { error: e.to_string() },
+            };
+        }
+
+        let language = detect_language_from_path(path);
+        let mut parse_options = if options.outline_only {
+            ParseOptions::outline()
+        } else {
+            ParseOptions::default()
+        };
+        if let Some(timeout) = options.parse_timeout {
+            parse_options = parse_options.with_timeout(timeout);
+        }
+        let parsed = parse_source_with_options(&content, language, parse_options);
+        if let (Some(timeout), Err(e)) = (options.parse_timeout, &parsed) {
+            if matches!(e.downcast_ref::<ParseError>(), Some(ParseError::TimedOut)) {
+                log::warn!("Abandoned parse of {} after {:?}", path.display(), timeout);
+                return FileIndexStatus::TimedOut { after: timeout };
+            }
+        }
+        let result = parsed
+            .and_then(|ast| self.index_file(path, &ast))
+            .and_then(|()| self.database.store_file_fingerprint(path, &fingerprint));
+
+        match result {
+            Ok(()) => FileIndexStatus::Indexed,
+            Err(e) => FileIndexStatus::Failed { error: e.to_string() },
+        }
+    }
+
+    /// Compare the files of `languages` under `root` with the index, without writing.
+    /// Files count as changed when their size or content hash differs from the stored one.
+    pub fn plan_directory<P: AsRef<Path>>(&self, root: P, languages: &[Language]) -> Result<IndexPlan> {
+        let root = root.as_ref();
+        let extensions: Vec<&str> = languages
+            .iter()
+            .flat_map(|language| language.extensions().iter().copied())
+            .collect();
+        let files = find_files(root, &extensions)?;
+
+        let mut plan = IndexPlan::default();
+        for path in &files {
+            if self.database.get_file_id(path)?.is_none() {
+                plan.added.push(path.clone());
+                continue;
+            }
+            let content = read_source(path)?;
+            let fingerprint = FileFingerprint {
+                file_size: content.len() as u64,
+                hash: content_hash(content.as_bytes()),
+                mtime: None,
+            };
+            if self.hash_unchanged(path, &fingerprint) {
+                plan.unchanged.push(path.clone());
+            } else {
+                plan.changed.push(path.clone());
+            }
+        }
+
+        let on_disk: HashSet<&PathBuf> = files.iter().collect();
+        plan.deleted = self
+            .database
+            .get_file_paths()?
+            .into_iter()
+            .map(PathBuf::from)
+            .filter(|path| path.starts_with(root) && !on_disk.contains(path))
+            .filter(|path| Language::from_path(path).is_some_and(|language| languages.contains(&language)))
+            .collect();
+
+        Ok(plan)
+    }
+
+    /// Whether the stored modification time matches and is old enough to be trusted
+    fn mtime_unchanged(&self, path: &Path, mtime: Option<i64>) -> bool {
+        let Some(mtime) = mtime else {
+            return false;
+        };
+        // Writes within the filesystem's timestamp granularity can keep the same mtime
+        let now = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(i64::MAX);
+        if now.saturating_sub(mtime) < MTIME_TRUST_WINDOW_NANOS {
+            return false;
+        }
+
+        matches!(
+            self.database.get_file_fingerprint(path),
+            Ok(Some(stored)) if stored.mtime == Some(mtime)
+        )
+    }
+
+    fn hash_unchanged(&self, path: &Path, fingerprint: &FileFingerprint) -> bool {
+        matches!(
+            self.database.get_file_fingerprint(path),
+            Ok(Some(stored)) if stored.hash == fingerprint.hash && stored.file_size == fingerprint.file_size
+        )
+    }
+
+    /// Searcher over the indexed symbols, for callers that run several queries
+    pub fn searcher(&self) -> SymbolSearcher<'_> {
+        SymbolSearcher::new(&self.database)
+    }
+
     /// Search for symbols by name
     pub fn search_symbols(&self, query: &str) -> Result<Vec<SymbolInfo>> {
         let searcher = SymbolSearcher::new(&self.database);
         searcher.search_by_name(query)
     }
 
+    /// Search for symbols by name one page at a time, returning the page and the total
+    /// number of matches so a UI can render paging controls
+    pub fn search_symbols_paged(&self, query: &str, limit: usize, offset: usize) -> Result<(Vec<SymbolInfo>, usize)> {
+        let (rows, total) = self.database.search_symbols_paged(query, limit, offset)?;
+        Ok((rows.into_iter().map(SymbolInfo::from).collect(), total))
+    }
+
+    /// Suggest up to `limit` symbols whose name starts with `prefix`, ordered by name.
+    /// Unlike `search_symbols`, which matches substrings, this is a prefix match
+    /// backed by the normalized name index.
+    pub fn autocomplete(&self, prefix: &str, kinds: Option<&[SymbolKind]>, limit: usize) -> Result<Vec<SymbolInfo>> {
+        let searcher = SymbolSearcher::new(&self.database);
+        searcher.autocomplete(prefix, kinds, limit)
+    }
+
     /// Find all references to a symbol
     pub fn find_references(&self, symbol: &SymbolInfo) -> Result<Vec<SymbolReference>> {
         let searcher = SymbolSearcher::new(&self.database);
         searcher.find_references(symbol)
     }
 
+    /// Find the implementation linked to a forward declaration
+    pub fn find_implementation(&self, declaration: &SymbolInfo) -> Result<Option<SymbolInfo>> {
+        let searcher = SymbolSearcher::new(&self.database);
+        searcher.find_implementation(declaration)
+    }
+
+    /// Resolve a `%TYPE` or `%ROWTYPE` reference to the indexed storage column or table
+    pub fn resolve_type_reference(&self, type_ref: &str) -> Result<Option<SymbolInfo>> {
+        let searcher = SymbolSearcher::new(&self.database);
+        searcher.resolve_type_reference(type_ref)
+    }
+
+    /// Compute the edits that rename a symbol and all of its stored references.
+    /// No files are modified.
+    pub fn rename_symbol(&self, symbol: &SymbolInfo, new_name: &str) -> Result<Vec<(PathBuf, Vec<TextEdit>)>> {
+        let searcher = SymbolSearcher::new(&self.database);
+        crate::refactor::rename_edits(&searcher, symbol, new_name)
+    }
+
     /// Find the definition of a symbol at a specific position
     pub fn find_definition(
         &self,
@@ -74,33 +341,260 @@ impl Index {
         searcher.find_definition_at_position(file_path, line, column)
     }
 
+    /// Resolve a possibly qualified name such as `Customer_API.Get_Name` as seen from
+    /// `from_file`, the same way references are resolved while indexing
+    pub fn resolve_name(&self, name: &str, from_file: &Path) -> Result<Option<SymbolInfo>> {
+        let normalized = normalize_identifier(name);
+        let (qualifier, target) = match normalized.rsplit_once('.') {
+            Some((qualifier, target)) => (qualifier.rsplit('.').next(), target),
+            None => (None, normalized.as_str()),
+        };
+        let file_id = self.database.get_file_id(from_file)?.unwrap_or(-1);
+
+        let Some(symbol_id) = self.database.resolve_symbol(target, qualifier, file_id)? else {
+            return Ok(None);
+        };
+        Ok(self.database.get_symbol(symbol_id)?.map(SymbolInfo::from))
+    }
+
+    /// Link references queued while their target was not indexed yet, e.g. calls into
+    /// a file indexed later in the same run. Returns the number of references resolved.
+    pub fn resolve_pending_references(&mut self) -> Result<usize> {
+        let mut resolved = 0;
+        for pending in self.database.get_pending_references()? {
+            let qualifier = pending.qualifier.as_deref();
+            if let Some(symbol_id) = self.database.resolve_symbol(&pending.name, qualifier, pending.file_id)? {
+                self.database.resolve_pending_reference(pending.id, symbol_id)?;
+                resolved += 1;
+            }
+        }
+        Ok(resolved)
+    }
+
     /// Get all symbols in a file
     pub fn get_file_symbols(&self, file_path: &Path) -> Result<Vec<SymbolInfo>> {
         let searcher = SymbolSearcher::new(&self.database);
         searcher.get_symbols_in_file(file_path)
     }
 
+    /// Get the calls between subprograms as `(caller, callee)` pairs of qualified names
+    pub fn call_graph(&self) -> Result<Vec<(String, String)>> {
+        self.database.get_call_edges()
+    }
+
+    /// Write the call graph as a Graphviz DOT digraph with one node per calling or called
+    /// subprogram. With `filter`, only calls from or to subprograms whose qualified name
+    /// starts with that prefix, ignoring case, are written.
+    pub fn export_call_graph_dot(&self, mut writer: impl Write, filter: Option<&str>) -> Result<()> {
+        let matches_filter = |name: &str| match filter {
+            Some(prefix) => name.get(..prefix.len()).is_some_and(|head| head.eq_ignore_ascii_case(prefix)),
+            None => true,
+        };
+        let edges: Vec<(String, String)> = self
+            .call_graph()?
+            .into_iter()
+            .filter(|(caller, callee)| matches_filter(caller) || matches_filter(callee))
+            .collect();
+        let nodes: BTreeSet<&str> = edges
+            .iter()
+            .flat_map(|(caller, callee)| [caller.as_str(), callee.as_str()])
+            .collect();
+
+        writeln!(writer, "digraph calls {{")?;
+        writeln!(writer, "    node [shape=box];")?;
+        for node in nodes {
+            writeln!(writer, "    {};", dot_id(node))?;
+        }
+        for (caller, callee) in &edges {
+            writeln!(writer, "    {} -> {};", dot_id(caller), dot_id(callee))?;
+        }
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+
     /// Get file statistics
     pub fn get_statistics(&self) -> Result<IndexStatistics> {
         self.database.get_statistics()
     }
 
+    /// Get the number of symbols in each indexed file, largest first
+    pub fn symbol_counts_per_file(&self) -> Result<Vec<(String, usize)>> {
+        self.database.stats_by_file()
+    }
+
+    /// Get file and symbol counts per IFS module, using the standard
+    /// `<module>/source/<module>/database/...` layout to find the module
+    pub fn module_report(&self) -> Result<Vec<ModuleStat>> {
+        self.module_report_with(ifs_module_from_path)
+    }
+
+    /// Get file and symbol counts per module using a custom module extractor.
+    /// Files for which `extract_module` returns `None` are grouped under `unknown`.
+    pub fn module_report_with<F>(&self, extract_module: F) -> Result<Vec<ModuleStat>>
+    where
+        F: Fn(&Path) -> Option<String>,
+    {
+        let mut modules: std::collections::BTreeMap<String, ModuleStat> =
+            std::collections::BTreeMap::new();
+        for (path, symbol_count) in self.database.symbol_counts_for_all_files()? {
+            let module = extract_module(Path::new(&path)).unwrap_or_else(|| "unknown".to_string());
+            let stat = modules.entry(module.clone()).or_insert_with(|| ModuleStat {
+                module,
+                file_count: 0,
+                symbol_count: 0,
+            });
+            stat.file_count += 1;
+            stat.symbol_count += symbol_count;
+        }
+        Ok(modules.into_values().collect())
+    }
+
+    /// Check the index for orphaned symbols and references
+    pub fn health_check(&self) -> Result<HealthReport> {
+        self.database.health_check()
+    }
+
+    /// Delete orphaned symbols and references, returning the number of rows removed
+    pub fn repair(&mut self) -> Result<usize> {
+        self.database.repair()
+    }
+
     /// Clear all indexed data
     pub fn clear(&mut self) -> Result<()> {
         self.database.clear_all()
     }
 }
 
+/// Quote a name as a DOT identifier, which Graphviz also uses as the node label
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Extract the module from an IFS source path such as
+/// `.../order/source/order/database/CustomerOrder.plsql`
+pub fn ifs_module_from_path(path: &Path) -> Option<String> {
+    let components: Vec<&str> = path
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect();
+    components
+        .windows(3)
+        .find(|window| window[0] == "source" && window[2] == "database")
+        .map(|window| window[1].to_string())
+}
+
 /// Detect language from file path
 fn detect_language_from_path(path: &Path) -> Language {
-    if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
-        let ext_with_dot = format!(".{}", extension);
-        Language::from_extension(&ext_with_dot).unwrap_or(Language::PlSql)
-    } else {
-        Language::PlSql
+    Language::from_path(path).unwrap_or(Language::PlSql)
+}
+
+/// Modification times more recent than this are verified by content hash
+const MTIME_TRUST_WINDOW_NANOS: i64 = 2_000_000_000;
+
+/// Options controlling a directory indexing run
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {
+    /// Files larger than this many bytes are skipped instead of parsed
+    pub max_file_bytes: Option<u64>,
+    /// Parse only signatures and declarations, skipping statement bodies.
+    /// Faster, but references made from inside bodies are not recorded.
+    pub outline_only: bool,
+    /// Stops the run before the next file once cancelled
+    pub cancellation: Option<CancellationToken>,
+    /// Abandon a file whose parse takes longer than this and move on to the next one
+    pub parse_timeout: Option<Duration>,
+}
+
+/// Errors that end a directory indexing run early
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("Indexing was cancelled")]
+    Cancelled,
+}
+
+/// Outcome of indexing a single file
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileIndexStatus {
+    Indexed,
+    Unchanged,
+    Failed { error: String },
+    Skipped { reason: String },
+    /// The parse was abandoned after exceeding `IndexOptions::parse_timeout`
+    TimedOut { after: Duration },
+}
+
+/// Per-file result of a directory indexing run
+#[derive(Debug, Clone)]
+pub struct FileIndexResult {
+    pub path: PathBuf,
+    pub status: FileIndexStatus,
+}
+
+/// Summary of a directory indexing run
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryIndexSummary {
+    pub results: Vec<FileIndexResult>,
+}
+
+impl DirectoryIndexSummary {
+    /// Number of files that were parsed and indexed
+    pub fn indexed_count(&self) -> usize {
+        self.count(|status| matches!(status, FileIndexStatus::Indexed))
+    }
+
+    /// Number of files skipped by an incremental run because they had not changed
+    pub fn unchanged_count(&self) -> usize {
+        self.count(|status| matches!(status, FileIndexStatus::Unchanged))
+    }
+
+    /// Number of files that failed to read, parse or index
+    pub fn failed_count(&self) -> usize {
+        self.count(|status| matches!(status, FileIndexStatus::Failed { .. }))
+    }
+
+    /// Number of files that were skipped without being parsed
+    pub fn skipped_count(&self) -> usize {
+        self.count(|status| matches!(status, FileIndexStatus::Skipped { .. }))
+    }
+
+    /// Number of files whose parse was abandoned after the timeout
+    pub fn timed_out_count(&self) -> usize {
+        self.count(|status| matches!(status, FileIndexStatus::TimedOut { .. }))
+    }
+
+    fn count<F: Fn(&FileIndexStatus) -> bool>(&self, predicate: F) -> usize {
+        self.results.iter().filter(|r| predicate(&r.status)).count()
     }
 }
 
+/// What re-indexing a directory would do, as computed by `Index::plan_directory`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexPlan {
+    /// Files on disk that are not in the index
+    pub added: Vec<PathBuf>,
+    /// Indexed files whose content differs from the stored fingerprint
+    pub changed: Vec<PathBuf>,
+    /// Indexed files whose content matches the stored fingerprint
+    pub unchanged: Vec<PathBuf>,
+    /// Indexed files that no longer exist on disk
+    pub deleted: Vec<PathBuf>,
+}
+
+impl IndexPlan {
+    /// Whether re-indexing would add, update or remove anything
+    pub fn has_changes(&self) -> bool {
+        !(self.added.is_empty() && self.changed.is_empty() && self.deleted.is_empty())
+    }
+}
+
+/// File and symbol counts for one IFS module
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleStat {
+    pub module: String,
+    pub file_count: usize,
+    pub symbol_count: usize,
+}
+
 /// Statistics about the index
 #[derive(Debug, Clone)]
 pub struct IndexStatistics {
@@ -110,9 +604,30 @@ pub struct IndexStatistics {
     pub symbols_by_language: std::collections::HashMap<Language, usize>,
 }
 
+/// Integrity problems found by `Index::health_check`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthReport {
+    /// Rows reported by `PRAGMA foreign_key_check`
+    pub foreign_key_violations: usize,
+    /// Symbols whose file no longer exists
+    pub orphaned_symbols: usize,
+    /// References whose symbol or file no longer exists
+    pub orphaned_references: usize,
+    /// Queued references whose file no longer exists
+    pub orphaned_pending_references: usize,
+}
+
+impl HealthReport {
+    /// Whether no problems were found
+    pub fn is_healthy(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::parse_source;
 
     #[test]
     fn test_index_creation() {
@@ -135,4 +650,496 @@ mod tests {
             Language::MarbleProjection
         );
     }
+
+    fn enumeration_ast(name: &str, values: &[&str]) -> AstNode {
+        let span = Span {
+            start: Position { line: 1, column: 1, offset: 0 },
+            end: Position { line: 1, column: 1, offset: 0 },
+        };
+        let identifier = |name: &str| Identifier {
+            name: name.to_string(),
+            span: span.clone(),
+        };
+        AstNode::Enumeration(EnumerationNode {
+            enumeration_name: identifier(name),
+            component: "ORDER".to_string(),
+            values: values
+                .iter()
+                .map(|value| EnumerationValue {
+                    name: identifier(value),
+                    client_value: None,
+                    label_text: None,
+                    properties: std::collections::HashMap::new(),
+                    span: span.clone(),
+                })
+                .collect(),
+            span: span.clone(),
+        })
+    }
+
+    #[test]
+    fn test_symbol_counts_per_file() {
+        let mut index = Index::in_memory().unwrap();
+        index
+            .index_file("OrderStatus.enumeration", &enumeration_ast("OrderStatus", &["Planned"]))
+            .unwrap();
+        index
+            .index_file(
+                "LineStatus.enumeration",
+                &enumeration_ast("LineStatus", &["Planned", "Released", "Closed"]),
+            )
+            .unwrap();
+
+        let counts = index.symbol_counts_per_file().unwrap();
+        assert_eq!(
+            counts,
+            vec![
+                ("LineStatus.enumeration".to_string(), 4),
+                ("OrderStatus.enumeration".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_report_groups_by_module() {
+        let mut index = Index::in_memory().unwrap();
+        index
+            .index_file(
+                "ifs/order/source/order/database/OrderStatus.enumeration",
+                &enumeration_ast("OrderStatus", &["Planned"]),
+            )
+            .unwrap();
+        index
+            .index_file(
+                "ifs/order/source/order/database/LineStatus.enumeration",
+                &enumeration_ast("LineStatus", &["Planned", "Closed"]),
+            )
+            .unwrap();
+        index
+            .index_file(
+                "ifs/invent/source/invent/database/PartStatus.enumeration",
+                &enumeration_ast("PartStatus", &["Active"]),
+            )
+            .unwrap();
+
+        let report = index.module_report().unwrap();
+        assert_eq!(
+            report,
+            vec![
+                ModuleStat { module: "invent".to_string(), file_count: 1, symbol_count: 2 },
+                ModuleStat { module: "order".to_string(), file_count: 2, symbol_count: 5 },
+            ]
+        );
+
+        let flat = index
+            .module_report_with(|path| path.iter().nth(1).map(|c| c.to_string_lossy().to_uppercase()))
+            .unwrap();
+        let modules: Vec<&str> = flat.iter().map(|stat| stat.module.as_str()).collect();
+        assert_eq!(modules, vec!["INVENT", "ORDER"]);
+    }
+
+    #[test]
+    fn test_merge_from_combines_shards() {
+        let mut first = Index::in_memory().unwrap();
+        first
+            .index_file("OrderStatus.enumeration", &enumeration_ast("OrderStatus", &["Planned"]))
+            .unwrap();
+        first
+            .index_file("LineStatus.enumeration", &enumeration_ast("LineStatus", &["Open", "Closed"]))
+            .unwrap();
+
+        let mut second = Index::in_memory().unwrap();
+        second
+            .index_file("PartStatus.enumeration", &enumeration_ast("PartStatus", &["Active"]))
+            .unwrap();
+        // Already in the first shard, so it is skipped
+        second
+            .index_file("OrderStatus.enumeration", &enumeration_ast("OrderStatus", &["Planned", "Closed"]))
+            .unwrap();
+
+        first.merge_from(&second).unwrap();
+
+        let statistics = first.get_statistics().unwrap();
+        assert_eq!(statistics.total_files, 3);
+        assert_eq!(statistics.total_symbols, 7);
+        assert_eq!(
+            first.symbol_counts_per_file().unwrap(),
+            vec![
+                ("LineStatus.enumeration".to_string(), 3),
+                ("OrderStatus.enumeration".to_string(), 2),
+                ("PartStatus.enumeration".to_string(), 2),
+            ]
+        );
+
+        let part_status = &first.database.search_symbols("PartStatus").unwrap()[0];
+        let children = first.database.get_child_symbols(part_status.id).unwrap();
+        let names: Vec<&str> = children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, vec!["Active"]);
+        assert!(first.health_check().unwrap().is_healthy());
+    }
+
+    #[test]
+    fn test_backup_and_restore_keeps_symbols() {
+        let mut index = Index::in_memory().unwrap();
+        index
+            .index_file("OrderStatus.enumeration", &enumeration_ast("OrderStatus", &["Planned"]))
+            .unwrap();
+        index
+            .index_file("LineStatus.enumeration", &enumeration_ast("LineStatus", &["Open", "Closed"]))
+            .unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let backup_path = temp_dir.path().join("index.db");
+        index.backup_to(&backup_path).unwrap();
+
+        let restored = Index::restore_from(&backup_path).unwrap();
+        let original = index.get_statistics().unwrap();
+        let copy = restored.get_statistics().unwrap();
+        assert_eq!(copy.total_files, original.total_files);
+        assert_eq!(copy.total_symbols, original.total_symbols);
+        assert_eq!(
+            restored.symbol_counts_per_file().unwrap(),
+            index.symbol_counts_per_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_incremental_index_skips_unchanged_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("unchanged.plsql");
+        std::fs::write(&file_path, "PROCEDURE Unchanged_Proc IS BEGIN NULL; END;").unwrap();
+
+        // Backdate the file so its modification time is trusted
+        let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&file_path)
+            .unwrap()
+            .set_modified(an_hour_ago)
+            .unwrap();
+
+        let mut index = Index::in_memory().unwrap();
+        let options = IndexOptions::default();
+
+        let first = index.index_directory_incremental(temp_dir.path(), &options).unwrap();
+        assert_eq!(first.indexed_count(), 1);
+        assert_eq!(first.unchanged_count(), 0);
+
+        let second = index.index_directory_incremental(temp_dir.path(), &options).unwrap();
+        assert_eq!(second.indexed_count(), 0);
+        assert_eq!(second.unchanged_count(), 1);
+        assert_eq!(index.get_statistics().unwrap().total_symbols, 1);
+
+        std::fs::write(&file_path, "PROCEDURE Changed_Proc IS BEGIN NULL; END;").unwrap();
+        let third = index.index_directory_incremental(temp_dir.path(), &options).unwrap();
+        assert_eq!(third.indexed_count(), 1);
+        assert_eq!(index.search_symbols("Changed_Proc").unwrap().len(), 1);
+        assert!(index.search_symbols("Unchanged_Proc").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_index_keeps_committed_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for name in ["First_API", "Second_API", "Third_API"] {
+            std::fs::write(
+                temp_dir.path().join(format!("{}.plsql", name.to_lowercase())),
+                format!("PROCEDURE {} IS BEGIN NULL; END;", name),
+            )
+            .unwrap();
+        }
+
+        let token = CancellationToken::new();
+        let options = IndexOptions {
+            cancellation: Some(token.clone()),
+            ..Default::default()
+        };
+        let mut index = Index::in_memory().unwrap();
+
+        let mut seen = Vec::new();
+        let error = index
+            .index_directory_with(temp_dir.path(), &options, false, &mut |result| {
+                seen.push(result.path.clone());
+                token.cancel();
+            })
+            .unwrap_err();
+
+        assert!(matches!(error.downcast_ref::<IndexError>(), Some(IndexError::Cancelled)));
+        assert_eq!(seen.len(), 1);
+
+        // Only the first file is in the index, and it is complete
+        let stats = index.get_statistics().unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.total_symbols, 1);
+        let counts = index.symbol_counts_per_file().unwrap();
+        assert_eq!(counts, vec![(seen[0].to_string_lossy().to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_index_directory_skips_oversized_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("small.plsql"),
+            "PROCEDURE Small_Proc IS BEGIN NULL; END;",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("large.plsql"),
+            format!("PROCEDURE Large_Proc IS BEGIN NULL; END;\n{}", "-- padding\n".repeat(100)),
+        )
+        .unwrap();
+
+        let mut index = Index::in_memory().unwrap();
+        let options = IndexOptions {
+            max_file_bytes: Some(100),
+            ..Default::default()
+        };
+        let summary = index.index_directory(temp_dir.path(), &options).unwrap();
+
+        assert_eq!(summary.indexed_count(), 1);
+        assert_eq!(summary.skipped_count(), 1);
+        assert_eq!(summary.failed_count(), 0);
+
+        let skipped = summary
+            .results
+            .iter()
+            .find(|r| matches!(r.status, FileIndexStatus::Skipped { .. }))
+            .unwrap();
+        assert!(skipped.path.ends_with("large.plsql"));
+    }
+
+    #[test]
+    fn test_autocomplete_matches_prefix_only() {
+        let mut index = Index::in_memory().unwrap();
+        for (path, source, language) in [
+            ("CustomerApi.plsql", "PACKAGE Customer_API IS END;", Language::PlSql),
+            ("OrderApi.plsql", "PACKAGE Order_API IS END;", Language::PlSql),
+            ("Customer.views", "VIEW Customer_Tab IS\nSELECT name FROM customer_info_tab;\n", Language::Views),
+        ] {
+            index.index_file(path, &parse_source(source, language).unwrap()).unwrap();
+        }
+
+        let names = |symbols: Vec<SymbolInfo>| symbols.into_iter().map(|s| s.name).collect::<Vec<_>>();
+        assert_eq!(names(index.autocomplete("Customer", None, 10).unwrap()), vec!["Customer_API", "Customer_Tab"]);
+        assert_eq!(names(index.autocomplete("customer_a", None, 10).unwrap()), vec!["Customer_API"]);
+        assert_eq!(
+            names(index.autocomplete("Customer", Some(&[SymbolKind::View]), 10).unwrap()),
+            vec!["Customer_Tab"]
+        );
+        assert_eq!(index.autocomplete("Customer", None, 1).unwrap().len(), 1);
+        assert!(index.autocomplete("API", None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_plan_directory_lists_changes_without_writing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, procedure: &str| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, format!("PROCEDURE {} IS BEGIN NULL; END;", procedure)).unwrap();
+            path
+        };
+        let kept = write("kept.plsql", "Kept_Proc");
+        let edited = write("edited.plsql", "Edited_Proc");
+        let removed = write("removed.plsql", "Removed_Proc");
+
+        let mut index = Index::in_memory().unwrap();
+        index.index_directory(temp_dir.path(), &IndexOptions::default()).unwrap();
+
+        let added = write("added.plsql", "Added_Proc");
+        write("edited.plsql", "Edited_Again_Proc");
+        std::fs::remove_file(&removed).unwrap();
+
+        let plan = index.plan_directory(temp_dir.path(), Language::all()).unwrap();
+        assert_eq!(plan.added, vec![added]);
+        assert_eq!(plan.changed, vec![edited]);
+        assert_eq!(plan.unchanged, vec![kept]);
+        assert_eq!(plan.deleted, vec![removed]);
+        assert!(plan.has_changes());
+
+        // Planning leaves the index untouched
+        assert_eq!(index.get_statistics().unwrap().total_files, 3);
+        assert!(index.search_symbols("Added_Proc").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_index_directory_records_parse_timeout() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("slow.plsql"),
+            format!("PROCEDURE Slow_Proc IS\nBEGIN\n{}END Slow_Proc;", "   NULL;\n".repeat(200_000)),
+        )
+        .unwrap();
+
+        let mut index = Index::in_memory().unwrap();
+        let options = IndexOptions {
+            parse_timeout: Some(Duration::from_millis(1)),
+            ..Default::default()
+        };
+        let summary = index.index_directory(temp_dir.path(), &options).unwrap();
+
+        assert_eq!(summary.timed_out_count(), 1);
+        assert_eq!(summary.indexed_count(), 0);
+        assert_eq!(
+            summary.results[0].status,
+            FileIndexStatus::TimedOut { after: Duration::from_millis(1) }
+        );
+        assert!(index.search_symbols("Slow_Proc").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_pending_references_links_calls_across_files() {
+        let caller = parse_source("PROCEDURE Caller_ IS\nBEGIN\n   Callee_;\nEND Caller_;", Language::PlSql).unwrap();
+        let callee = parse_source("PROCEDURE Callee_ IS\nBEGIN\n   NULL;\nEND Callee_;", Language::PlSql).unwrap();
+
+        // The caller is indexed first, so its call cannot be resolved yet
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("caller.plsql", &caller).unwrap();
+        index.index_file("callee.plsql", &callee).unwrap();
+
+        let callee_symbol = index.search_symbols("Callee_").unwrap().remove(0);
+        assert!(index.find_references(&callee_symbol).unwrap().is_empty());
+
+        assert_eq!(index.resolve_pending_references().unwrap(), 1);
+        let references = index.find_references(&callee_symbol).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].file_path, "caller.plsql");
+        assert_eq!(references[0].reference_kind, ReferenceKind::Call);
+
+        // Nothing is left to resolve on a second pass
+        assert_eq!(index.resolve_pending_references().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_reindexing_a_file_keeps_references_from_other_files() {
+        let callee = parse_source(
+            "PACKAGE A_API IS\n   PROCEDURE Refresh;\nEND A_API;",
+            Language::PlSql,
+        )
+        .unwrap();
+        let caller = parse_source("PROCEDURE Caller_ IS\nBEGIN\n   A_API.Refresh;\nEND Caller_;", Language::PlSql).unwrap();
+
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("a.plsql", &callee).unwrap();
+        index.index_file("b.plsql", &caller).unwrap();
+        let refresh = index.search_symbols("Refresh").unwrap().remove(0);
+        assert_eq!(index.find_references(&refresh).unwrap().len(), 1);
+
+        index.index_file("a.plsql", &callee).unwrap();
+        let refresh = index.search_symbols("Refresh").unwrap().remove(0);
+        let references = index.find_references(&refresh).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].file_path, "b.plsql");
+        assert_eq!(references[0].reference_kind, ReferenceKind::Call);
+        assert!(index.database.get_pending_references().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hover_qualifies_symbols_with_their_parents() {
+        let source = "PACKAGE BODY A_API IS\n   PROCEDURE Refresh (force_ IN BOOLEAN) IS\n   BEGIN\n      NULL;\n   END Refresh;\nEND A_API;";
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("a.plsql", &parse_source(source, Language::PlSql).unwrap()).unwrap();
+
+        let refresh = index.search_symbols("Refresh").unwrap().remove(0);
+        assert!(refresh.hover_markdown().starts_with("**Procedure** `A_API.Refresh`"), "{}", refresh.hover_markdown());
+        let force = index.search_symbols("force_").unwrap().remove(0);
+        assert_eq!(force.qualified_name(), "A_API.Refresh.force_");
+    }
+
+    #[test]
+    fn test_merge_from_keeps_pending_references() {
+        let caller = parse_source("PROCEDURE Caller_ IS\nBEGIN\n   Callee_;\nEND Caller_;", Language::PlSql).unwrap();
+        let callee = parse_source("PROCEDURE Callee_ IS\nBEGIN\n   NULL;\nEND Callee_;", Language::PlSql).unwrap();
+
+        let mut first = Index::in_memory().unwrap();
+        first.index_file("callee.plsql", &callee).unwrap();
+        let mut second = Index::in_memory().unwrap();
+        second.index_file("caller.plsql", &caller).unwrap();
+
+        first.merge_from(&second).unwrap();
+        assert_eq!(first.resolve_pending_references().unwrap(), 1);
+
+        let callee_symbol = first.search_symbols("Callee_").unwrap().remove(0);
+        let references = first.find_references(&callee_symbol).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].file_path, "caller.plsql");
+    }
+
+    #[test]
+    fn test_locals_and_predefined_names_are_not_queued() {
+        let source = "PROCEDURE Check_ IS\nBEGIN\n   count_ := 1;\n   RAISE_APPLICATION_ERROR(-20000, 'x');\n   DBMS_OUTPUT.Put_Line('x');\n   RAISE NO_DATA_FOUND;\n   Later_API.Run;\nEND Check_;";
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("check.plsql", &parse_source(source, Language::PlSql).unwrap()).unwrap();
+
+        let pending = index.database.get_pending_references().unwrap();
+        let names: Vec<&str> = pending.iter().map(|reference| reference.name.as_str()).collect();
+        assert_eq!(names, vec!["RUN"]);
+    }
+
+    #[test]
+    fn test_local_references_resolve_within_the_enclosing_subprogram() {
+        let source = "PACKAGE BODY C_API IS\n   PROCEDURE One (info_ OUT VARCHAR2) IS\n   BEGIN\n      info_ := 'one';\n   END One;\n   PROCEDURE Two (info_ OUT VARCHAR2) IS\n   BEGIN\n      info_ := 'two';\n   END Two;\nEND C_API;";
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("c.plsql", &parse_source(source, Language::PlSql).unwrap()).unwrap();
+
+        let parameters = index.search_symbols("info_").unwrap();
+        assert_eq!(parameters.len(), 2);
+        for parameter in parameters {
+            let references = index.find_references(&parameter).unwrap();
+            let lines: Vec<usize> = references.iter().map(|reference| reference.span.start.line).collect();
+            let expected = if parameter.span.start.line == 2 { vec![4] } else { vec![8] };
+            assert_eq!(lines, expected, "references to info_ declared on line {}", parameter.span.start.line);
+        }
+    }
+
+    #[test]
+    fn test_search_symbols_paged_returns_slice_and_total() {
+        let names: Vec<String> = (0..50).map(|i| format!("Value_{:02}", i)).collect();
+        let values: Vec<&str> = names.iter().map(String::as_str).collect();
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("Status.enumeration", &enumeration_ast("Status", &values)).unwrap();
+
+        let (page, total) = index.search_symbols_paged("Value_", 20, 20).unwrap();
+        assert_eq!(total, 50);
+        let page_names: Vec<String> = page.into_iter().map(|symbol| symbol.name).collect();
+        assert_eq!(page_names, names[20..40]);
+
+        let (last_page, total) = index.search_symbols_paged("Value_", 20, 40).unwrap();
+        assert_eq!(total, 50);
+        assert_eq!(last_page.len(), 10);
+    }
+
+    #[test]
+    fn test_export_call_graph_dot_writes_call_edges() {
+        let source = "\
+PACKAGE BODY Chain_API IS
+   PROCEDURE C_ IS
+   BEGIN
+      NULL;
+   END C_;
+
+   PROCEDURE B_ IS
+   BEGIN
+      C_;
+   END B_;
+
+   PROCEDURE A_ IS
+   BEGIN
+      B_;
+   END A_;
+END Chain_API;";
+        let other = "PROCEDURE Unrelated_ IS\nBEGIN\n   Helper_;\nEND Unrelated_;\n\nPROCEDURE Helper_ IS\nBEGIN\n   NULL;\nEND Helper_;";
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("chain.plsql", &parse_source(source, Language::PlSql).unwrap()).unwrap();
+        index.index_file("other.plsql", &parse_source(other, Language::PlSql).unwrap()).unwrap();
+        index.resolve_pending_references().unwrap();
+
+        let mut output = Vec::new();
+        index.export_call_graph_dot(&mut output, Some("chain_api")).unwrap();
+        let dot = String::from_utf8(output).unwrap();
+
+        assert!(dot.starts_with("digraph calls {"), "{}", dot);
+        assert!(dot.contains("\"Chain_API.A_\" -> \"Chain_API.B_\";"), "{}", dot);
+        assert!(dot.contains("\"Chain_API.B_\" -> \"Chain_API.C_\";"), "{}", dot);
+        assert!(!dot.contains("\"Chain_API.A_\" -> \"Chain_API.C_\""), "{}", dot);
+        assert!(!dot.contains("Unrelated_"), "{}", dot);
+    }
 }
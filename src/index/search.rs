@@ -1,7 +1,8 @@
 // Search functionality for the index
 
 use crate::index::database::Database;
-use crate::index::symbols::{SymbolInfo, SymbolReference, ReferenceKind};
+use crate::index::symbols::{SymbolInfo, SymbolKind, SymbolReference, ReferenceKind, PARAMETER_DEFAULT_SUFFIX};
+use crate::parser::{ast::normalize_identifier, Language};
 use crate::Result;
 use std::path::Path;
 
@@ -22,6 +23,24 @@ impl<'a> SymbolSearcher<'a> {
         Ok(rows.into_iter().map(SymbolInfo::from).collect())
     }
     
+    /// Up to `limit` symbols whose name starts with `prefix`, ignoring case
+    /// for unquoted names, optionally restricted to `kinds`
+    pub fn autocomplete(&self, prefix: &str, kinds: Option<&[SymbolKind]>, limit: usize) -> Result<Vec<SymbolInfo>> {
+        let kinds: Vec<String> = kinds.unwrap_or_default().iter().map(SymbolKind::to_string).collect();
+        let rows = self
+            .database
+            .search_symbols_by_prefix(&normalize_identifier(prefix), &kinds, limit)?;
+        Ok(rows.into_iter().map(SymbolInfo::from).collect())
+    }
+    
+    /// Search for enumeration values whose label text contains `label`
+    pub fn search_by_label(&self, label: &str) -> Result<Vec<SymbolInfo>> {
+        let rows = self
+            .database
+            .search_symbols_by_label(label, &SymbolKind::EnumerationValue.to_string())?;
+        Ok(rows.into_iter().map(SymbolInfo::from).collect())
+    }
+    
     /// Find all references to a symbol
     pub fn find_references(&self, symbol: &SymbolInfo) -> Result<Vec<SymbolReference>> {
         if let Some(symbol_id) = symbol.id {
@@ -55,6 +74,104 @@ impl<'a> SymbolSearcher<'a> {
         }
     }
     
+    /// Find the implementation linked to a forward declaration
+    pub fn find_implementation(&self, declaration: &SymbolInfo) -> Result<Option<SymbolInfo>> {
+        let references = self.find_references(declaration)?;
+        
+        for reference in references {
+            if reference.reference_kind != ReferenceKind::Declaration {
+                continue;
+            }
+            
+            let symbols = self.get_symbols_in_file(Path::new(&reference.file_path))?;
+            if let Some(implementation) = symbols
+                .into_iter()
+                .find(|symbol| symbol.span == reference.span && symbol.id != declaration.id)
+            {
+                return Ok(Some(implementation));
+            }
+        }
+        
+        Ok(None)
+    }
+    
+    /// Resolve an anchored type such as `customer_tab.customer_id%TYPE` or `customer_tab%ROWTYPE`
+    /// to the indexed storage column or table, and a plain type name such as
+    /// `Customer_API.Public_Rec` to the indexed type declaration. Returns `None` when the target
+    /// is not indexed, which includes built-in types like `VARCHAR2`.
+    pub fn resolve_type_reference(&self, type_ref: &str) -> Result<Option<SymbolInfo>> {
+        let type_ref = type_ref.trim();
+        let Some((path, attribute)) = type_ref.rsplit_once('%') else {
+            let normalized = normalize_identifier(type_ref);
+            let (qualifier, target) = split_qualifier(&normalized);
+            let types = self.database.find_types(target, qualifier)?;
+            return Ok(types.into_iter().next().map(SymbolInfo::from));
+        };
+        let parts: Vec<&str> = path.split('.').map(str::trim).collect();
+        
+        // Schema prefixes such as `appowner.customer_tab` are ignored
+        let (table_name, column_name) = match attribute.to_uppercase().as_str() {
+            "ROWTYPE" => (parts[parts.len() - 1], None),
+            "TYPE" if parts.len() >= 2 => (parts[parts.len() - 2], Some(parts[parts.len() - 1])),
+            _ => return Ok(None),
+        };
+        
+        // Storage tables are indexed with the entity symbol kinds
+        let tables = self.database.find_symbols_exact(
+            table_name,
+            &SymbolKind::Entity.to_string(),
+            Language::Storage,
+        )?;
+        let Some(table) = tables.into_iter().next() else {
+            return Ok(None);
+        };
+        
+        let Some(column_name) = column_name else {
+            return Ok(Some(SymbolInfo::from(table)));
+        };
+        
+        let column_kind = SymbolKind::EntityAttribute.to_string();
+        let column = self
+            .database
+            .get_child_symbols(table.id)?
+            .into_iter()
+            .find(|symbol| symbol.kind == column_kind && symbol.name.eq_ignore_ascii_case(column_name));
+        Ok(column.map(SymbolInfo::from))
+    }
+    
+    /// Resolve a call to the overloads of `name` that accept `arg_count` arguments. A qualified
+    /// name such as `Customer_API.Get_Name` only matches subprograms in that package. An overload
+    /// accepts the call when it has at least `arg_count` parameters and every parameter beyond
+    /// the supplied arguments has a default value.
+    pub fn resolve_call(&self, name: &str, arg_count: usize) -> Result<Vec<SymbolInfo>> {
+        let normalized = normalize_identifier(name);
+        let (qualifier, target) = split_qualifier(&normalized);
+        
+        let mut overloads = Vec::new();
+        for row in self.database.find_subprograms(target, qualifier)? {
+            let parameters: Vec<_> = self
+                .database
+                .get_child_symbols(row.id)?
+                .into_iter()
+                .filter(|child| child.kind == SymbolKind::Parameter.to_string())
+                .collect();
+            let required = parameters
+                .iter()
+                .filter(|parameter| {
+                    !parameter
+                        .signature
+                        .as_deref()
+                        .is_some_and(|signature| signature.ends_with(PARAMETER_DEFAULT_SUFFIX))
+                })
+                .count();
+            if required <= arg_count && arg_count <= parameters.len() {
+                overloads.push(SymbolInfo::from(row));
+            }
+        }
+        
+        Ok(overloads)
+    }
+    
     /// Find symbol definition at a specific position
     pub fn find_definition_at_position(
         &self,
@@ -84,6 +201,24 @@ impl<'a> SymbolSearcher<'a> {
         Ok(rows.into_iter().map(SymbolInfo::from).collect())
     }
     
+    /// Get the symbols of a file lying entirely within `start_line..=end_line`
+    pub fn symbols_in_range(&self, file: &Path, start_line: usize, end_line: usize) -> Result<Vec<SymbolInfo>> {
+        let rows = self.database.get_file_symbols_in_range(file, start_line, end_line)?;
+        Ok(rows.into_iter().map(SymbolInfo::from).collect())
+    }
+    
+    /// Find the lower-layer implementations an `@Override` or `@Overtake` subprogram may
+    /// replace: same-named procedures or functions of the same package in other files
+    /// that do not override anything themselves. Anything but exactly one match means
+    /// the override is ambiguous.
+    pub fn resolve_override_targets(&self, symbol: &SymbolInfo) -> Result<Vec<SymbolInfo>> {
+        let Some(symbol_id) = symbol.id else {
+            return Ok(Vec::new());
+        };
+        let rows = self.database.find_override_candidates(symbol_id)?;
+        Ok(rows.into_iter().map(SymbolInfo::from).collect())
+    }
+    
     /// Search for symbols by kind
     pub fn search_by_kind(&self, kind_pattern: &str) -> Result<Vec<SymbolInfo>> {
         // This would need to be implemented in the database layer
@@ -128,6 +263,15 @@ impl<'a> SymbolSearcher<'a> {
     }
 }
 
+/// Split a normalized name such as `APPOWNER.CUSTOMER_API.GET_NAME` into its package qualifier
+/// and target name. Schema prefixes before the package are ignored.
+fn split_qualifier(normalized: &str) -> (Option<&str>, &str) {
+    match normalized.rsplit_once('.') {
+        Some((qualifier, target)) => (Some(qualifier.rsplit('.').next().unwrap_or(qualifier)), target),
+        None => (None, normalized),
+    }
+}
+
 /// Advanced search queries
 pub struct SearchQuery {
     pub name_pattern: Option<String>,
@@ -284,7 +428,33 @@ impl<'a> AdvancedSearcher<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::index::Database;
+    use crate::index::{Database, SymbolIndexer};
+    use crate::parser::ast::*;
+    
+    fn span_at(line: usize) -> Span {
+        Span {
+            start: Position { line, column: 1, offset: line * 100 },
+            end: Position { line, column: 20, offset: line * 100 + 19 },
+        }
+    }
+    
+    fn identifier(name: &str, line: usize) -> Identifier {
+        Identifier { name: name.to_string(), span: span_at(line) }
+    }
+    
+    fn varchar2(line: usize) -> Type {
+        Type { name: "VARCHAR2".to_string(), parameters: Vec::new(), span: span_at(line) }
+    }
+    
+    fn id_parameter(line: usize) -> Parameter {
+        Parameter {
+            name: identifier("id_", line),
+            param_type: varchar2(line),
+            mode: ParameterMode::In,
+            default_value: None,
+            span: span_at(line),
+        }
+    }
 
     #[test]
     fn test_search_query_builder() {
@@ -310,4 +480,495 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
     }
+    
+    #[test]
+    fn test_find_implementation_from_forward_declaration() {
+        let source = "PACKAGE BODY Customer_API IS\n\
+            FUNCTION Get_Name (id_ IN VARCHAR2) RETURN VARCHAR2;\n\
+            \n\
+            \n\
+            FUNCTION Get_Name (id_ IN VARCHAR2) RETURN VARCHAR2 IS\n\
+            BEGIN\n\
+               RETURN NULL;\n\
+            END Get_Name;\n\
+            END Customer_API;";
+        let package = crate::parser::parse_source(source, Language::PlSql).unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database)
+            .index_ast("customer.plsql", &package)
+            .unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let symbols = searcher.search_by_name("Get_Name").unwrap();
+        let declaration = symbols.iter().find(|s| s.span.start.line == 2).unwrap();
+        let definition = symbols.iter().find(|s| s.span.start.line == 5).unwrap();
+        
+        let implementation = searcher.find_implementation(declaration).unwrap();
+        assert_eq!(implementation.as_ref(), Some(definition));
+        assert_eq!(searcher.find_implementation(definition).unwrap(), None);
+    }
+    
+    fn customer_table() -> AstNode {
+        let column = |name: &str, line: usize| TableColumn {
+            name: identifier(name, line),
+            data_type: "VARCHAR2(20)".to_string(),
+            nullable: false,
+            default_value: None,
+            span: span_at(line),
+        };
+        AstNode::Storage(StorageNode {
+            layer: None,
+            definitions: vec![StorageDefinition::Table {
+                name: identifier("customer_tab", 1),
+                columns: vec![column("customer_id", 2), column("name", 3)],
+                constraints: Vec::new(),
+                span: span_at(1),
+            }],
+            span: span_at(1),
+        })
+    }
+    
+    #[test]
+    fn test_resolve_type_reference_to_storage_column() {
+        let storage = customer_table();
+        
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database)
+            .index_ast("customer.storage", &storage)
+            .unwrap();
+        let searcher = SymbolSearcher::new(&database);
+        
+        let resolved = searcher
+            .resolve_type_reference("customer_tab.customer_id%TYPE")
+            .unwrap()
+            .expect("column should resolve");
+        assert_eq!(resolved.name, "customer_id");
+        assert_eq!(resolved.kind, SymbolKind::EntityAttribute);
+        assert_eq!(resolved.span.start.line, 2);
+        
+        let row_type = searcher.resolve_type_reference("CUSTOMER_TAB%ROWTYPE").unwrap().unwrap();
+        assert_eq!(row_type.name, "customer_tab");
+        
+        assert_eq!(searcher.resolve_type_reference("customer_tab.missing%TYPE").unwrap(), None);
+        assert_eq!(searcher.resolve_type_reference("order_tab.order_no%TYPE").unwrap(), None);
+        assert_eq!(searcher.resolve_type_reference("VARCHAR2").unwrap(), None);
+    }
+    
+    #[test]
+    fn test_references_resolve_case_insensitively() {
+        let package = AstNode::PlSql(PlSqlNode::Package {
+            name: identifier("Customer_API", 1),
+            is_body: false,
+            component: None,
+            annotations: Vec::new(),
+            declarations: vec![PlSqlDeclaration::Subprogram(Box::new(PlSqlNode::Function {
+                name: identifier("Get_Name", 2),
+                visibility: ProcedureVisibility::Public,
+                annotations: Vec::new(),
+                parameters: vec![id_parameter(2)],
+                return_type: varchar2(2),
+                is_pipelined: false,
+                result_cached: false,
+                body: Vec::new(),
+                exception_handlers: Vec::new(),
+                end_label: None,
+                pragmas: Vec::new(),
+                declarations: Vec::new(),
+                span: span_at(2),
+            }))],
+            body: None,
+            span: span_at(1),
+        });
+        let call = |name: &str, line: usize| PlSqlStatement::Call {
+            name: identifier(name, line),
+            arguments: Vec::new(),
+            span: span_at(line),
+            trivia: None,
+        };
+        let caller = AstNode::PlSql(PlSqlNode::Procedure {
+            name: identifier("Print_Customer", 1),
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: Vec::new(),
+            body: vec![call("customer_api.get_name", 2), call("CUSTOMER_API.GET_NAME", 3), call("Other_API.Get_Name", 4)],
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+            span: span_at(1),
+        });
+        
+        let mut database = Database::in_memory().unwrap();
+        let mut indexer = SymbolIndexer::new(&mut database);
+        indexer.index_ast("customer.plsql", &package).unwrap();
+        indexer.index_ast("print.plsql", &caller).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let get_name = searcher
+            .search_by_name("Get_Name")
+            .unwrap()
+            .into_iter()
+            .find(|s| s.kind == SymbolKind::Function)
+            .unwrap();
+        
+        let references = searcher.find_references(&get_name).unwrap();
+        let lines: Vec<usize> = references.iter().map(|r| r.span.start.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+        assert!(references.iter().all(|r| r.reference_kind == ReferenceKind::Call && r.file_path == "print.plsql"));
+    }
+    
+    #[test]
+    fn test_view_records_usage_of_storage_table() {
+        let view = AstNode::Views(ViewsNode {
+            layer: None,
+            column_definitions: Vec::new(),
+            views: vec![ViewDefinition {
+                name: identifier("Customer", 1),
+                visibility: ViewVisibility::Public,
+                annotations: Vec::new(),
+                columns: Vec::new(),
+                query: SqlQuery {
+                    select: Vec::new(),
+                    from: vec![FromItem { table: identifier("CUSTOMER_TAB", 4), alias: None, span: span_at(4) }],
+                    where_clause: None,
+                    group_by: Vec::new(),
+                    having: None,
+                    order_by: Vec::new(),
+                    span: span_at(2),
+                },
+                span: span_at(1),
+            }],
+            span: span_at(1),
+        });
+        
+        let mut database = Database::in_memory().unwrap();
+        let mut indexer = SymbolIndexer::new(&mut database);
+        indexer.index_ast("customer.storage", &customer_table()).unwrap();
+        indexer.index_ast("customer.views", &view).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let table = searcher
+            .search_by_name("customer_tab")
+            .unwrap()
+            .into_iter()
+            .find(|s| s.kind == SymbolKind::Entity)
+            .unwrap();
+        
+        let references = searcher.find_references(&table).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].file_path, "customer.views");
+        assert_eq!(references[0].reference_kind, ReferenceKind::Usage);
+        assert_eq!(references[0].span.start.line, 4);
+    }
+    
+    #[test]
+    fn test_anchored_parameter_type_records_type_usage_of_column() {
+        let procedure = AstNode::PlSql(PlSqlNode::Procedure {
+            name: identifier("Set_Customer", 1),
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: vec![Parameter {
+                name: identifier("id_", 2),
+                param_type: Type {
+                    name: "customer_tab.customer_id%TYPE".to_string(),
+                    parameters: Vec::new(),
+                    span: span_at(2),
+                },
+                mode: ParameterMode::In,
+                default_value: None,
+                span: span_at(2),
+            }],
+            body: Vec::new(),
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+            span: span_at(1),
+        });
+        
+        let mut database = Database::in_memory().unwrap();
+        let mut indexer = SymbolIndexer::new(&mut database);
+        indexer.index_ast("customer.storage", &customer_table()).unwrap();
+        indexer.index_ast("customer.plsql", &procedure).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let column = searcher
+            .resolve_type_reference("customer_tab.customer_id%TYPE")
+            .unwrap()
+            .unwrap();
+        
+        let references = searcher.find_references(&column).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].file_path, "customer.plsql");
+        assert_eq!(references[0].reference_kind, ReferenceKind::TypeUsage);
+        assert_eq!(references[0].span.start.line, 2);
+    }
+    
+    #[test]
+    fn test_named_parameter_type_records_type_usage_of_declared_type() {
+        let mut database = Database::in_memory().unwrap();
+        let package = AstNode::PlSql(PlSqlNode::Package {
+            name: identifier("Customer_API", 1),
+            is_body: false,
+            component: None,
+            annotations: Vec::new(),
+            declarations: Vec::new(),
+            body: None,
+            span: span_at(1),
+        });
+        SymbolIndexer::new(&mut database).index_ast("customer_api.plsql", &package).unwrap();
+        
+        // The parser has no record type declarations yet, so store `Public_Rec` directly
+        let file_id = database.get_file_id("customer_api.plsql").unwrap().unwrap();
+        let package_id = database.find_symbols_exact("Customer_API", "Package", Language::PlSql).unwrap()[0].id;
+        database
+            .store_symbol(file_id, "Public_Rec", "Type", 2, 1, 2, 20, 200, 219, Some(package_id), None, None)
+            .unwrap();
+        
+        let procedure = AstNode::PlSql(PlSqlNode::Procedure {
+            name: identifier("Print_Customer", 1),
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: vec![
+                Parameter {
+                    name: identifier("rec_", 2),
+                    param_type: Type {
+                        name: "Customer_API.Public_Rec".to_string(),
+                        parameters: Vec::new(),
+                        span: span_at(2),
+                    },
+                    mode: ParameterMode::In,
+                    default_value: None,
+                    span: span_at(2),
+                },
+                id_parameter(3),
+            ],
+            body: Vec::new(),
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+            span: span_at(1),
+        });
+        SymbolIndexer::new(&mut database).index_ast("print.plsql", &procedure).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        assert!(searcher.resolve_type_reference("Order_API.Public_Rec").unwrap().is_none());
+        assert!(searcher.resolve_type_reference("VARCHAR2").unwrap().is_none());
+        let public_rec = searcher
+            .resolve_type_reference("customer_api.public_rec")
+            .unwrap()
+            .unwrap();
+        assert_eq!(public_rec.name, "Public_Rec");
+        
+        let references = searcher.find_references(&public_rec).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].file_path, "print.plsql");
+        assert_eq!(references[0].reference_kind, ReferenceKind::TypeUsage);
+        assert_eq!(references[0].span.start.line, 2);
+    }
+    
+    #[test]
+    fn test_view_column_ref_records_usage_of_entity() {
+        let entity = AstNode::Entity(EntityNode {
+            entity_name: identifier("Customer", 1),
+            component: "ORDER".to_string(),
+            code_gen_properties: None,
+            attributes: Vec::new(),
+            keys: Vec::new(),
+            references: Vec::new(),
+            state_machine: None,
+            span: span_at(1),
+        });
+        let views = crate::parser::parse_source(
+            "COLUMN Customer_No IS\n   Flags = 'KMI-L'\n   Ref(Customer);\n",
+            Language::Views,
+        )
+        .unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        let mut indexer = SymbolIndexer::new(&mut database);
+        indexer.index_ast("Customer.entity", &entity).unwrap();
+        indexer.index_ast("Order.views", &views).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let customer = searcher
+            .search_by_name("Customer")
+            .unwrap()
+            .into_iter()
+            .find(|s| s.kind == SymbolKind::Entity)
+            .unwrap();
+        
+        let references = searcher.find_references(&customer).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].file_path, "Order.views");
+        assert_eq!(references[0].reference_kind, ReferenceKind::Usage);
+        assert_eq!(references[0].span.start.line, 1);
+    }
+    
+    #[test]
+    fn test_state_transitions_record_usages_of_states() {
+        let entity = crate::parser::parse_source(
+            "entityname ShopOrder;\nstatemachine {\n   initial state Planned {\n      event Release to Released;\n   }\n   final state Released;\n}\n",
+            Language::Entity,
+        )
+        .unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        let mut indexer = SymbolIndexer::new(&mut database);
+        indexer.index_ast("ShopOrder.entity", &entity).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let released = searcher
+            .search_by_name("Released")
+            .unwrap()
+            .into_iter()
+            .find(|s| s.kind == SymbolKind::State)
+            .expect("missing state symbol");
+        assert_eq!(released.span.start.line, 6);
+        
+        let references = searcher.find_references(&released).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].reference_kind, ReferenceKind::Usage);
+        assert_eq!(references[0].span.start.line, 4);
+    }
+    
+    #[test]
+    fn test_enumeration_value_label_is_captured_and_searchable() {
+        let source = "enumerationname OrderStatus;\ncomponent ORDER;\nlayer Core;\n\nvalues {\n   value Planned {\n      ClientValue = \"Planned\";\n      DbValue = \"PLANNED\";\n      LabelText = \"Planned for Delivery\";\n   }\n}\n";
+        let enumeration = crate::parser::parse_source(source, Language::Enumeration).unwrap();
+        
+        if let AstNode::Enumeration(node) = &enumeration {
+            assert_eq!(node.enumeration_name.name, "OrderStatus");
+            assert_eq!(node.component, "ORDER");
+            let value = &node.values[0];
+            assert_eq!(value.client_value.as_deref(), Some("Planned"));
+            assert_eq!(value.label_text.as_deref(), Some("Planned for Delivery"));
+            assert_eq!(value.db_value(), Some("PLANNED"));
+        } else {
+            panic!("Expected enumeration node");
+        }
+        
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database)
+            .index_ast("OrderStatus.enumeration", &enumeration)
+            .unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let found = searcher.search_by_label("for delivery").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Planned");
+        assert_eq!(found[0].signature.as_deref(), Some("Planned"));
+        assert_eq!(found[0].label.as_deref(), Some("Planned for Delivery"));
+        assert_eq!(found[0].documentation, None);
+        assert!(searcher.search_by_label("Released").unwrap().is_empty());
+        
+        // LIKE wildcards in the search text are matched literally
+        assert!(searcher.search_by_label("%").unwrap().is_empty());
+        assert!(searcher.search_by_label("for_delivery").unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_symbols_in_range_excludes_symbols_outside_lines() {
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database)
+            .index_ast("customer.storage", &customer_table())
+            .unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let names = |start_line: usize, end_line: usize| -> Vec<String> {
+            searcher
+                .symbols_in_range(Path::new("customer.storage"), start_line, end_line)
+                .unwrap()
+                .into_iter()
+                .map(|symbol| symbol.name)
+                .collect()
+        };
+        
+        assert_eq!(names(2, 3), vec!["customer_id", "name"]);
+        assert_eq!(names(3, 10), vec!["name"]);
+        assert_eq!(names(1, 2), vec!["customer_tab", "customer_id"]);
+        assert!(searcher.symbols_in_range(Path::new("other.storage"), 1, 10).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_resolve_call_picks_overload_by_argument_count() {
+        let source = "PROCEDURE Do_It (a_ IN VARCHAR2) IS\nBEGIN\n   NULL;\nEND Do_It;\n\n\
+                      PROCEDURE Do_It (a_ IN VARCHAR2, b_ IN NUMBER, c_ IN NUMBER DEFAULT 0) IS\nBEGIN\n   NULL;\nEND Do_It;";
+        let ast = crate::parser::parse_source(source, Language::PlSql).unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database).index_ast("do_it.plsql", &ast).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let two_args = searcher.resolve_call("Do_It", 2).unwrap();
+        assert_eq!(two_args.len(), 1);
+        assert_eq!(two_args[0].span.start.line, 6);
+        assert_eq!(searcher.resolve_call("Do_It", 3).unwrap()[0].span.start.line, 6);
+        
+        let one_arg = searcher.resolve_call("do_it", 1).unwrap();
+        assert_eq!(one_arg.len(), 1);
+        assert_eq!(one_arg[0].span.start.line, 1);
+        
+        assert!(searcher.resolve_call("Do_It", 0).unwrap().is_empty());
+        assert!(searcher.resolve_call("Do_It", 4).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_resolve_call_filters_by_package_qualifier() {
+        let mut database = Database::in_memory().unwrap();
+        for (path, package) in [("customer.plsql", "Customer_API"), ("supplier.plsql", "Supplier_API")] {
+            let source = format!(
+                "PACKAGE BODY {package} IS\n   PROCEDURE Get_Name (id_ IN VARCHAR2) IS\n   BEGIN\n      NULL;\n   END;\nEND {package};"
+            );
+            let ast = crate::parser::parse_source(&source, Language::PlSql).unwrap();
+            SymbolIndexer::new(&mut database).index_ast(path, &ast).unwrap();
+        }
+        
+        let searcher = SymbolSearcher::new(&database);
+        assert_eq!(searcher.resolve_call("Get_Name", 1).unwrap().len(), 2);
+        
+        let qualified = searcher.resolve_call("supplier_api.Get_Name", 1).unwrap();
+        assert_eq!(qualified.len(), 1);
+        assert_eq!(qualified[0].file_path, "supplier.plsql");
+        assert!(searcher.resolve_call("Order_API.Get_Name", 1).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_returning_into_records_assignment_and_column_usage() {
+        let source = "PROCEDURE New_Order (id_ OUT NUMBER) IS\nBEGIN\n   \
+                      INSERT INTO customer_tab (name) VALUES ('Acme') RETURNING customer_id INTO id_;\n\
+                      END New_Order;";
+        let ast = crate::parser::parse_source(source, Language::PlSql).unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        let mut indexer = SymbolIndexer::new(&mut database);
+        indexer.index_ast("customer.storage", &customer_table()).unwrap();
+        indexer.index_ast("new_order.plsql", &ast).unwrap();
+        
+        let searcher = SymbolSearcher::new(&database);
+        let parameter = searcher
+            .get_symbols_in_file(Path::new("new_order.plsql"))
+            .unwrap()
+            .into_iter()
+            .find(|symbol| symbol.kind == SymbolKind::Parameter)
+            .unwrap();
+        let kinds: Vec<ReferenceKind> = searcher
+            .find_references(&parameter)
+            .unwrap()
+            .into_iter()
+            .map(|reference| reference.reference_kind)
+            .collect();
+        assert_eq!(kinds, vec![ReferenceKind::Assignment]);
+        
+        let column = searcher
+            .resolve_type_reference("customer_tab.customer_id%TYPE")
+            .unwrap()
+            .unwrap();
+        let references = searcher.find_references(&column).unwrap();
+        assert!(references
+            .iter()
+            .any(|reference| reference.reference_kind == ReferenceKind::Usage && reference.file_path == "new_order.plsql"));
+    }
 }
@@ -1,8 +1,10 @@
 // Symbol indexing and management
 
 use crate::index::database::{Database, SymbolRow};
+use crate::index::search::SymbolSearcher;
 use crate::parser::ast::*;
 use crate::Result;
+use std::collections::HashMap;
 use std::path::Path;
 use serde::{Deserialize, Serialize};
 
@@ -16,9 +18,78 @@ pub struct SymbolInfo {
     pub file_path: String,
     pub signature: Option<String>,
     pub documentation: Option<String>,
+    /// Annotations such as `@Override` written above a subprogram
+    pub annotations: Vec<String>,
+    /// Label text of an enumeration value
+    pub label: Option<String>,
+    /// `Public` or `Private` for views and view columns
+    pub visibility: Option<String>,
     pub parent: Option<Box<SymbolInfo>>,
 }
 
+impl SymbolInfo {
+    /// Whether the symbol is annotated `@Override` or `@Overtake`
+    pub fn is_layer_override(&self) -> bool {
+        self.annotations.iter().any(|annotation| annotation == "@Override" || annotation == "@Overtake")
+    }
+    
+    /// Whether the symbol is a view or view column marked `PRIVATE`
+    pub fn is_private(&self) -> bool {
+        self.visibility.as_deref() == Some("Private")
+    }
+    
+    /// Name qualified with the names of all enclosing symbols, e.g. `Customer_API.Get_Name`
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}.{}", parent.qualified_name(), self.name),
+            None => self.name.clone(),
+        }
+    }
+    
+    /// Render the symbol as Markdown for an editor hover
+    pub fn hover_markdown(&self) -> String {
+        let mut markdown = format!("**{}** `{}`", self.kind, self.qualified_name());
+        
+        let signature = match (&self.kind, &self.signature) {
+            (SymbolKind::Parameter, Some(signature)) => Some(parameter_hover_signature(&self.name, signature)),
+            (_, Some(signature)) => Some(signature.clone()),
+            (_, None) => None,
+        };
+        if let Some(signature) = signature {
+            markdown.push_str(&format!("\n\n```plsql\n{}\n```", signature));
+        }
+        
+        let documentation = self.documentation.as_deref().or(self.label.as_deref());
+        if let Some(documentation) = documentation.filter(|d| !d.trim().is_empty()) {
+            markdown.push_str(&format!("\n\n{}", documentation.trim()));
+        }
+        
+        markdown
+    }
+}
+
+/// Parameter signatures are stored as `<type> <mode>`, followed by ` DEFAULT` when the
+/// parameter has a default value; show them as `<name> <MODE> <type> [DEFAULT]`
+fn parameter_hover_signature(name: &str, signature: &str) -> String {
+    let (signature, default) = match signature.strip_suffix(PARAMETER_DEFAULT_SUFFIX) {
+        Some(signature) => (signature, PARAMETER_DEFAULT_SUFFIX),
+        None => (signature, ""),
+    };
+    match signature.rsplit_once(' ') {
+        Some((param_type, mode)) => {
+            let mode = match mode {
+                "InOut" => "IN OUT".to_string(),
+                other => other.to_uppercase(),
+            };
+            format!("{} {} {}{}", name, mode, param_type, default)
+        }
+        None => format!("{} {}{}", name, signature, default),
+    }
+}
+
+/// Suffix on a parameter symbol's signature marking that the parameter has a default value
+pub(crate) const PARAMETER_DEFAULT_SUFFIX: &str = " DEFAULT";
+
 /// Types of symbols
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SymbolKind {
@@ -44,6 +115,7 @@ pub enum SymbolKind {
     Client,
     ClientLayout,
     ClientCommand,
+    State,
 }
 
 impl std::fmt::Display for SymbolKind {
@@ -71,6 +143,38 @@ impl std::fmt::Display for SymbolKind {
             SymbolKind::Client => write!(f, "Client"),
             SymbolKind::ClientLayout => write!(f, "Client Layout"),
             SymbolKind::ClientCommand => write!(f, "Client Command"),
+            SymbolKind::State => write!(f, "State"),
+        }
+    }
+}
+
+impl SymbolKind {
+    /// The closest LSP `SymbolKind` number, used for document symbols and completion
+    pub fn to_lsp_kind(&self) -> u8 {
+        match self {
+            SymbolKind::Package => 3,              // Namespace
+            SymbolKind::Procedure => 6,            // Method
+            SymbolKind::Function => 12,            // Function
+            SymbolKind::Variable => 13,            // Variable
+            SymbolKind::Parameter => 13,           // Variable
+            SymbolKind::Type => 23,                // Struct
+            SymbolKind::Constant => 14,            // Constant
+            SymbolKind::Exception => 24,           // Event
+            SymbolKind::Cursor => 13,              // Variable
+            SymbolKind::Entity => 5,               // Class
+            SymbolKind::EntityAttribute => 8,      // Field
+            SymbolKind::EntityKey => 20,           // Key
+            SymbolKind::Enumeration => 10,         // Enum
+            SymbolKind::EnumerationValue => 22,    // EnumMember
+            SymbolKind::View => 5,                 // Class
+            SymbolKind::ViewColumn => 8,           // Field
+            SymbolKind::Projection => 2,           // Module
+            SymbolKind::ProjectionAttribute => 7,  // Property
+            SymbolKind::ProjectionAction => 6,     // Method
+            SymbolKind::Client => 2,               // Module
+            SymbolKind::ClientLayout => 19,        // Object
+            SymbolKind::ClientCommand => 24,       // Event
+            SymbolKind::State => 22,               // EnumMember
         }
     }
 }
@@ -92,6 +196,9 @@ pub enum ReferenceKind {
     Call,
     Assignment,
     Declaration,
+    /// A parameter or variable typed with a declared type, or anchored to a table or
+    /// column with `%TYPE` / `%ROWTYPE`
+    TypeUsage,
 }
 
 impl std::fmt::Display for ReferenceKind {
@@ -102,6 +209,7 @@ impl std::fmt::Display for ReferenceKind {
             ReferenceKind::Call => write!(f, "Call"),
             ReferenceKind::Assignment => write!(f, "Assignment"),
             ReferenceKind::Declaration => write!(f, "Declaration"),
+            ReferenceKind::TypeUsage => write!(f, "TypeUsage"),
         }
     }
 }
@@ -128,13 +236,20 @@ impl<'a> SymbolIndexer<'a> {
         };
         
         match ast {
-            AstNode::PlSql(node) => self.index_plsql_node(file_id, &file_path, node, None)?,
+            AstNode::PlSql(node) => {
+                self.index_plsql_node(file_id, &file_path, node, None)?;
+            }
             AstNode::Entity(node) => self.index_entity_node(file_id, &file_path, node)?,
             AstNode::Enumeration(node) => self.index_enumeration_node(file_id, &file_path, node)?,
             AstNode::Views(node) => self.index_views_node(file_id, &file_path, node)?,
             AstNode::Storage(node) => self.index_storage_node(file_id, &file_path, node)?,
             AstNode::MarbleProjection(node) => self.index_marble_projection_node(file_id, &file_path, node)?,
             AstNode::MarbleClient(node) => self.index_marble_client_node(file_id, &file_path, node)?,
+            AstNode::SourceFile { children, .. } => {
+                for child in children {
+                    self.index_ast(file_path.as_ref(), child)?;
+                }
+            }
         }
         
         Ok(())
@@ -146,8 +261,8 @@ impl<'a> SymbolIndexer<'a> {
         file_path: P,
         node: &PlSqlNode,
         parent_id: Option<i64>,
-    ) -> Result<()> {
-        match node {
+    ) -> Result<i64> {
+        let symbol_id = match node {
             PlSqlNode::Package { name, declarations, body, span: _, .. } => {
                 let symbol_id = self.store_symbol(
                     file_id,
@@ -160,9 +275,15 @@ impl<'a> SymbolIndexer<'a> {
                 )?;
                 
                 // Index declarations
+                let mut forward_declarations = Vec::new();
                 for declaration in declarations {
-                    self.index_plsql_declaration(file_id, &file_path, declaration, Some(symbol_id))?;
+                    let declaration_id =
+                        self.index_plsql_declaration(file_id, &file_path, declaration, Some(symbol_id))?;
+                    if let PlSqlDeclaration::SubprogramSpec { .. } = declaration {
+                        forward_declarations.push((declaration_id, declaration));
+                    }
                 }
+                self.link_forward_declarations(file_id, &forward_declarations, declarations)?;
                 
                 // Index body statements
                 if let Some(body_statements) = body {
@@ -170,9 +291,11 @@ impl<'a> SymbolIndexer<'a> {
                         self.index_plsql_statement(file_id, &file_path, statement, Some(symbol_id))?;
                     }
                 }
+                
+                symbol_id
             }
             
-            PlSqlNode::Procedure { name, parameters, body, span: _, .. } => {
+            PlSqlNode::Procedure { name, parameters, annotations, declarations, body, span: _, .. } => {
                 let signature = self.build_procedure_signature(name, parameters);
                 let symbol_id = self.store_symbol(
                     file_id,
@@ -183,20 +306,34 @@ impl<'a> SymbolIndexer<'a> {
                     Some(&signature),
                     None,
                 )?;
+                self.store_annotations(symbol_id, annotations)?;
                 
                 // Index parameters
                 for parameter in parameters {
                     self.index_parameter(file_id, &file_path, parameter, Some(symbol_id))?;
                 }
                 
+                // Index local declarations and nested subprograms
+                for declaration in declarations {
+                    self.index_plsql_declaration(file_id, &file_path, declaration, Some(symbol_id))?;
+                }
+                
                 // Index body statements
                 for statement in body {
                     self.index_plsql_statement(file_id, &file_path, statement, Some(symbol_id))?;
                 }
+                
+                symbol_id
             }
             
-            PlSqlNode::Function { name, parameters, return_type, body, span: _, .. } => {
-                let signature = self.build_function_signature(name, parameters, return_type);
+            PlSqlNode::Function { name, parameters, return_type, is_pipelined, result_cached, annotations, declarations, body, .. } => {
+                let mut signature = self.build_function_signature(name, parameters, return_type);
+                if *is_pipelined {
+                    signature.push_str(" PIPELINED");
+                }
+                if *result_cached {
+                    signature.push_str(" RESULT_CACHE");
+                }
                 let symbol_id = self.store_symbol(
                     file_id,
                     &name.name,
@@ -206,43 +343,57 @@ impl<'a> SymbolIndexer<'a> {
                     Some(&signature),
                     None,
                 )?;
+                self.store_annotations(symbol_id, annotations)?;
                 
                 // Index parameters
                 for parameter in parameters {
                     self.index_parameter(file_id, &file_path, parameter, Some(symbol_id))?;
                 }
                 
+                // Index local declarations and nested subprograms
+                for declaration in declarations {
+                    self.index_plsql_declaration(file_id, &file_path, declaration, Some(symbol_id))?;
+                }
+                
                 // Index body statements
                 for statement in body {
                     self.index_plsql_statement(file_id, &file_path, statement, Some(symbol_id))?;
                 }
+                
+                symbol_id
             }
-        }
+        };
         
-        Ok(())
+        Ok(symbol_id)
     }
     
     fn index_plsql_declaration<P: AsRef<Path>>(
         &mut self,
         file_id: i64,
-        _file_path: P,
+        file_path: P,
         declaration: &PlSqlDeclaration,
         parent_id: Option<i64>,
-    ) -> Result<()> {
-        match declaration {
-            PlSqlDeclaration::Variable { name, type_name, default_value: _, span: _ } => {
-                self.store_symbol(
+    ) -> Result<i64> {
+        let symbol_id = match declaration {
+            PlSqlDeclaration::Variable { name, type_name, default_value, span: _ } => {
+                let signature = match default_value {
+                    Some(value) => format!("{} := {}", type_name, value),
+                    None => type_name.to_string(),
+                };
+                let symbol_id = self.store_symbol(
                     file_id,
                     &name.name,
                     SymbolKind::Variable,
                     &name.span,
                     parent_id,
-                    Some(&type_name.name),
+                    Some(&signature),
                     None,
                 )?;
+                self.store_type_usage(file_id, type_name)?;
+                symbol_id
             }
             
-            PlSqlDeclaration::Cursor { name, query, span: _ } => {
+            PlSqlDeclaration::Cursor { name, query, for_update: _, span: _ } => {
                 self.store_symbol(
                     file_id,
                     &name.name,
@@ -251,7 +402,7 @@ impl<'a> SymbolIndexer<'a> {
                     parent_id,
                     Some(query),
                     None,
-                )?;
+                )?
             }
             
             PlSqlDeclaration::Exception { name, span: _ } => {
@@ -263,6 +414,78 @@ impl<'a> SymbolIndexer<'a> {
                     parent_id,
                     None,
                     None,
+                )?
+            }
+            
+            PlSqlDeclaration::SubprogramSpec { name, parameters, return_type, span: _ } => {
+                let (kind, signature) = match return_type {
+                    Some(return_type) => (
+                        SymbolKind::Function,
+                        self.build_function_signature(name, parameters, return_type),
+                    ),
+                    None => (
+                        SymbolKind::Procedure,
+                        self.build_procedure_signature(name, parameters),
+                    ),
+                };
+                self.store_symbol(
+                    file_id,
+                    &name.name,
+                    kind,
+                    &name.span,
+                    parent_id,
+                    Some(&signature),
+                    None,
+                )?
+            }
+            
+            PlSqlDeclaration::Subprogram(node) => {
+                self.index_plsql_node(file_id, file_path.as_ref(), node, parent_id)?
+            }
+        };
+        
+        Ok(symbol_id)
+    }
+    
+    /// Record a `Declaration` reference from each forward declaration to the
+    /// implementation with the same signature in the same declaration section
+    fn link_forward_declarations(
+        &mut self,
+        file_id: i64,
+        forward_declarations: &[(i64, &PlSqlDeclaration)],
+        declarations: &[PlSqlDeclaration],
+    ) -> Result<()> {
+        for (declaration_id, declaration) in forward_declarations {
+            let declared_signature = match declaration {
+                PlSqlDeclaration::SubprogramSpec { name, parameters, return_type: Some(return_type), .. } => {
+                    self.build_function_signature(name, parameters, return_type)
+                }
+                PlSqlDeclaration::SubprogramSpec { name, parameters, return_type: None, .. } => {
+                    self.build_procedure_signature(name, parameters)
+                }
+                _ => continue,
+            };
+            
+            let implementation = declarations.iter().find_map(|candidate| match candidate {
+                PlSqlDeclaration::Subprogram(node) => self
+                    .build_node_signature(node)
+                    .filter(|(signature, _)| signature.eq_ignore_ascii_case(&declared_signature)),
+                _ => None,
+            });
+            
+            if let Some((_, name)) = implementation {
+                let span = &name.span;
+                self.database.store_reference(
+                    *declaration_id,
+                    file_id,
+                    span.start.line,
+                    span.start.column,
+                    span.end.line,
+                    span.end.column,
+                    span.start.offset,
+                    span.end.offset,
+                    &ReferenceKind::Declaration.to_string(),
+                    None,
                 )?;
             }
         }
@@ -278,47 +501,145 @@ impl<'a> SymbolIndexer<'a> {
         parent_id: Option<i64>,
     ) -> Result<()> {
         match statement {
-            PlSqlStatement::Assignment { target, value: _, span: _ } => {
+            PlSqlStatement::Assignment { target, value: _, span: _, trivia: _ } => {
                 // Store reference to the target variable
-                self.store_reference(
+                self.store_local_reference(
                     file_id,
-                    &target.name,
-                    &target.span,
+                    parent_id,
+                    target,
                     ReferenceKind::Assignment,
                 )?;
             }
             
-            PlSqlStatement::If { condition: _, then_branch, else_branch, span: _ } => {
+            PlSqlStatement::If { condition: _, then_branch, else_branch, span: _, trivia: _ } => {
                 // Index expressions and statements in branches
                 for stmt in then_branch {
-                    self.index_plsql_statement(file_id, &file_path, stmt, parent_id)?;
+                    self.index_plsql_statement(file_id, file_path.as_ref(), stmt, parent_id)?;
                 }
                 
                 if let Some(else_stmts) = else_branch {
                     for stmt in else_stmts {
-                        self.index_plsql_statement(file_id, &file_path, stmt, parent_id)?;
+                        self.index_plsql_statement(file_id, file_path.as_ref(), stmt, parent_id)?;
                     }
                 }
             }
             
-            PlSqlStatement::Loop { body, span: _ } => {
+            PlSqlStatement::Loop { body, span: _, trivia: _ } => {
                 for stmt in body {
-                    self.index_plsql_statement(file_id, &file_path, stmt, parent_id)?;
+                    self.index_plsql_statement(file_id, file_path.as_ref(), stmt, parent_id)?;
                 }
             }
             
-            PlSqlStatement::Return { value: _, span: _ } => {
+            PlSqlStatement::Return { value: _, span: _, trivia: _ } => {
                 // Index return statement
             }
             
-            PlSqlStatement::Call { name, arguments: _, span: _ } => {
-                // Store reference to the called procedure/function
+            PlSqlStatement::Call { name, arguments: _, span: _, trivia: _ } => {
+                // super(...) calls the overridden implementation, which lives in another layer
+                if name.is_super() {
+                    return Ok(());
+                }
+                
+                // Store reference to the called procedure/function, made from the enclosing one
+                self.store_reference_from(file_id, name, ReferenceKind::Call, parent_id)?;
+            }
+            
+            PlSqlStatement::SelectInto { into, query: _, span: _, trivia: _ } => {
+                // Store references to the variables being assigned
+                for target in into {
+                    self.store_local_reference(
+                        file_id,
+                        parent_id,
+                        target,
+                        ReferenceKind::Assignment,
+                    )?;
+                }
+            }
+            
+            PlSqlStatement::Raise { exception, span: _, trivia: _ } => {
+                // Store reference to a named exception
+                if let Some(exception) = exception {
+                    self.store_local_reference(
+                        file_id,
+                        parent_id,
+                        exception,
+                        ReferenceKind::Usage,
+                    )?;
+                }
+            }
+            
+            PlSqlStatement::Label { .. } | PlSqlStatement::Goto { .. } => {
+                // Labels are local to their subprogram and not indexed
+            }
+            
+            PlSqlStatement::Fetch { cursor, into, bulk_collect: _, limit: _, span: _, trivia: _ } => {
+                // Store references to the cursor and the variables being assigned
+                self.store_local_reference(
+                    file_id,
+                    parent_id,
+                    cursor,
+                    ReferenceKind::Usage,
+                )?;
+                for target in into {
+                    self.store_local_reference(
+                        file_id,
+                        parent_id,
+                        target,
+                        ReferenceKind::Assignment,
+                    )?;
+                }
+            }
+            
+            PlSqlStatement::Dml { kind: _, target_table, raw: _, returning, span: _, trivia: _ } => {
+                // Store reference to the table being modified
                 self.store_reference(
                     file_id,
-                    &name.name,
-                    &name.span,
-                    ReferenceKind::Call,
+                    target_table,
+                    ReferenceKind::Usage,
                 )?;
+                
+                if let Some(returning) = returning {
+                    // Returned columns belong to the modified table
+                    let table = target_table.name.rsplit('.').next().unwrap_or(&target_table.name);
+                    for column in &returning.columns {
+                        let Expression::Identifier(column) = column else {
+                            continue;
+                        };
+                        let qualified = Identifier {
+                            name: if column.name.contains('.') {
+                                column.name.clone()
+                            } else {
+                                format!("{}.{}", table, column.name)
+                            },
+                            span: column.span.clone(),
+                        };
+                        self.store_reference(file_id, &qualified, ReferenceKind::Usage)?;
+                    }
+                    for target in &returning.into {
+                        self.store_local_reference(
+                            file_id,
+                            parent_id,
+                            target,
+                            ReferenceKind::Assignment,
+                        )?;
+                    }
+                }
+            }
+            
+            PlSqlStatement::Block { declarations, body, exception_handlers, span: _, trivia: _ } => {
+                for declaration in declarations {
+                    self.index_plsql_declaration(file_id, file_path.as_ref(), declaration, parent_id)?;
+                }
+                
+                for stmt in body {
+                    self.index_plsql_statement(file_id, file_path.as_ref(), stmt, parent_id)?;
+                }
+                
+                for handler in exception_handlers {
+                    for stmt in &handler.body {
+                        self.index_plsql_statement(file_id, file_path.as_ref(), stmt, parent_id)?;
+                    }
+                }
             }
         }
         
@@ -332,7 +653,10 @@ impl<'a> SymbolIndexer<'a> {
         parameter: &Parameter,
         parent_id: Option<i64>,
     ) -> Result<()> {
-        let signature = format!("{} {:?}", parameter.param_type.name, parameter.mode);
+        let mut signature = format!("{} {:?}", parameter.param_type, parameter.mode);
+        if parameter.default_value.is_some() {
+            signature.push_str(PARAMETER_DEFAULT_SUFFIX);
+        }
         
         self.store_symbol(
             file_id,
@@ -343,10 +667,21 @@ impl<'a> SymbolIndexer<'a> {
             Some(&signature),
             None,
         )?;
+        self.store_type_usage(file_id, &parameter.param_type)?;
         
         Ok(())
     }
     
+    /// Record a `TypeUsage` reference from a type name such as `Customer_API.Public_Rec` or
+    /// an anchored type such as `customer_tab.customer_id%TYPE` to the indexed type, column or table
+    fn store_type_usage(&mut self, file_id: i64, type_name: &Type) -> Result<()> {
+        let target = SymbolSearcher::new(self.database).resolve_type_reference(&type_name.name)?;
+        match target.and_then(|symbol| symbol.id) {
+            Some(symbol_id) => self.store_resolved_reference(symbol_id, file_id, &type_name.span, ReferenceKind::TypeUsage, None),
+            None => Ok(()),
+        }
+    }
+    
     fn index_entity_node<P: AsRef<Path>>(
         &mut self,
         file_id: i64,
@@ -389,6 +724,31 @@ impl<'a> SymbolIndexer<'a> {
             )?;
         }
         
+        // Index states and link each transition to the states it connects
+        if let Some(state_machine) = &node.state_machine {
+            let mut state_ids = HashMap::new();
+            for state in &state_machine.states {
+                let state_id = self.store_symbol(
+                    file_id,
+                    &state.name.name,
+                    SymbolKind::State,
+                    &state.name.span,
+                    Some(symbol_id),
+                    None,
+                    None,
+                )?;
+                state_ids.insert(state.name.normalized(), state_id);
+            }
+            
+            for transition in &state_machine.transitions {
+                for state in [&transition.from_state, &transition.to_state] {
+                    if let Some(&state_id) = state_ids.get(&state.normalized()) {
+                        self.store_resolved_reference(state_id, file_id, &state.span, ReferenceKind::Usage, None)?;
+                    }
+                }
+            }
+        }
+        
         Ok(())
     }
     
@@ -410,7 +770,7 @@ impl<'a> SymbolIndexer<'a> {
         
         // Index enumeration values
         for value in &node.values {
-            self.store_symbol(
+            let value_id = self.store_symbol(
                 file_id,
                 &value.name.name,
                 SymbolKind::EnumerationValue,
@@ -419,6 +779,9 @@ impl<'a> SymbolIndexer<'a> {
                 Some(&value.client_value.as_deref().unwrap_or("")),
                 None,
             )?;
+            if let Some(label) = &value.label_text {
+                self.database.set_symbol_label(value_id, label)?;
+            }
         }
         
         Ok(())
@@ -430,6 +793,18 @@ impl<'a> SymbolIndexer<'a> {
         _file_path: P,
         node: &ViewsNode,
     ) -> Result<()> {
+        // Columns with a `Ref` use the entity they reference
+        let view_columns = node.views.iter().flat_map(|view| &view.columns);
+        for column in node.column_definitions.iter().chain(view_columns) {
+            if let Some(reference) = &column.reference {
+                let target = Identifier {
+                    name: reference.clone(),
+                    span: column.span.clone(),
+                };
+                self.store_reference(file_id, &target, ReferenceKind::Usage)?;
+            }
+        }
+        
         // Index each view definition
         for view in &node.views {
             let symbol_id = self.store_symbol(
@@ -441,10 +816,11 @@ impl<'a> SymbolIndexer<'a> {
                 None,
                 None,
             )?;
+            self.database.set_symbol_visibility(symbol_id, &format!("{:?}", view.visibility))?;
             
-            // Index view columns
+            // Index view columns, marking private ones so API-surface queries can skip them
             for column in &view.columns {
-                self.store_symbol(
+                let column_id = self.store_symbol(
                     file_id,
                     &column.name.name,
                     SymbolKind::ViewColumn,
@@ -453,6 +829,16 @@ impl<'a> SymbolIndexer<'a> {
                     column.datatype.as_deref(),
                     None,
                 )?;
+                self.database.set_symbol_visibility(column_id, &format!("{:?}", column.visibility))?;
+            }
+            
+            // Record usages of the tables and views the query selects from
+            for from_item in &view.query.from {
+                self.store_reference(
+                    file_id,
+                    &from_item.table,
+                    ReferenceKind::Usage,
+                )?;
             }
         }
         
@@ -520,7 +906,7 @@ impl<'a> SymbolIndexer<'a> {
                         }
                     }
                 }
-                StorageDefinition::Index { name, .. } => {
+                StorageDefinition::Index { name, table_name, .. } => {
                     self.store_symbol(
                         file_id,
                         &name.name,
@@ -530,6 +916,13 @@ impl<'a> SymbolIndexer<'a> {
                         None,
                         None,
                     )?;
+                    
+                    // Store reference to the indexed table
+                    self.store_reference(
+                        file_id,
+                        table_name,
+                        ReferenceKind::Usage,
+                    )?;
                 }
                 StorageDefinition::Sequence { name, .. } => {
                     self.store_symbol(
@@ -655,23 +1048,122 @@ impl<'a> SymbolIndexer<'a> {
         Ok(symbol_id)
     }
     
+    /// Record a subprogram's annotations, one per line
+    fn store_annotations(&mut self, symbol_id: i64, annotations: &[IfsAnnotation]) -> Result<()> {
+        if annotations.is_empty() {
+            return Ok(());
+        }
+        let lines: Vec<String> = annotations.iter().map(ToString::to_string).collect();
+        self.database.set_symbol_annotations(symbol_id, &lines.join("\n"))
+    }
+    
+    /// Store a reference to a name that is usually declared in the enclosing subprogram,
+    /// such as an assignment target. Unqualified names only resolve to the parameters and
+    /// locals of `scope_id`; anything else is predefined or not indexed, so it is dropped.
+    fn store_local_reference(
+        &mut self,
+        file_id: i64,
+        scope_id: Option<i64>,
+        name: &Identifier,
+        kind: ReferenceKind,
+    ) -> Result<()> {
+        if name.name.contains('.') {
+            return self.store_reference(file_id, name, kind);
+        }
+        let Some(scope_id) = scope_id else {
+            return Ok(());
+        };
+        let normalized = name.normalized();
+        let local = self
+            .database
+            .get_child_symbols(scope_id)?
+            .into_iter()
+            .find(|symbol| normalize_identifier(&symbol.name) == normalized);
+        match local {
+            Some(symbol) => self.store_resolved_reference(symbol.id, file_id, &name.span, kind, None),
+            None => Ok(()),
+        }
+    }
+    
+    /// Store a reference to the symbol `name` resolves to, comparing normalized names.
+    /// Names that do not resolve yet are queued for `Index::resolve_pending_references`.
     fn store_reference(
         &mut self,
-        _file_id: i64,
-        _name: &str,
-        _span: &Span,
-        _kind: ReferenceKind,
+        file_id: i64,
+        name: &Identifier,
+        kind: ReferenceKind,
+    ) -> Result<()> {
+        self.store_reference_from(file_id, name, kind, None)
+    }
+    
+    /// Store a reference made from the body of the symbol `caller_id`
+    fn store_reference_from(
+        &mut self,
+        file_id: i64,
+        name: &Identifier,
+        kind: ReferenceKind,
+        caller_id: Option<i64>,
     ) -> Result<()> {
-        // For now, just store without linking to symbol_id
-        // In a real implementation, we'd need to resolve the symbol first
+        let normalized = name.normalized();
+        let (qualifier, target) = match normalized.rsplit_once('.') {
+            Some((qualifier, target)) => (qualifier.rsplit('.').next(), target),
+            None => (None, normalized.as_str()),
+        };
+        
+        match self.database.resolve_symbol(target, qualifier, file_id)? {
+            Some(symbol_id) => self.store_resolved_reference(symbol_id, file_id, &name.span, kind, caller_id),
+            // Predefined names are never indexed, so there is nothing to wait for
+            None if is_predefined(qualifier, target) => Ok(()),
+            None => {
+                // The target may live in a file that has not been indexed yet
+                self.database
+                    .store_pending_reference(file_id, target, qualifier, &name.span, &kind.to_string(), caller_id)?;
+                Ok(())
+            }
+        }
+    }
+    
+    /// Store a reference to a symbol whose id is already known
+    fn store_resolved_reference(
+        &mut self,
+        symbol_id: i64,
+        file_id: i64,
+        span: &Span,
+        kind: ReferenceKind,
+        caller_id: Option<i64>,
+    ) -> Result<()> {
+        self.database.store_reference(
+            symbol_id,
+            file_id,
+            span.start.line,
+            span.start.column,
+            span.end.line,
+            span.end.column,
+            span.start.offset,
+            span.end.offset,
+            &kind.to_string(),
+            caller_id,
+        )?;
         
         Ok(())
     }
     
+    fn build_node_signature<'n>(&self, node: &'n PlSqlNode) -> Option<(String, &'n Identifier)> {
+        match node {
+            PlSqlNode::Procedure { name, parameters, .. } => {
+                Some((self.build_procedure_signature(name, parameters), name))
+            }
+            PlSqlNode::Function { name, parameters, return_type, .. } => {
+                Some((self.build_function_signature(name, parameters, return_type), name))
+            }
+            PlSqlNode::Package { .. } => None,
+        }
+    }
+    
     fn build_procedure_signature(&self, name: &Identifier, parameters: &[Parameter]) -> String {
         let param_strings: Vec<String> = parameters
             .iter()
-            .map(|p| format!("{} {:?} {}", p.name.name, p.mode, p.param_type.name))
+            .map(|p| format!("{} {:?} {}", p.name.name, p.mode, p.param_type))
             .collect();
         
         format!("{}({})", name.name, param_strings.join(", "))
@@ -680,10 +1172,27 @@ impl<'a> SymbolIndexer<'a> {
     fn build_function_signature(&self, name: &Identifier, parameters: &[Parameter], return_type: &Type) -> String {
         let param_strings: Vec<String> = parameters
             .iter()
-            .map(|p| format!("{} {:?} {}", p.name.name, p.mode, p.param_type.name))
+            .map(|p| format!("{} {:?} {}", p.name.name, p.mode, p.param_type))
             .collect();
         
-        format!("{}({}) RETURN {}", name.name, param_strings.join(", "), return_type.name)
+        format!("{}({}) RETURN {}", name.name, param_strings.join(", "), return_type)
+    }
+}
+
+/// Names Oracle defines that are referenced without ever being declared in indexed source
+const PREDEFINED_NAMES: &[&str] = &[
+    "RAISE_APPLICATION_ERROR", "NO_DATA_FOUND", "TOO_MANY_ROWS", "DUP_VAL_ON_INDEX", "VALUE_ERROR",
+    "ZERO_DIVIDE", "INVALID_NUMBER", "INVALID_CURSOR", "CURSOR_ALREADY_OPEN", "OTHERS", "DUAL",
+];
+
+/// Whether a reference names something Oracle supplies: a predefined exception or procedure,
+/// or a member of a `SYS`, `STANDARD`, `DBMS_*` or `UTL_*` package
+fn is_predefined(qualifier: Option<&str>, target: &str) -> bool {
+    match qualifier {
+        Some(qualifier) => {
+            matches!(qualifier, "SYS" | "STANDARD") || qualifier.starts_with("DBMS_") || qualifier.starts_with("UTL_")
+        }
+        None => PREDEFINED_NAMES.contains(&target),
     }
 }
 
@@ -710,7 +1219,13 @@ impl From<SymbolRow> for SymbolInfo {
             file_path: row.file_path,
             signature: row.signature,
             documentation: row.documentation,
-            parent: None, // TODO: Resolve parent relationships
+            annotations: row
+                .annotations
+                .map(|annotations| annotations.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            label: row.label,
+            visibility: row.visibility,
+            parent: row.parent.map(|parent| Box::new(SymbolInfo::from(*parent))),
         }
     }
 }
@@ -742,6 +1257,7 @@ impl std::str::FromStr for SymbolKind {
             "Client" => Ok(SymbolKind::Client),
             "Client Layout" => Ok(SymbolKind::ClientLayout),
             "Client Command" => Ok(SymbolKind::ClientCommand),
+            "State" => Ok(SymbolKind::State),
             _ => Err(()),
         }
     }
@@ -757,7 +1273,122 @@ impl std::str::FromStr for ReferenceKind {
             "Call" => Ok(ReferenceKind::Call),
             "Assignment" => Ok(ReferenceKind::Assignment),
             "Declaration" => Ok(ReferenceKind::Declaration),
+            "TypeUsage" => Ok(ReferenceKind::TypeUsage),
             _ => Err(()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn symbol(name: &str, kind: SymbolKind, signature: Option<&str>, parent: Option<SymbolInfo>) -> SymbolInfo {
+        let position = Position { line: 1, column: 1, offset: 0 };
+        SymbolInfo {
+            id: None,
+            name: name.to_string(),
+            kind,
+            span: Span { start: position.clone(), end: position },
+            file_path: "Customer.plsql".to_string(),
+            signature: signature.map(str::to_string),
+            documentation: None,
+            annotations: Vec::new(),
+            label: None,
+            visibility: None,
+            parent: parent.map(Box::new),
+        }
+    }
+    
+    #[test]
+    fn test_function_hover_markdown() {
+        let package = symbol("Customer_API", SymbolKind::Package, None, None);
+        let mut function = symbol(
+            "Get_Name",
+            SymbolKind::Function,
+            Some("Get_Name(customer_id_ In VARCHAR2) RETURN VARCHAR2"),
+            Some(package),
+        );
+        function.documentation = Some("Returns the customer's name.".to_string());
+        
+        let hover = function.hover_markdown();
+        assert!(hover.starts_with("**Function** `Customer_API.Get_Name`"));
+        assert!(hover.contains("RETURN VARCHAR2"));
+        assert!(hover.contains("(customer_id_ In VARCHAR2)"));
+        assert!(hover.ends_with("Returns the customer's name."));
+    }
+    
+    #[test]
+    fn test_parameter_hover_shows_mode_and_type() {
+        let parameter = symbol("info_", SymbolKind::Parameter, Some("VARCHAR2 InOut"), None);
+        assert!(parameter.hover_markdown().contains("info_ IN OUT VARCHAR2"));
+        
+        let parameter = symbol("limit_", SymbolKind::Parameter, Some("NUMBER In DEFAULT"), None);
+        assert!(parameter.hover_markdown().contains("limit_ IN NUMBER DEFAULT"));
+    }
+    
+    #[test]
+    fn test_function_signature_includes_clauses() {
+        let ast = crate::parser::parse_source(
+            "FUNCTION Get_Lines RETURN line_table PIPELINED RESULT_CACHE IS\nBEGIN\n   RETURN;\nEND;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database).index_ast("lines.plsql", &ast).unwrap();
+        
+        let rows = database.search_symbols("Get_Lines").unwrap();
+        assert_eq!(rows[0].signature.as_deref(), Some("Get_Lines() RETURN line_table PIPELINED RESULT_CACHE"));
+    }
+    
+    #[test]
+    fn test_view_visibility_is_indexed_apart_from_the_signature() {
+        let ast = crate::parser::parse_source(
+            "VIEW Customer_Pub IS\n   Customer_No.Datatype = 'STRING(20)'\n   PRIVATE Internal_Note.Prompt = 'Note'\n\
+             SELECT customer_no customer_no, internal_note internal_note\nFROM customer_tab;\n\n\
+             PRIVATE VIEW Customer_Internal IS\n   Name.Prompt = 'Name'\nSELECT name name\nFROM customer_tab;\n",
+            crate::parser::Language::Views,
+        )
+        .unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database).index_ast("customer.views", &ast).unwrap();
+        
+        let symbol = |name: &str| SymbolInfo::from(database.search_symbols(name).unwrap().remove(0));
+        let customer_no = symbol("Customer_No");
+        assert!(!customer_no.is_private());
+        assert_eq!(customer_no.visibility.as_deref(), Some("Public"));
+        assert_eq!(customer_no.signature.as_deref(), Some("STRING(20)"));
+        assert!(symbol("Internal_Note").is_private());
+        assert!(!symbol("Customer_Pub").is_private());
+        assert!(symbol("Customer_Internal").is_private());
+    }
+    
+    #[test]
+    fn test_variable_signature_includes_initializer() {
+        let ast = crate::parser::parse_source(
+            "PACKAGE BODY Order_API IS\n   count_ NUMBER := 0;\n   status_ VARCHAR2(30) DEFAULT 'x';\nEND Order_API;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        
+        let mut database = Database::in_memory().unwrap();
+        SymbolIndexer::new(&mut database).index_ast("Order.plsql", &ast).unwrap();
+        
+        let rows = database.search_symbols("count_").unwrap();
+        assert_eq!(rows[0].signature.as_deref(), Some("NUMBER := 0"));
+        let rows = database.search_symbols("status_").unwrap();
+        assert_eq!(rows[0].signature.as_deref(), Some("VARCHAR2(30) := 'x'"));
+    }
+    
+    #[test]
+    fn test_lsp_kind_mapping() {
+        assert_eq!(SymbolKind::Package.to_lsp_kind(), 3);
+        assert_eq!(SymbolKind::Procedure.to_lsp_kind(), 6);
+        assert_eq!(SymbolKind::Function.to_lsp_kind(), 12);
+        assert_eq!(SymbolKind::Parameter.to_lsp_kind(), SymbolKind::Variable.to_lsp_kind());
+        assert_eq!(SymbolKind::Enumeration.to_lsp_kind(), 10);
+        assert_eq!(SymbolKind::EnumerationValue.to_lsp_kind(), 22);
+    }
+}
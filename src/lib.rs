@@ -9,11 +9,14 @@
 // - Marble DSL for frontend client layout and behaviour
 
 pub mod index;
+pub mod lsp;
 pub mod parser;
+pub mod refactor;
 pub mod static_analysis;
 pub mod utils;
 
 pub use index::*;
+pub use lsp::*;
 pub use parser::*;
 pub use static_analysis::*;
 pub use utils::*;
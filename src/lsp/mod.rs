@@ -0,0 +1,246 @@
+// Language server logic without a transport
+//
+// `LspBackend` ties the parser, index and static analysis together behind
+// the requests an editor integration needs. Callers own the JSON-RPC layer
+// and forward document events and queries to it.
+
+use crate::index::{Index, SymbolInfo, SymbolReference};
+use crate::parser::ast::{AstNode, LspPosition, Position, Span};
+use crate::parser::{parse_source, Language, LineIndex, ParseError};
+use crate::static_analysis::{analyze_source, AnalysisConfig, Diagnostic, Severity};
+use crate::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Open documents backed by an in-memory index
+pub struct LspBackend {
+    index: Index,
+    config: AnalysisConfig,
+    documents: HashMap<PathBuf, Document>,
+}
+
+/// State kept for one open document
+struct Document {
+    text: String,
+    /// Tree of the last version that parsed, kept while later edits fail to parse
+    ast: Option<AstNode>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl LspBackend {
+    /// Create a backend with the default analysis configuration
+    pub fn new() -> Result<Self> {
+        Self::with_config(AnalysisConfig::default())
+    }
+
+    /// Create a backend that analyzes documents with `config`
+    pub fn with_config(config: AnalysisConfig) -> Result<Self> {
+        Ok(Self {
+            index: Index::in_memory()?,
+            config,
+            documents: HashMap::new(),
+        })
+    }
+
+    /// Start tracking a document and index its contents
+    pub fn did_open<P: AsRef<Path>>(&mut self, path: P, text: String) -> Result<()> {
+        let document = Document {
+            text: String::new(),
+            ast: None,
+            diagnostics: Vec::new(),
+        };
+        self.documents.insert(path.as_ref().to_path_buf(), document);
+        self.update(path.as_ref(), text)
+    }
+
+    /// Replace the full text of an open document
+    pub fn did_change<P: AsRef<Path>>(&mut self, path: P, text: String) -> Result<()> {
+        if !self.documents.contains_key(path.as_ref()) {
+            return self.did_open(path, text);
+        }
+        self.update(path.as_ref(), text)
+    }
+
+    /// Stop tracking a document. Its symbols stay in the index so other
+    /// documents can still resolve references to it.
+    pub fn did_close<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.documents.remove(path.as_ref());
+        Ok(())
+    }
+
+    /// Symbols defined in a document, as of its last version that parsed
+    pub fn document_symbols<P: AsRef<Path>>(&self, path: P) -> Result<Vec<SymbolInfo>> {
+        self.index.get_file_symbols(path.as_ref())
+    }
+
+    /// Tree of the last version of an open document that parsed
+    pub fn tree<P: AsRef<Path>>(&self, path: P) -> Option<&AstNode> {
+        self.documents.get(path.as_ref())?.ast.as_ref()
+    }
+
+    /// Diagnostics for the current version of an open document
+    pub fn diagnostics<P: AsRef<Path>>(&self, path: P) -> &[Diagnostic] {
+        self.documents
+            .get(path.as_ref())
+            .map(|document| document.diagnostics.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The symbol named by the word at `position`, resolved from the document
+    pub fn definition<P: AsRef<Path>>(&self, path: P, position: LspPosition) -> Result<Option<SymbolInfo>> {
+        let path = path.as_ref();
+        let Some(document) = self.documents.get(path) else {
+            return Ok(None);
+        };
+        match name_at(&document.text, position) {
+            Some(name) => self.index.resolve_name(name, path),
+            None => Ok(None),
+        }
+    }
+
+    /// References to the symbol named by the word at `position`
+    pub fn references<P: AsRef<Path>>(&self, path: P, position: LspPosition) -> Result<Vec<SymbolReference>> {
+        match self.definition(path, position)? {
+            Some(symbol) => self.index.find_references(&symbol),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn update(&mut self, path: &Path, text: String) -> Result<()> {
+        let language = Language::from_path(path).unwrap_or(Language::PlSql);
+        let parsed = parse_source(&text, language);
+
+        let Some(document) = self.documents.get_mut(path) else {
+            return Ok(());
+        };
+        match parsed {
+            Ok(ast) => {
+                document.diagnostics = analyze_source(&ast, &text, &self.config)?;
+                self.index.index_file(path, &ast)?;
+                document.ast = Some(ast);
+            }
+            Err(error) => {
+                document.diagnostics = vec![parse_error_diagnostic(&error, &text)];
+            }
+        }
+        document.text = text;
+        Ok(())
+    }
+}
+
+/// Diagnostic for a document that failed to parse, placed at the offending token when known
+fn parse_error_diagnostic(error: &anyhow::Error, text: &str) -> Diagnostic {
+    let (line, column) = match error.downcast_ref::<ParseError>() {
+        Some(ParseError::UnexpectedToken { line, column, .. }) => (*line, *column),
+        _ => (1, 1),
+    };
+    let offset = LineIndex::new(text).offset(line, column).unwrap_or(0);
+    let position = Position { line, column, offset };
+
+    Diagnostic {
+        message: error.to_string(),
+        span: Span {
+            start: position.clone(),
+            end: position,
+        },
+        severity: Severity::Error,
+        code: Some("parse-error".to_string()),
+        source: "ifs-parser".to_string(),
+        related_information: Vec::new(),
+    }
+}
+
+/// The possibly qualified name around an LSP position, e.g. `Customer_API.Get_Name`
+fn name_at(text: &str, position: LspPosition) -> Option<&str> {
+    let line = text.split('\n').nth(position.line as usize)?;
+    let line_start = line.as_ptr() as usize - text.as_ptr() as usize;
+
+    // Walk UTF-16 code units to the byte offset of the position within the line
+    let mut units = 0;
+    let column = line
+        .char_indices()
+        .find(|(_, c)| {
+            units += c.len_utf16();
+            units > position.character as usize
+        })
+        .map_or(line.len(), |(i, _)| i);
+
+    let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '$' | '#' | '.');
+    let start = line[..column]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_name_char(*c))
+        .last()
+        .map_or(column, |(i, _)| i);
+    let end = line[column..]
+        .char_indices()
+        .find(|(_, c)| !is_name_char(*c))
+        .map_or(line.len(), |(i, _)| column + i);
+
+    let name = text[line_start + start..line_start + end].trim_matches('.');
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::ReferenceKind;
+
+    const SOURCE: &str = "PACKAGE BODY Customer_API IS\n   PROCEDURE Refresh IS\n   BEGIN\n      NULL;\n   END Refresh;\n\n   PROCEDURE Print IS\n   BEGIN\n      Refresh;\n   END Print;\nEND Customer_API;";
+
+    #[test]
+    fn test_did_open_then_document_symbols() {
+        let mut backend = LspBackend::new().unwrap();
+        backend.did_open("customer.plsql", SOURCE.to_string()).unwrap();
+
+        let names: Vec<String> = backend
+            .document_symbols("customer.plsql")
+            .unwrap()
+            .into_iter()
+            .map(|symbol| symbol.name)
+            .collect();
+        for expected in ["Customer_API", "Refresh", "Print"] {
+            assert!(names.iter().any(|name| name == expected), "{:?}", names);
+        }
+        assert!(!backend.diagnostics("customer.plsql").iter().any(|d| d.code.as_deref() == Some("parse-error")));
+    }
+
+    #[test]
+    fn test_definition_and_references_of_called_procedure() {
+        let mut backend = LspBackend::new().unwrap();
+        backend.did_open("customer.plsql", SOURCE.to_string()).unwrap();
+
+        // Inside `Refresh;` in the body of Print
+        let call = LspPosition { line: 8, character: 9 };
+        let definition = backend.definition("customer.plsql", call).unwrap().expect("definition");
+        assert_eq!(definition.name, "Refresh");
+        assert_eq!(definition.span.start.line, 2);
+
+        let references = backend.references("customer.plsql", call).unwrap();
+        assert!(references
+            .iter()
+            .any(|reference| reference.reference_kind == ReferenceKind::Call && reference.span.start.line == 9));
+
+        let blank = LspPosition { line: 5, character: 0 };
+        assert_eq!(backend.definition("customer.plsql", blank).unwrap(), None);
+    }
+
+    #[test]
+    fn test_failed_parse_reports_error_and_keeps_symbols() {
+        let mut backend = LspBackend::new().unwrap();
+        backend.did_open("customer.plsql", SOURCE.to_string()).unwrap();
+        backend
+            .did_change("customer.plsql", "PACKAGE BODY Customer_API\nBEGIN".to_string())
+            .unwrap();
+
+        let diagnostics = backend.diagnostics("customer.plsql");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("parse-error"));
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(!backend.document_symbols("customer.plsql").unwrap().is_empty());
+        assert!(backend.tree("customer.plsql").is_some());
+
+        backend.did_close("customer.plsql").unwrap();
+        assert!(backend.diagnostics("customer.plsql").is_empty());
+    }
+}
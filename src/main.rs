@@ -1,11 +1,22 @@
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
+use ifs_parser::index::{Index, IndexOptions};
 use ifs_parser::parser::tree_sitter_simple::IfsPlsqlParser;
 use ifs_parser::parser::ast::AstNode;
+use ifs_parser::parser::{
+    check_format, parse_many, parse_source_with_options, tokenize, CancellationToken, ParseError, ParseOptions,
+};
+use ifs_parser::parser::Language;
+use ifs_parser::static_analysis::{
+    analyze_overrides, analyze_source, publish_diagnostics_params, render_diagnostic, AnalysisConfig, Diagnostic, DiagnosticCollection, Severity,
+};
+use ifs_parser::utils::file_utils::{changed_files_since, read_source};
+use ifs_parser::utils::performance::GLOBAL_PERF_MONITOR;
 use ifs_parser::Result;
 use std::fs;
 use std::path::Path;
-use std::time::Instant;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 fn main() -> Result<()> {
     ifs_parser::init()?;
@@ -20,7 +31,7 @@ fn main() -> Result<()> {
                 .long("file")
                 .value_name("FILE")
                 .help("Parse a single PL/SQL file")
-                .required_unless_present("directory"),
+                .required_unless_present_any(["directory", "json-schema"]),
         )
         .arg(
             Arg::new("directory")
@@ -28,16 +39,82 @@ fn main() -> Result<()> {
                 .long("directory")
                 .value_name("DIR")
                 .help("Parse all .plsql files in a directory")
-                .required_unless_present("file"),
+                .required_unless_present_any(["file", "json-schema"]),
         )
         .arg(
             Arg::new("output")
                 .short('o')
                 .long("output")
                 .value_name("OUTPUT")
-                .help("Output format: json, tree, summary")
+                .help("Output format: json, tree, summary, tokens, lsp")
                 .default_value("summary"),
         )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Print --output tokens as JSON")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("GIT_REF")
+                .help("Only parse files in the directory changed since a git ref")
+                .requires("directory"),
+        )
+        .arg(
+            Arg::new("max-file-bytes")
+                .long("max-file-bytes")
+                .value_name("BYTES")
+                .help("Skip files larger than this many bytes")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("timeout-ms")
+                .long("timeout-ms")
+                .value_name("MS")
+                .help("Give up on a file whose parse takes longer than this many milliseconds")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("index")
+                .long("index")
+                .value_name("DB")
+                .help("Incrementally index the directory into this database")
+                .requires("directory"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("With --index, print which files would be added, updated or removed")
+                .action(clap::ArgAction::SetTrue)
+                .requires("index"),
+        )
+        .arg(
+            Arg::new("analyze")
+                .long("analyze")
+                .help("Run static analysis on parsed files; with --index, check @Override targets")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("check-format")
+                .long("check-format")
+                .help("Report lines of the file that differ from the canonical layout, without modifying it")
+                .action(clap::ArgAction::SetTrue)
+                .requires("file"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Report time spent reading, parsing and analyzing")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("json-schema")
+                .long("json-schema")
+                .help("Print the JSON Schema of the AST and exit (requires the json-schema feature)")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("verbose")
                 .short('v')
@@ -48,15 +125,46 @@ fn main() -> Result<()> {
 
     let matches = app.get_matches();
 
+    if matches.get_flag("json-schema") {
+        return print_json_schema();
+    }
+
     if let Some(file_path) = matches.get_one::<String>("file") {
+        if matches.get_flag("check-format") {
+            return check_file_format(file_path);
+        }
         parse_single_file(file_path, &matches)?;
+    } else if let (Some(dir_path), Some(db_path)) =
+        (matches.get_one::<String>("directory"), matches.get_one::<String>("index"))
+    {
+        index_directory(dir_path, db_path, &matches)?;
     } else if let Some(dir_path) = matches.get_one::<String>("directory") {
         parse_directory(dir_path, &matches)?;
     }
 
+    let json_output = matches!(
+        matches.get_one::<String>("output").map(|s| s.as_str()),
+        Some("json") | Some("lsp")
+    );
+    if matches.get_flag("profile") && !json_output {
+        println!();
+        print!("{}", GLOBAL_PERF_MONITOR.report());
+    }
+
     Ok(())
 }
 
+#[cfg(feature = "json-schema")]
+fn print_json_schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&AstNode::json_schema())?);
+    Ok(())
+}
+
+#[cfg(not(feature = "json-schema"))]
+fn print_json_schema() -> Result<()> {
+    Err(anyhow::anyhow!("--json-schema requires building with `--features json-schema`"))
+}
+
 fn parse_single_file(file_path: &str, matches: &ArgMatches) -> Result<()> {
     let path = Path::new(file_path);
 
@@ -71,19 +179,50 @@ fn parse_single_file(file_path: &str, matches: &ArgMatches) -> Result<()> {
         file_path.cyan()
     );
 
+    if let Some(size) = exceeds_max_file_bytes(path, matches) {
+        match matches.get_one::<String>("output").map(|s| s.as_str()) {
+            Some("json") => {
+                let result = serde_json::json!({
+                    "success": false,
+                    "skipped": true,
+                    "file_path": file_path,
+                    "file_size": size,
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            }
+            _ => {
+                println!(
+                    "{} Skipping file: {} bytes exceeds --max-file-bytes",
+                    "Warning:".yellow().bold(),
+                    size
+                );
+            }
+        }
+        return Ok(());
+    }
+
     let start_time = Instant::now();
-    let content = fs::read_to_string(path)?;
+    let content = GLOBAL_PERF_MONITOR.time_it("read", || read_source(path))?;
+
+    if matches.get_one::<String>("output").map(|s| s.as_str()) == Some("tokens") {
+        return print_tokens(file_path, &content, matches.get_flag("json"));
+    }
 
     let mut parser = IfsPlsqlParser::new()?;
+    if let Some(ms) = matches.get_one::<u64>("timeout-ms") {
+        parser = parser.with_timeout_micros(ms.saturating_mul(1000));
+    }
 
-    match parser.parse(&content) {
+    match GLOBAL_PERF_MONITOR.time_it("parse", || parser.parse(&content)) {
         Ok(ast) => {
             let elapsed = start_time.elapsed();
+            let diagnostics = run_analysis(&ast, &content, matches)?;
 
             match matches.get_one::<String>("output").map(|s| s.as_str()) {
                 Some("json") => {
                     // Create a comprehensive result object
-                    let result = serde_json::json!({
+                    let mut result = serde_json::json!({
                         "success": true,
                         "file_path": file_path,
                         "parse_time_ms": elapsed.as_secs_f64() * 1000.0,
@@ -95,8 +234,19 @@ fn parse_single_file(file_path: &str, matches: &ArgMatches) -> Result<()> {
                         "ast": ast,
                         "timestamp": chrono::Utc::now().to_rfc3339()
                     });
+                    if let Some(diagnostics) = &diagnostics {
+                        result["diagnostics"] = serde_json::to_value(diagnostics)?;
+                        result["diagnostic_summary"] = severity_counts(diagnostics.iter());
+                    }
+                    attach_profile(&mut result, matches);
                     println!("{}", serde_json::to_string_pretty(&result)?);
                 }
+                Some("lsp") => {
+                    let uri = file_uri(path)?;
+                    let diagnostics = diagnostics.unwrap_or_default();
+                    let params = publish_diagnostics_params(&uri, &diagnostics, &content);
+                    println!("{}", serde_json::to_string_pretty(&params)?);
+                }
                 Some("tree") => {
                     // Output tree structure
                     println!("{} Tree view:", "AST:".blue().bold());
@@ -115,13 +265,16 @@ fn parse_single_file(file_path: &str, matches: &ArgMatches) -> Result<()> {
                         content.lines().count()
                     );
                     println!("  {} {} bytes", "File size:".bold(), content.len());
+                    if let Some(diagnostics) = &diagnostics {
+                        print_diagnostics(diagnostics, &content);
+                    }
                 }
             }
         }
         Err(e) => {
             match matches.get_one::<String>("output").map(|s| s.as_str()) {
                 Some("json") => {
-                    let error_result = serde_json::json!({
+                    let mut error_result = serde_json::json!({
                         "success": false,
                         "file_path": file_path,
                         "error": format!("{}", e),
@@ -132,6 +285,7 @@ fn parse_single_file(file_path: &str, matches: &ArgMatches) -> Result<()> {
                         },
                         "timestamp": chrono::Utc::now().to_rfc3339()
                     });
+                    attach_profile(&mut error_result, matches);
                     println!("{}", serde_json::to_string_pretty(&error_result)?);
                 }
                 _ => {
@@ -145,6 +299,30 @@ fn parse_single_file(file_path: &str, matches: &ArgMatches) -> Result<()> {
     Ok(())
 }
 
+/// Print a diff of the lines that need reformatting and exit with status 1 if there are any
+fn check_file_format(file_path: &str) -> Result<()> {
+    let path = Path::new(file_path);
+    let content = read_source(path)?;
+    let differences = check_format(&content, Language::PlSql)?;
+    if differences.is_empty() {
+        println!("{} {} is formatted", "Success:".green().bold(), file_path);
+        return Ok(());
+    }
+
+    for difference in &differences {
+        println!("{}:{}", file_path, difference.line);
+        println!("{}", format!("-{}", difference.original).red());
+        println!("{}", format!("+{}", difference.formatted).green());
+    }
+    println!(
+        "{} {} needs formatting ({} lines)",
+        "Warning:".yellow().bold(),
+        file_path,
+        differences.len()
+    );
+    std::process::exit(1);
+}
+
 fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
     let path = Path::new(dir_path);
 
@@ -163,7 +341,19 @@ fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
         dir_path.cyan()
     );
 
-    let plsql_files = find_plsql_files(path)?;
+    let plsql_files = match matches.get_one::<String>("since") {
+        Some(git_ref) => match changed_files_since(path, git_ref) {
+            Ok(files) => files
+                .into_iter()
+                .filter(|file| Language::from_path(file) == Some(Language::PlSql))
+                .collect(),
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+        },
+        None => find_plsql_files(path)?,
+    };
 
     if plsql_files.is_empty() {
         println!(
@@ -182,8 +372,11 @@ fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
     let start_time = Instant::now();
     let mut successful = 0;
     let mut failed = 0;
+    let mut skipped = 0;
     let mut total_lines = 0;
     let mut total_size = 0;
+    let mut analyzed_files = Vec::new();
+    let mut inputs = Vec::new();
 
     for (i, file_path) in plsql_files.iter().enumerate() {
         if matches.get_flag("verbose") {
@@ -195,26 +388,68 @@ fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
             );
         }
 
-        match fs::read_to_string(file_path) {
+        if let Some(size) = exceeds_max_file_bytes(file_path, matches) {
+            skipped += 1;
+            log::warn!(
+                "Skipping {}: {} bytes exceeds --max-file-bytes",
+                file_path.display(),
+                size
+            );
+            if matches.get_flag("verbose") {
+                println!(
+                    "    {} Skipped: {} bytes exceeds limit",
+                    "Warning:".yellow().bold(),
+                    size
+                );
+            }
+            continue;
+        }
+
+        match GLOBAL_PERF_MONITOR.time_it("read", || read_source(file_path)) {
             Ok(content) => {
                 total_lines += content.lines().count();
                 total_size += content.len();
+                inputs.push((file_path.clone(), content));
+            }
+            Err(e) => {
+                failed += 1;
+                if matches.get_flag("verbose") {
+                    println!("    {} Failed to read file: {}", "Error:".red().bold(), e);
+                }
+            }
+        }
+    }
 
-                let mut parser = IfsPlsqlParser::new()?;
-                match parser.parse(&content) {
-                    Ok(_) => successful += 1,
-                    Err(e) => {
-                        failed += 1;
-                        if matches.get_flag("verbose") {
-                            println!("    {} {}", "Error:".red().bold(), e);
-                        }
-                    }
+    let cancellation = cancel_on_interrupt();
+    let timeout = matches.get_one::<u64>("timeout-ms").map(|ms| Duration::from_millis(*ms));
+    let parsed = GLOBAL_PERF_MONITOR.time_it("parse", || match timeout {
+        Some(timeout) => {
+            let mut parsed = Vec::new();
+            for (file_path, content) in &inputs {
+                if cancellation.is_cancelled() {
+                    return Err(ParseError::Cancelled.into());
+                }
+                let language = Language::from_path(file_path).unwrap_or(Language::PlSql);
+                let options = ParseOptions::default().with_timeout(timeout);
+                parsed.push((file_path.clone(), parse_source_with_options(content, language, options)));
+            }
+            Ok(parsed)
+        }
+        None => parse_many(&inputs, Some(&cancellation)),
+    })?;
+
+    for ((file_path, result), (_, content)) in parsed.into_iter().zip(&inputs) {
+        match result {
+            Ok(ast) => {
+                successful += 1;
+                if let Some(diagnostics) = run_analysis(&ast, content, matches)? {
+                    analyzed_files.push((file_path.to_string_lossy().to_string(), diagnostics));
                 }
             }
             Err(e) => {
                 failed += 1;
                 if matches.get_flag("verbose") {
-                    println!("    {} Failed to read file: {}", "Error:".red().bold(), e);
+                    println!("    {} {}: {}", "Error:".red().bold(), file_path.display(), e);
                 }
             }
         }
@@ -229,13 +464,14 @@ fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
 
     match matches.get_one::<String>("output").map(|s| s.as_str()) {
         Some("json") => {
-            let result = serde_json::json!({
+            let mut result = serde_json::json!({
                 "success": true,
                 "directory_path": dir_path,
                 "summary": {
                     "total_files": plsql_files.len(),
                     "successful_parses": successful,
                     "failed_parses": failed,
+                    "skipped_files": skipped,
                     "success_rate": success_rate
                 },
                 "metrics": {
@@ -247,6 +483,21 @@ fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
                 },
                 "timestamp": chrono::Utc::now().to_rfc3339()
             });
+            if matches.get_flag("analyze") {
+                let files: Vec<serde_json::Value> = analyzed_files
+                    .iter()
+                    .map(|(file_path, diagnostics)| {
+                        serde_json::json!({
+                            "file_path": file_path,
+                            "diagnostics": diagnostics
+                        })
+                    })
+                    .collect();
+                result["files"] = serde_json::Value::Array(files);
+                result["summary"]["diagnostics"] =
+                    severity_counts(analyzed_files.iter().flat_map(|(_, d)| d.iter()));
+            }
+            attach_profile(&mut result, matches);
             println!("{}", serde_json::to_string_pretty(&result)?);
         }
         _ => {
@@ -264,6 +515,9 @@ fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
             if failed > 0 {
                 println!("  {} {}", "Failed:".bold(), failed.to_string().red());
             }
+            if skipped > 0 {
+                println!("  {} {}", "Skipped:".bold(), skipped.to_string().yellow());
+            }
             println!("  {} {} lines", "Total lines:".bold(), total_lines);
             println!(
                 "  {} {:.2} MB",
@@ -276,12 +530,240 @@ fn parse_directory(dir_path: &str, matches: &ArgMatches) -> Result<()> {
                 "Processing rate:".bold(),
                 plsql_files.len() as f64 / elapsed.as_secs_f64()
             );
+            if matches.get_flag("analyze") {
+                let diagnostics: Vec<Diagnostic> = analyzed_files
+                    .into_iter()
+                    .flat_map(|(_, diagnostics)| diagnostics)
+                    .collect();
+                print_diagnostic_totals(&diagnostics);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Cancelled by the first Ctrl-C, so a directory run stops before its next file
+static INTERRUPT: OnceLock<CancellationToken> = OnceLock::new();
+
+/// Get a token that the first Ctrl-C cancels; a second Ctrl-C terminates immediately
+#[cfg(unix)]
+fn cancel_on_interrupt() -> CancellationToken {
+    extern "C" fn on_interrupt(_signal: libc::c_int) {
+        // Both calls are async-signal-safe: an atomic load and store, and signal()
+        if let Some(token) = INTERRUPT.get() {
+            token.cancel();
+        }
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+
+    let token = INTERRUPT.get_or_init(CancellationToken::new).clone();
+    let handler: extern "C" fn(libc::c_int) = on_interrupt;
+    unsafe {
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+    }
+    token
+}
+
+/// Get a token that the first Ctrl-C cancels; without signal handling it is never cancelled
+#[cfg(not(unix))]
+fn cancel_on_interrupt() -> CancellationToken {
+    INTERRUPT.get_or_init(CancellationToken::new).clone()
+}
+
+/// Index a directory into `db_path`, or with `--dry-run` only print the plan
+fn index_directory(dir_path: &str, db_path: &str, matches: &ArgMatches) -> Result<()> {
+    if matches.get_flag("dry-run") {
+        // Plan against an in-memory copy so a dry run never creates or migrates the file
+        let index = if Path::new(db_path).exists() {
+            Index::restore_from(db_path)?
+        } else {
+            Index::in_memory()?
+        };
+        let plan = index.plan_directory(dir_path, Language::all())?;
+        let sections = [
+            ("Added", &plan.added),
+            ("Changed", &plan.changed),
+            ("Deleted", &plan.deleted),
+        ];
+        for (label, paths) in sections {
+            println!("{} {} file(s)", format!("{}:", label).blue().bold(), paths.len());
+            for path in paths {
+                println!("  {}", path.display());
+            }
+        }
+        println!("{} {} file(s)", "Unchanged:".blue().bold(), plan.unchanged.len());
+        return Ok(());
+    }
+
+    let mut index = Index::new(db_path)?;
+
+    let options = IndexOptions {
+        max_file_bytes: matches.get_one::<u64>("max-file-bytes").copied(),
+        parse_timeout: matches.get_one::<u64>("timeout-ms").map(|ms| Duration::from_millis(*ms)),
+        cancellation: Some(cancel_on_interrupt()),
+        ..IndexOptions::default()
+    };
+    let summary = index.index_directory_incremental(dir_path, &options)?;
+    for result in &summary.results {
+        match &result.status {
+            ifs_parser::index::FileIndexStatus::Failed { error } => {
+                eprintln!("{} {}: {}", "Error:".red().bold(), result.path.display(), error);
+            }
+            ifs_parser::index::FileIndexStatus::TimedOut { after } => {
+                eprintln!("{} {}: timed out after {:?}", "Error:".red().bold(), result.path.display(), after);
+            }
+            _ => {}
+        }
+    }
+    println!(
+        "{} {} indexed, {} unchanged, {} skipped, {} failed, {} timed out",
+        "Summary:".green().bold(),
+        summary.indexed_count(),
+        summary.unchanged_count(),
+        summary.skipped_count(),
+        summary.failed_count(),
+        summary.timed_out_count()
+    );
+
+    if matches.get_flag("analyze") {
+        check_overrides(&index, &summary)?;
+    }
+    Ok(())
+}
+
+/// Report `@Override` and `@Overtake` subprograms without exactly one base implementation
+fn check_overrides(index: &Index, summary: &ifs_parser::index::DirectoryIndexSummary) -> Result<()> {
+    let config = AnalysisConfig::default();
+    for result in &summary.results {
+        let is_indexed = matches!(
+            result.status,
+            ifs_parser::index::FileIndexStatus::Indexed | ifs_parser::index::FileIndexStatus::Unchanged
+        );
+        if !is_indexed || Language::from_path(&result.path) != Some(Language::PlSql) {
+            continue;
         }
+        let diagnostics = analyze_overrides(index, &result.path, &config)?;
+        if diagnostics.is_empty() {
+            continue;
+        }
+        let source = read_source(&result.path)?;
+        println!("{}", result.path.display().to_string().bold());
+        print_diagnostics(&diagnostics, &source);
+        println!();
+    }
+    Ok(())
+}
+
+/// Run static analysis when `--analyze` is set
+fn run_analysis(ast: &AstNode, source: &str, matches: &ArgMatches) -> Result<Option<Vec<Diagnostic>>> {
+    if !matches.get_flag("analyze") {
+        return Ok(None);
     }
+    let diagnostics = GLOBAL_PERF_MONITOR.time_it("analyze", || {
+        analyze_source(ast, source, &AnalysisConfig::default())
+    })?;
+    Ok(Some(diagnostics))
+}
 
+/// Absolute `file://` URI for `path`, percent-encoding characters not allowed in a URI path
+fn file_uri(path: &Path) -> Result<String> {
+    let absolute = fs::canonicalize(path)?;
+    let mut uri = String::from("file://");
+    let path = absolute.to_string_lossy().replace('\\', "/");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    Ok(uri)
+}
+
+/// Add the performance counters as a `profile` object when `--profile` is set
+fn attach_profile(result: &mut serde_json::Value, matches: &ArgMatches) {
+    if matches.get_flag("profile") {
+        result["profile"] = GLOBAL_PERF_MONITOR.report_json();
+    }
+}
+
+/// Count diagnostics by severity
+fn severity_counts<'a>(diagnostics: impl Iterator<Item = &'a Diagnostic>) -> serde_json::Value {
+    let (mut errors, mut warnings, mut info, mut hints) = (0, 0, 0, 0);
+    for diagnostic in diagnostics {
+        match diagnostic.severity {
+            Severity::Error => errors += 1,
+            Severity::Warning => warnings += 1,
+            Severity::Info => info += 1,
+            Severity::Hint => hints += 1,
+        }
+    }
+    serde_json::json!({
+        "total": errors + warnings + info + hints,
+        "error": errors,
+        "warning": warnings,
+        "info": info,
+        "hint": hints
+    })
+}
+
+fn print_diagnostics(diagnostics: &[Diagnostic], source: &str) {
+    for diagnostic in diagnostics {
+        println!("{}\n", render_diagnostic(diagnostic, source));
+    }
+    print_diagnostic_totals(diagnostics);
+}
+
+fn print_diagnostic_totals(diagnostics: &[Diagnostic]) {
+    let summary = diagnostics.iter().cloned().collect::<DiagnosticCollection>().summary();
+    print!("  {} {}", "Diagnostics:".bold(), summary);
+}
+
+/// Dump the lexer's tokens to help diagnose files that fail to parse
+fn print_tokens(file_path: &str, content: &str, as_json: bool) -> Result<()> {
+    let language = Path::new(file_path)
+        .extension()
+        .and_then(|ext| Language::from_extension(&format!(".{}", ext.to_string_lossy())))
+        .unwrap_or(Language::PlSql);
+    let tokens = tokenize(content, language, true);
+
+    if as_json {
+        let result = serde_json::json!({
+            "file_path": file_path,
+            "language": format!("{:?}", language),
+            "tokens": tokens,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    for (index, token) in tokens.iter().enumerate() {
+        println!(
+            "{:>6}  {:<16} {:<32} {}:{}",
+            index,
+            format!("{:?}", token.token_type),
+            format!("{:?}", token.value),
+            token.position.line,
+            token.position.column
+        );
+    }
     Ok(())
 }
 
+/// Return the file size if it exceeds the `--max-file-bytes` limit
+fn exceeds_max_file_bytes(path: &Path, matches: &ArgMatches) -> Option<u64> {
+    let max_file_bytes = *matches.get_one::<u64>("max-file-bytes")?;
+    let size = fs::metadata(path).ok()?.len();
+    (size > max_file_bytes).then_some(size)
+}
+
 fn find_plsql_files(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
     let mut files = Vec::new();
 
@@ -331,6 +813,12 @@ fn print_ast_tree(ast: &AstNode, indent: usize) {
             println!("{}🖥️  Marble Client Node", indent_str);
             // Add marble client-specific printing if needed
         }
+        AstNode::SourceFile { children, .. } => {
+            println!("{}📄 Source File: {} units", indent_str, children.len());
+            for child in children {
+                print_ast_tree(child, indent + 1);
+            }
+        }
     }
 }
 
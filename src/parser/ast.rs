@@ -8,9 +8,13 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
 
 /// Position information for source code elements
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "compact-spans", serde(into = "[usize; 3]", from = "[usize; 3]"))]
 pub struct Position {
     pub line: usize,
     pub column: usize,
@@ -19,20 +23,129 @@ pub struct Position {
 
 /// Span information covering a range in source code
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "compact-spans", serde(into = "[usize; 6]", from = "[usize; 6]"))]
 pub struct Span {
     pub start: Position,
     pub end: Position,
 }
 
+/// Compact `[line, column, offset]` form used by the `compact-spans` feature
+impl From<Position> for [usize; 3] {
+    fn from(position: Position) -> Self {
+        [position.line, position.column, position.offset]
+    }
+}
+
+impl From<[usize; 3]> for Position {
+    fn from([line, column, offset]: [usize; 3]) -> Self {
+        Self { line, column, offset }
+    }
+}
+
+/// Compact `[start line, start column, start offset, end line, end column, end offset]` form
+impl From<Span> for [usize; 6] {
+    fn from(span: Span) -> Self {
+        let [sl, sc, so] = <[usize; 3]>::from(span.start);
+        let [el, ec, eo] = <[usize; 3]>::from(span.end);
+        [sl, sc, so, el, ec, eo]
+    }
+}
+
+impl From<[usize; 6]> for Span {
+    fn from([sl, sc, so, el, ec, eo]: [usize; 6]) -> Self {
+        Self {
+            start: Position::from([sl, sc, so]),
+            end: Position::from([el, ec, eo]),
+        }
+    }
+}
+
+/// Position in LSP terms: 0-based line and UTF-16 code unit character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// Range in LSP terms, end exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+impl Position {
+    /// Convert to an LSP position. The character is counted in UTF-16 code
+    /// units from the start of the line containing `offset` in `source`.
+    pub fn to_lsp_position(&self, source: &str) -> LspPosition {
+        let mut offset = self.offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let character = source[line_start..offset].encode_utf16().count();
+        LspPosition {
+            line: self.line.saturating_sub(1) as u32,
+            character: character as u32,
+        }
+    }
+}
+
+impl Span {
+    /// Convert to an LSP range using `source` for UTF-16 column computation
+    pub fn to_lsp_range(&self, source: &str) -> LspRange {
+        LspRange {
+            start: self.start.to_lsp_position(source),
+            end: self.end.to_lsp_position(source),
+        }
+    }
+}
+
 /// Identifier with position information
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Identifier {
     pub name: String,
     pub span: Span,
 }
 
+impl Identifier {
+    /// Name used for comparison and lookup: unquoted parts are uppercased,
+    /// quoted parts keep their exact text without the quotes
+    pub fn normalized(&self) -> String {
+        normalize_identifier(&self.name)
+    }
+    
+    /// Name given to IFS `super(...)` calls, which invoke the implementation
+    /// this subprogram overrides rather than a symbol called `super`
+    pub const SUPER: &'static str = "super";
+    
+    /// Whether this is the synthetic name of a `super(...)` call
+    pub fn is_super(&self) -> bool {
+        self.name == Self::SUPER
+    }
+}
+
+/// Normalize a possibly qualified identifier such as `Customer_API.Get_Name`
+pub fn normalize_identifier(name: &str) -> String {
+    name.split('.')
+        .map(|part| {
+            let part = part.trim();
+            match part.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+                Some(quoted) => quoted.to_string(),
+                None => part.to_uppercase(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
 /// Root AST node that can contain any supported language
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum AstNode {
     PlSql(PlSqlNode),
     Entity(EntityNode),
@@ -41,13 +154,23 @@ pub enum AstNode {
     Storage(StorageNode),
     MarbleProjection(MarbleProjectionNode),
     MarbleClient(MarbleClientNode),
+    /// Several top-level units parsed from one file, in source order
+    SourceFile {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+        children: Vec<AstNode>,
+        span: Span,
+    },
 }
 
 // PL/SQL AST nodes with IFS-specific features
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum PlSqlNode {
     Package {
         name: Identifier,
+        /// Parsed from a standalone `PACKAGE BODY`
+        is_body: bool,
         component: Option<String>,
         annotations: Vec<IfsAnnotation>,
         declarations: Vec<PlSqlDeclaration>,
@@ -60,6 +183,16 @@ pub enum PlSqlNode {
         annotations: Vec<IfsAnnotation>,
         parameters: Vec<Parameter>,
         body: Vec<PlSqlStatement>,
+        exception_handlers: Vec<ExceptionHandler>,
+        /// Name repeated after the closing `END`, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        end_label: Option<Identifier>,
+        /// `PRAGMA` directives in the declaration section
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pragmas: Vec<Pragma>,
+        /// Local variables, cursors, exceptions and nested subprograms
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        declarations: Vec<PlSqlDeclaration>,
         span: Span,
     },
     Function {
@@ -68,21 +201,64 @@ pub enum PlSqlNode {
         annotations: Vec<IfsAnnotation>,
         parameters: Vec<Parameter>,
         return_type: Type,
+        /// Declared with the `PIPELINED` clause
+        is_pipelined: bool,
+        /// Declared with the `RESULT_CACHE` clause
+        result_cached: bool,
         body: Vec<PlSqlStatement>,
+        exception_handlers: Vec<ExceptionHandler>,
+        /// Name repeated after the closing `END`, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        end_label: Option<Identifier>,
+        /// `PRAGMA` directives in the declaration section
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pragmas: Vec<Pragma>,
+        /// Local variables, cursors, exceptions and nested subprograms
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        declarations: Vec<PlSqlDeclaration>,
         span: Span,
     },
 }
 
+/// `PRAGMA name[(arguments)];` compiler directive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Pragma {
+    pub name: Identifier,
+    pub arguments: Vec<Expression>,
+    pub span: Span,
+}
+
+impl Pragma {
+    /// Whether this is `PRAGMA AUTONOMOUS_TRANSACTION`
+    pub fn is_autonomous_transaction(&self) -> bool {
+        self.name.normalized() == "AUTONOMOUS_TRANSACTION"
+    }
+}
+
 /// IFS-specific annotations for procedures and functions
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum IfsAnnotation {
     Override,
     Overtake,
     UncheckedAccess,
 }
 
+impl fmt::Display for IfsAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            IfsAnnotation::Override => "@Override",
+            IfsAnnotation::Overtake => "@Overtake",
+            IfsAnnotation::UncheckedAccess => "@UncheckedAccess",
+        };
+        write!(f, "{}", text)
+    }
+}
+
 /// Procedure/Function visibility based on naming convention
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum ProcedureVisibility {
     Public,     // No trailing underscores
     Protected,  // Ends with __
@@ -91,6 +267,7 @@ pub enum ProcedureVisibility {
 
 /// Overtake directives for procedure modifications
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum OvertakeDirective {
     Search {
         code: String,
@@ -127,6 +304,7 @@ pub enum OvertakeDirective {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum PlSqlDeclaration {
     Variable {
         name: Identifier,
@@ -137,44 +315,430 @@ pub enum PlSqlDeclaration {
     Cursor {
         name: Identifier,
         query: String,
+        /// The query ends with a `FOR UPDATE` locking clause
+        #[serde(default)]
+        for_update: bool,
         span: Span,
     },
     Exception {
         name: Identifier,
         span: Span,
     },
+    /// Forward declaration of a procedure or function without a body
+    SubprogramSpec {
+        name: Identifier,
+        parameters: Vec<Parameter>,
+        return_type: Option<Type>,
+        span: Span,
+    },
+    /// Nested procedure or function implementation
+    Subprogram(Box<PlSqlNode>),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum PlSqlStatement {
     Assignment {
         target: Identifier,
         value: Expression,
         span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
     },
     If {
         condition: Expression,
         then_branch: Vec<PlSqlStatement>,
         else_branch: Option<Vec<PlSqlStatement>>,
         span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
     },
     Loop {
         body: Vec<PlSqlStatement>,
         span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
     },
     Return {
         value: Option<Expression>,
         span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
     },
     Call {
         name: Identifier,
         arguments: Vec<Expression>,
         span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
+    },
+    SelectInto {
+        into: Vec<Identifier>,
+        query: String,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
+    },
+    /// Nested `[DECLARE ...] BEGIN ... EXCEPTION ... END` block
+    Block {
+        #[serde(default)]
+        declarations: Vec<PlSqlDeclaration>,
+        body: Vec<PlSqlStatement>,
+        exception_handlers: Vec<ExceptionHandler>,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
+    },
+    /// `RAISE [exception]`; without a name the current exception is re-raised
+    Raise {
+        exception: Option<Identifier>,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
+    },
+    /// `<<name>>` label marking a `GOTO` target
+    Label {
+        name: Identifier,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
+    },
+    /// `GOTO label`
+    Goto {
+        label: Identifier,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
+    },
+    /// `FETCH cursor [BULK COLLECT] INTO target[, target] [LIMIT rows]`
+    Fetch {
+        cursor: Identifier,
+        into: Vec<Identifier>,
+        bulk_collect: bool,
+        limit: Option<Expression>,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
     },
+    /// `INSERT`, `UPDATE`, `DELETE` or `MERGE` statement
+    Dml {
+        kind: DmlKind,
+        target_table: Identifier,
+        raw: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        returning: Option<ReturningClause>,
+        span: Span,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trivia: Option<Trivia>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum DmlKind {
+    Insert,
+    Update,
+    Delete,
+    Merge,
+}
+
+/// `RETURNING column[, ...] [BULK COLLECT] INTO target[, ...]` at the end of a DML statement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ReturningClause {
+    /// Values read from the affected rows, usually column names
+    pub columns: Vec<Expression>,
+    /// Variables the values are assigned to
+    pub into: Vec<Identifier>,
+    pub bulk_collect: bool,
+    pub span: Span,
+}
+
+/// A comment or blank line kept around a node for lossless rewriting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum TriviaPiece {
+    /// Comment text including its `--` or `/* */` delimiters
+    Comment(String),
+    /// An empty line separating the node from its neighbours
+    BlankLine,
+}
+
+/// Trivia attached to a node when parsing with `ParseOptions::preserve_trivia`.
+/// Leading trivia sits on the lines above the node; trailing trivia follows it
+/// on its last line.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Trivia {
+    pub leading: Vec<TriviaPiece>,
+    pub trailing: Vec<TriviaPiece>,
+}
+
+impl Trivia {
+    /// Whether neither leading nor trailing trivia was kept
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty()
+    }
+    
+    /// Re-emit `code` surrounded by this trivia, indenting leading comments by `indent`
+    pub fn wrap(&self, code: &str, indent: &str) -> String {
+        let mut text = String::new();
+        for piece in &self.leading {
+            match piece {
+                TriviaPiece::Comment(comment) => {
+                    text.push_str(indent);
+                    text.push_str(comment);
+                }
+                TriviaPiece::BlankLine => {}
+            }
+            text.push('\n');
+        }
+        text.push_str(indent);
+        text.push_str(code);
+        for piece in &self.trailing {
+            if let TriviaPiece::Comment(comment) = piece {
+                text.push(' ');
+                text.push_str(comment);
+            }
+        }
+        text
+    }
+}
+
+/// A `WHEN <exception> [OR <exception>] THEN` handler
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ExceptionHandler {
+    pub exceptions: Vec<Identifier>,
+    pub body: Vec<PlSqlStatement>,
+    pub span: Span,
+}
+
+impl ExceptionHandler {
+    /// Check whether this handler catches the named exception, directly or via OTHERS
+    pub fn handles(&self, exception: &str) -> bool {
+        self.exceptions.iter().any(|e| {
+            e.name.eq_ignore_ascii_case(exception) || e.name.eq_ignore_ascii_case("OTHERS")
+        })
+    }
+}
+
+impl PlSqlStatement {
+    /// Source span of the statement
+    pub fn span(&self) -> &Span {
+        match self {
+            PlSqlStatement::Assignment { span, .. }
+            | PlSqlStatement::If { span, .. }
+            | PlSqlStatement::Loop { span, .. }
+            | PlSqlStatement::Return { span, .. }
+            | PlSqlStatement::Call { span, .. }
+            | PlSqlStatement::SelectInto { span, .. }
+            | PlSqlStatement::Block { span, .. }
+            | PlSqlStatement::Raise { span, .. }
+            | PlSqlStatement::Label { span, .. }
+            | PlSqlStatement::Goto { span, .. }
+            | PlSqlStatement::Fetch { span, .. }
+            | PlSqlStatement::Dml { span, .. } => span,
+        }
+    }
+    
+    /// Comments and blank lines kept around the statement, if any
+    pub fn trivia(&self) -> Option<&Trivia> {
+        match self {
+            PlSqlStatement::Assignment { trivia, .. }
+            | PlSqlStatement::If { trivia, .. }
+            | PlSqlStatement::Loop { trivia, .. }
+            | PlSqlStatement::Return { trivia, .. }
+            | PlSqlStatement::Call { trivia, .. }
+            | PlSqlStatement::SelectInto { trivia, .. }
+            | PlSqlStatement::Block { trivia, .. }
+            | PlSqlStatement::Raise { trivia, .. }
+            | PlSqlStatement::Label { trivia, .. }
+            | PlSqlStatement::Goto { trivia, .. }
+            | PlSqlStatement::Fetch { trivia, .. }
+            | PlSqlStatement::Dml { trivia, .. } => trivia.as_ref(),
+        }
+    }
+    
+    /// Mutable access to the statement's trivia slot
+    pub fn trivia_mut(&mut self) -> &mut Option<Trivia> {
+        match self {
+            PlSqlStatement::Assignment { trivia, .. }
+            | PlSqlStatement::If { trivia, .. }
+            | PlSqlStatement::Loop { trivia, .. }
+            | PlSqlStatement::Return { trivia, .. }
+            | PlSqlStatement::Call { trivia, .. }
+            | PlSqlStatement::SelectInto { trivia, .. }
+            | PlSqlStatement::Block { trivia, .. }
+            | PlSqlStatement::Raise { trivia, .. }
+            | PlSqlStatement::Label { trivia, .. }
+            | PlSqlStatement::Goto { trivia, .. }
+            | PlSqlStatement::Fetch { trivia, .. }
+            | PlSqlStatement::Dml { trivia, .. } => trivia,
+        }
+    }
+    
+    /// Whether control never continues past this statement: `RETURN`, `RAISE`
+    /// or a call to `RAISE_APPLICATION_ERROR`
+    pub fn is_terminating(&self) -> bool {
+        match self {
+            PlSqlStatement::Return { .. } | PlSqlStatement::Raise { .. } => true,
+            PlSqlStatement::Call { name, .. } => name.name.eq_ignore_ascii_case("raise_application_error"),
+            _ => false,
+        }
+    }
+    
+    /// Expressions appearing directly in this statement, not in nested statements
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            PlSqlStatement::Assignment { value, .. } => vec![value],
+            PlSqlStatement::If { condition, .. } => vec![condition],
+            PlSqlStatement::Return { value, .. } => value.iter().collect(),
+            PlSqlStatement::Call { arguments, .. } => arguments.iter().collect(),
+            PlSqlStatement::Fetch { limit, .. } => limit.iter().collect(),
+            PlSqlStatement::Dml { returning, .. } => returning.iter().flat_map(|clause| &clause.columns).collect(),
+            _ => Vec::new(),
+        }
+    }
+    
+    /// Statements nested directly inside this one, including exception handler bodies
+    pub fn child_statements(&self) -> Vec<&PlSqlStatement> {
+        match self {
+            PlSqlStatement::If { then_branch, else_branch, .. } => then_branch
+                .iter()
+                .chain(else_branch.iter().flatten())
+                .collect(),
+            PlSqlStatement::Loop { body, .. } => body.iter().collect(),
+            PlSqlStatement::Block { body, exception_handlers, .. } => body
+                .iter()
+                .chain(exception_handlers.iter().flat_map(|handler| &handler.body))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl PlSqlNode {
+    /// Declarations in the package specification or body, or in a subprogram's declaration section
+    pub fn declarations(&self) -> &[PlSqlDeclaration] {
+        match self {
+            PlSqlNode::Package { declarations, .. }
+            | PlSqlNode::Procedure { declarations, .. }
+            | PlSqlNode::Function { declarations, .. } => declarations,
+        }
+    }
+    
+    /// Top-level statements of the body, including exception handler bodies
+    pub fn child_statements(&self) -> Vec<&PlSqlStatement> {
+        match self {
+            PlSqlNode::Package { body, .. } => body.iter().flatten().collect(),
+            PlSqlNode::Procedure { body, exception_handlers, .. }
+            | PlSqlNode::Function { body, exception_handlers, .. } => body
+                .iter()
+                .chain(exception_handlers.iter().flat_map(|handler| &handler.body))
+                .collect(),
+        }
+    }
+    
+    /// Replace the IFS annotations on this node
+    pub fn with_annotations(mut self, new_annotations: Vec<IfsAnnotation>) -> Self {
+        match &mut self {
+            PlSqlNode::Package { annotations, .. }
+            | PlSqlNode::Procedure { annotations, .. }
+            | PlSqlNode::Function { annotations, .. } => *annotations = new_annotations,
+        }
+        self
+    }
+}
+
+/// Borrowed reference to any traversable node, for generic tree walks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeRef<'a> {
+    PlSql(&'a PlSqlNode),
+    Declaration(&'a PlSqlDeclaration),
+    Statement(&'a PlSqlStatement),
+}
+
+impl<'a> NodeRef<'a> {
+    /// Direct children of this node, in source order
+    pub fn children(&self) -> Vec<NodeRef<'a>> {
+        match *self {
+            NodeRef::PlSql(node) => node
+                .declarations()
+                .iter()
+                .map(NodeRef::Declaration)
+                .chain(node.child_statements().into_iter().map(NodeRef::Statement))
+                .collect(),
+            NodeRef::Declaration(PlSqlDeclaration::Subprogram(node)) => vec![NodeRef::PlSql(node)],
+            NodeRef::Declaration(_) => Vec::new(),
+            NodeRef::Statement(statement) => statement
+                .child_statements()
+                .into_iter()
+                .map(NodeRef::Statement)
+                .collect(),
+        }
+    }
+}
+
+impl AstNode {
+    /// Direct children of the root node. Only PL/SQL trees have nested nodes.
+    pub fn children(&self) -> Vec<NodeRef<'_>> {
+        match self {
+            AstNode::PlSql(node) => vec![NodeRef::PlSql(node)],
+            AstNode::SourceFile { children, .. } => children.iter().flat_map(AstNode::children).collect(),
+            _ => Vec::new(),
+        }
+    }
+    
+    /// The top-level units of the tree: the children of a `SourceFile`, or the node itself
+    pub fn units(&self) -> Vec<&AstNode> {
+        match self {
+            AstNode::SourceFile { children, .. } => children.iter().flat_map(AstNode::units).collect(),
+            _ => vec![self],
+        }
+    }
+    
+    /// Depth-first, pre-order iterator over every node below the root
+    pub fn descendants(&self) -> Descendants<'_> {
+        let mut stack = self.children();
+        stack.reverse();
+        Descendants { stack }
+    }
+    
+    /// Rough estimate of the memory held by the tree: the root plus the strings,
+    /// vectors, maps and boxes it owns. Useful for deciding when a file is large
+    /// enough to parse with `parse_source_outline` instead.
+    pub fn approx_size_bytes(&self) -> usize {
+        use super::heap_size::HeapSize;
+        std::mem::size_of_val(self) + self.heap_bytes()
+    }
+    
+    /// JSON Schema of the serialized tree, for validating `--output json` results
+    /// and generating types in other languages
+    #[cfg(feature = "json-schema")]
+    pub fn json_schema() -> schemars::schema::RootSchema {
+        schemars::schema_for!(AstNode)
+    }
+}
+
+/// Pre-order depth-first iterator returned by [`AstNode::descendants`]
+pub struct Descendants<'a> {
+    stack: Vec<NodeRef<'a>>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = NodeRef<'a>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().into_iter().rev());
+        Some(node)
+    }
 }
 
 // Entity AST nodes (IFS text representation)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EntityNode {
     pub entity_name: Identifier,
     pub component: String,
@@ -187,12 +751,14 @@ pub struct EntityNode {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct CodeGenProperties {
     pub properties: HashMap<String, String>,
     pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EntityAttribute {
     pub visibility: AttributeVisibility,
     pub name: Identifier,
@@ -202,7 +768,46 @@ pub struct EntityAttribute {
     pub span: Span,
 }
 
+impl EntityAttribute {
+    /// Interpret the positional flags: key kind, then `M`andatory, `I`nsertable,
+    /// `U`pdatable and `L`ist of values, with `-` marking an unset position
+    pub fn decode_flags(&self) -> AttributeFlags {
+        let flag = |position: usize, expected: char| {
+            self.flags
+                .chars()
+                .nth(position)
+                .is_some_and(|c| c.eq_ignore_ascii_case(&expected))
+        };
+        AttributeFlags {
+            key: flag(0, 'K'),
+            parent_key: flag(0, 'P'),
+            mandatory: flag(1, 'M'),
+            insertable: flag(2, 'I'),
+            updatable: flag(3, 'U'),
+            list_of_values: flag(4, 'L'),
+        }
+    }
+}
+
+/// Meaning of an entity attribute's flags string, see [`EntityAttribute::decode_flags`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttributeFlags {
+    /// Part of the primary key (`K`)
+    pub key: bool,
+    /// Part of the key inherited from a parent entity (`P`)
+    pub parent_key: bool,
+    /// Must have a value, i.e. the column is NOT NULL
+    pub mandatory: bool,
+    /// Can be set when a row is inserted
+    pub insertable: bool,
+    /// Can be changed after the row is inserted
+    pub updatable: bool,
+    /// Shown in list of values queries
+    pub list_of_values: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum AttributeVisibility {
     Public,
     Private,
@@ -210,6 +815,7 @@ pub enum AttributeVisibility {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EntityKey {
     pub name: Identifier,
     pub columns: Vec<Identifier>,
@@ -218,6 +824,7 @@ pub struct EntityKey {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EntityReference {
     pub name: Identifier,
     pub referenced_entity: Identifier,
@@ -226,6 +833,7 @@ pub struct EntityReference {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct StateMachine {
     pub states: Vec<State>,
     pub transitions: Vec<StateTransition>,
@@ -233,6 +841,7 @@ pub struct StateMachine {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct State {
     pub name: Identifier,
     pub state_type: StateType,
@@ -240,6 +849,7 @@ pub struct State {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum StateType {
     Initial,
     Normal,
@@ -247,6 +857,7 @@ pub enum StateType {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct StateTransition {
     pub from_state: Identifier,
     pub to_state: Identifier,
@@ -256,6 +867,7 @@ pub struct StateTransition {
 
 // Enumeration AST nodes (IFS text representation)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EnumerationNode {
     pub enumeration_name: Identifier,
     pub component: String,
@@ -264,15 +876,29 @@ pub struct EnumerationNode {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EnumerationValue {
     pub name: Identifier,
     pub client_value: Option<String>,
+    /// Translatable text shown to users, from the `LabelText` property
+    pub label_text: Option<String>,
     pub properties: HashMap<String, String>,
     pub span: Span,
 }
 
+impl EnumerationValue {
+    /// Value stored in the database, from the `DbValue` property
+    pub fn db_value(&self) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("dbvalue"))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
 // Views AST nodes (IFS custom SQL format)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ViewsNode {
     pub layer: Option<String>,
     pub column_definitions: Vec<ColumnDefinition>,
@@ -281,8 +907,10 @@ pub struct ViewsNode {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ColumnDefinition {
     pub name: Identifier,
+    pub visibility: ViewVisibility,
     pub flags: Option<String>,
     pub datatype: Option<String>,
     pub prompt: Option<String>,
@@ -292,16 +920,29 @@ pub struct ColumnDefinition {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ViewDefinition {
     pub name: Identifier,
+    pub visibility: ViewVisibility,
     pub annotations: Vec<IfsAnnotation>,
     pub columns: Vec<ColumnDefinition>,
     pub query: SqlQuery,
     pub span: Span,
 }
 
+/// `PUBLIC`/`PRIVATE` marker on a view or column. Unmarked views are public
+/// and unmarked view columns take the visibility of their view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum ViewVisibility {
+    #[default]
+    Public,
+    Private,
+}
+
 // Storage AST nodes (INDEX, SEQUENCE, TABLE definitions)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct StorageNode {
     pub layer: Option<String>,
     pub definitions: Vec<StorageDefinition>,
@@ -309,6 +950,7 @@ pub struct StorageNode {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum StorageDefinition {
     Index {
         name: Identifier,
@@ -331,6 +973,7 @@ pub enum StorageDefinition {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct TableColumn {
     pub name: Identifier,
     pub data_type: String,
@@ -340,6 +983,7 @@ pub struct TableColumn {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum TableConstraint {
     PrimaryKey {
         name: Identifier,
@@ -354,6 +998,7 @@ pub enum TableConstraint {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SqlQuery {
     pub select: Vec<SelectItem>,
     pub from: Vec<FromItem>,
@@ -365,13 +1010,22 @@ pub struct SqlQuery {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SelectItem {
     pub expression: Expression,
     pub alias: Option<Identifier>,
     pub span: Span,
 }
 
+impl SelectItem {
+    /// Whether the item is `*` or `alias.*`
+    pub fn is_star(&self) -> bool {
+        matches!(&self.expression, Expression::Literal { value, .. } if value == "*" || value.ends_with(".*"))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct FromItem {
     pub table: Identifier,
     pub alias: Option<Identifier>,
@@ -379,6 +1033,7 @@ pub struct FromItem {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OrderByItem {
     pub expression: Expression,
     pub direction: OrderDirection,
@@ -386,6 +1041,7 @@ pub struct OrderByItem {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum OrderDirection {
     Asc,
     Desc,
@@ -393,6 +1049,7 @@ pub enum OrderDirection {
 
 // Marble DSL AST nodes
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MarbleProjectionNode {
     pub name: Identifier,
     pub entity: Identifier,
@@ -402,6 +1059,7 @@ pub struct MarbleProjectionNode {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProjectionAttribute {
     pub name: Identifier,
     pub source: Option<Identifier>,
@@ -410,6 +1068,7 @@ pub struct ProjectionAttribute {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProjectionAction {
     pub name: Identifier,
     pub parameters: Vec<Parameter>,
@@ -417,6 +1076,7 @@ pub struct ProjectionAction {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MarbleClientNode {
     pub name: Identifier,
     pub layout: Vec<LayoutElement>,
@@ -425,6 +1085,7 @@ pub struct MarbleClientNode {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum LayoutElement {
     Group {
         name: Identifier,
@@ -445,6 +1106,7 @@ pub enum LayoutElement {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ClientCommand {
     pub name: Identifier,
     pub action: String,
@@ -454,6 +1116,7 @@ pub struct ClientCommand {
 
 // Common types
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Parameter {
     pub name: Identifier,
     pub param_type: Type,
@@ -463,6 +1126,7 @@ pub struct Parameter {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum ParameterMode {
     In,
     Out,
@@ -470,13 +1134,25 @@ pub enum ParameterMode {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Type {
     pub name: String,
     pub parameters: Vec<String>,
     pub span: Span,
 }
 
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.parameters.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}({})", self.name, self.parameters.join(","))
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum Expression {
     Identifier(Identifier),
     Literal {
@@ -499,9 +1175,95 @@ pub enum Expression {
         arguments: Vec<Expression>,
         span: Span,
     },
+    /// Simple `CASE x WHEN v THEN r ... END` when `operand` is set,
+    /// searched `CASE WHEN condition THEN r ... END` otherwise
+    Case {
+        operand: Option<Box<Expression>>,
+        branches: Vec<(Expression, Expression)>,
+        default: Option<Box<Expression>>,
+        span: Span,
+    },
+}
+
+impl Expression {
+    /// Operands and other expressions nested directly inside this one
+    pub fn sub_expressions(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Identifier(_) | Expression::Literal { .. } => Vec::new(),
+            Expression::Binary { left, right, .. } => vec![left.as_ref(), right.as_ref()],
+            Expression::Unary { operand, .. } => vec![operand.as_ref()],
+            Expression::FunctionCall { arguments, .. } => arguments.iter().collect(),
+            Expression::Case { operand, branches, default, .. } => operand
+                .as_deref()
+                .into_iter()
+                .chain(branches.iter().flat_map(|(condition, result)| [condition, result]))
+                .chain(default.as_deref())
+                .collect(),
+        }
+    }
+    
+    /// Whether this is the `NULL` literal
+    pub fn is_null_literal(&self) -> bool {
+        matches!(self, Expression::Literal { value, .. } if value.eq_ignore_ascii_case("null"))
+    }
+}
+
+impl fmt::Display for Expression {
+    /// Render the expression as PL/SQL source, adding parentheses only where an
+    /// operand binds looser than its operator. Operators associate to the left, so a
+    /// right operand of equal precedence is parenthesized too, as in `a - (b - c)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Identifier(identifier) => write!(f, "{}", identifier.name),
+            Expression::Literal { value, .. } => write!(f, "{}", value),
+            Expression::Binary { left, operator, right, .. } => {
+                let operand = |expression: &Expression, is_right: bool, f: &mut fmt::Formatter<'_>| match expression {
+                    Expression::Binary { operator: inner, .. }
+                        if inner.precedence() < operator.precedence()
+                            || (is_right && inner.precedence() == operator.precedence()) =>
+                    {
+                        write!(f, "({})", expression)
+                    }
+                    _ => write!(f, "{}", expression),
+                };
+                operand(left, false, f)?;
+                write!(f, " {} ", operator)?;
+                operand(right, true, f)
+            }
+            Expression::Unary { operator, operand, .. } => {
+                let operand = match operand.as_ref() {
+                    Expression::Binary { .. } => format!("({})", operand),
+                    _ => operand.to_string(),
+                };
+                match operator {
+                    UnaryOperator::IsNull | UnaryOperator::IsNotNull => write!(f, "{} {}", operand, operator),
+                    UnaryOperator::Not => write!(f, "NOT {}", operand),
+                    UnaryOperator::Minus | UnaryOperator::Plus => write!(f, "{}{}", operator, operand),
+                }
+            }
+            Expression::FunctionCall { name, arguments, .. } => {
+                let arguments: Vec<String> = arguments.iter().map(|argument| argument.to_string()).collect();
+                write!(f, "{}({})", name.name, arguments.join(", "))
+            }
+            Expression::Case { operand, branches, default, .. } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (condition, result) in branches {
+                    write!(f, " WHEN {} THEN {}", condition, result)?;
+                }
+                if let Some(default) = default {
+                    write!(f, " ELSE {}", default)?;
+                }
+                write!(f, " END")
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum BinaryOperator {
     Add,
     Subtract,
@@ -519,9 +1281,308 @@ pub enum BinaryOperator {
     In,
 }
 
+impl BinaryOperator {
+    /// Binding strength of comparisons such as `=` and `LIKE`, shared by `IS [NOT] NULL`
+    pub const COMPARISON_PRECEDENCE: u8 = 4;
+    
+    /// Binding strength used by the precedence-climbing expression parser.
+    /// Higher values bind tighter, following Oracle's operator precedence.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::LessThan
+            | BinaryOperator::LessThanOrEqual
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::GreaterThanOrEqual
+            | BinaryOperator::Like
+            | BinaryOperator::In => Self::COMPARISON_PRECEDENCE,
+            BinaryOperator::Add | BinaryOperator::Subtract => 5,
+            BinaryOperator::Multiply | BinaryOperator::Divide => 6,
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Equal => "=",
+            BinaryOperator::NotEqual => "<>",
+            BinaryOperator::LessThan => "<",
+            BinaryOperator::LessThanOrEqual => "<=",
+            BinaryOperator::GreaterThan => ">",
+            BinaryOperator::GreaterThanOrEqual => ">=",
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+            BinaryOperator::Like => "LIKE",
+            BinaryOperator::In => "IN",
+        };
+        write!(f, "{}", text)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum UnaryOperator {
     Not,
     Minus,
     Plus,
+    /// Postfix `IS NULL`
+    IsNull,
+    /// Postfix `IS NOT NULL`
+    IsNotNull,
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            UnaryOperator::Not => "NOT",
+            UnaryOperator::Minus => "-",
+            UnaryOperator::Plus => "+",
+            UnaryOperator::IsNull => "IS NULL",
+            UnaryOperator::IsNotNull => "IS NOT NULL",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operator_display() {
+        assert_eq!(BinaryOperator::Add.to_string(), "+");
+        assert_eq!(BinaryOperator::NotEqual.to_string(), "<>");
+        assert_eq!(BinaryOperator::And.to_string(), "AND");
+        assert_eq!(BinaryOperator::Like.to_string(), "LIKE");
+        assert_eq!(UnaryOperator::Not.to_string(), "NOT");
+        assert_eq!(UnaryOperator::Minus.to_string(), "-");
+    }
+
+    #[test]
+    fn test_expression_display() {
+        let span = Span::from([0; 6]);
+        let literal = |value: &str| Expression::Literal { value: value.to_string(), span: span.clone() };
+        let sum = Expression::Binary {
+            left: Box::new(literal("1")),
+            operator: BinaryOperator::Add,
+            right: Box::new(literal("2")),
+            span: span.clone(),
+        };
+        let product = Expression::Binary {
+            left: Box::new(sum),
+            operator: BinaryOperator::Multiply,
+            right: Box::new(literal("3")),
+            span: span.clone(),
+        };
+        assert_eq!(product.to_string(), "(1 + 2) * 3");
+        assert_eq!(literal("'x'").to_string(), "'x'");
+        
+        let difference = |left: Expression, right: Expression| Expression::Binary {
+            left: Box::new(left),
+            operator: BinaryOperator::Subtract,
+            right: Box::new(right),
+            span: span.clone(),
+        };
+        let nested_right = difference(literal("1"), difference(literal("2"), literal("3")));
+        assert_eq!(nested_right.to_string(), "1 - (2 - 3)");
+        let nested_left = difference(difference(literal("1"), literal("2")), literal("3"));
+        assert_eq!(nested_left.to_string(), "1 - 2 - 3");
+    }
+    
+    #[test]
+    fn test_expression_display_round_trips_through_the_parser() {
+        for expression in ["a - (b - c)", "a - b - c", "a / (b * c)", "(a + b) * c", "a OR b AND c"] {
+            let source = format!("PROCEDURE Run IS\nBEGIN\n   x_ := {};\nEND;", expression);
+            let ast = crate::parser::parse_source(&source, crate::parser::Language::PlSql).unwrap();
+            let value = ast
+                .descendants()
+                .find_map(|node| match node {
+                    NodeRef::Statement(PlSqlStatement::Assignment { value, .. }) => Some(value.to_string()),
+                    _ => None,
+                })
+                .unwrap();
+            assert_eq!(value, expression);
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        assert!(BinaryOperator::Or.precedence() < BinaryOperator::And.precedence());
+        assert!(BinaryOperator::And.precedence() < BinaryOperator::Multiply.precedence());
+        assert!(BinaryOperator::Equal.precedence() < BinaryOperator::Add.precedence());
+        assert!(BinaryOperator::Add.precedence() < BinaryOperator::Divide.precedence());
+    }
+    
+    #[test]
+    fn test_identifier_normalization() {
+        assert_eq!(normalize_identifier("Customer_API.Get_Name"), "CUSTOMER_API.GET_NAME");
+        assert_eq!(normalize_identifier("customer_api"), normalize_identifier("CUSTOMER_API"));
+        assert_eq!(normalize_identifier("\"MixedCase\""), "MixedCase");
+        assert_eq!(normalize_identifier("app.\"Quoted\""), "APP.Quoted");
+    }
+    
+    #[test]
+    fn test_span_to_lsp_range() {
+        let source = "BEGIN\n  x := 1;\nEND;";
+        let span = Span {
+            start: Position { line: 2, column: 3, offset: 8 },
+            end: Position { line: 2, column: 4, offset: 9 },
+        };
+        let range = span.to_lsp_range(source);
+        assert_eq!(range.start, LspPosition { line: 1, character: 2 });
+        assert_eq!(range.end, LspPosition { line: 1, character: 3 });
+    }
+    
+    #[test]
+    fn test_span_to_lsp_range_counts_utf16_units() {
+        // 'ø' is two bytes in UTF-8 but one UTF-16 unit, '𝄞' is four bytes and two units
+        let source = "-- first\nv := 'ø𝄞' || name;";
+        let name_offset = source.find("name").unwrap();
+        let span = Span {
+            start: Position { line: 2, column: 16, offset: name_offset },
+            end: Position { line: 2, column: 20, offset: name_offset + 4 },
+        };
+        let range = span.to_lsp_range(source);
+        assert_eq!(range.start, LspPosition { line: 1, character: 14 });
+        assert_eq!(range.end, LspPosition { line: 1, character: 18 });
+    }
+    
+    #[test]
+    fn test_descendants_visit_nested_statements() {
+        let source = "PROCEDURE Check_State IS\nBEGIN\n   IF a_ THEN\n      IF b_ THEN\n         Do_It;\n      ELSE\n         x_ := 1;\n      END IF;\n   END IF;\n   RETURN;\nEND;";
+        let ast = crate::parser::parse_source(source, crate::parser::Language::PlSql).unwrap();
+        
+        let statements = ast
+            .descendants()
+            .filter(|node| matches!(node, NodeRef::Statement(_)))
+            .count();
+        assert_eq!(statements, 5);
+        
+        let calls = ast
+            .descendants()
+            .filter(|node| matches!(node, NodeRef::Statement(PlSqlStatement::Call { .. })))
+            .count();
+        assert_eq!(calls, 1);
+    }
+    
+    #[test]
+    fn test_compact_span_round_trip() {
+        let span = Span {
+            start: Position { line: 3, column: 5, offset: 40 },
+            end: Position { line: 3, column: 12, offset: 47 },
+        };
+        
+        let json = serde_json::to_string(&<[usize; 6]>::from(span.clone())).unwrap();
+        assert_eq!(json, "[3,5,40,3,12,47]");
+        assert_eq!(Span::from(serde_json::from_str::<[usize; 6]>(&json).unwrap()), span);
+        
+        #[cfg(feature = "compact-spans")]
+        {
+            assert_eq!(serde_json::to_string(&span).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Span>(&json).unwrap(), span);
+        }
+    }
+    
+    #[test]
+    fn test_approx_size_grows_with_body() {
+        use crate::parser::{parse_source, Language};
+        
+        let small = parse_source("PROCEDURE Small IS\nBEGIN\n   NULL;\nEND;", Language::PlSql).unwrap();
+        let calls = "   Log_SYS.Trace('step', value_);\n".repeat(50);
+        let large = parse_source(&format!("PROCEDURE Large IS\nBEGIN\n{}END;", calls), Language::PlSql).unwrap();
+        
+        let small_size = small.approx_size_bytes();
+        assert!(small_size > std::mem::size_of::<AstNode>());
+        assert!(large.approx_size_bytes() > small_size + 50 * std::mem::size_of::<PlSqlStatement>());
+    }
+    
+    #[test]
+    fn test_approx_size_counts_capacity_and_boxed_expressions() {
+        let span = Span::from([0; 6]);
+        let literal = |value: &str| Expression::Literal { value: value.to_string(), span: span.clone() };
+        let binary = Expression::Binary {
+            left: Box::new(literal("1")),
+            operator: BinaryOperator::Add,
+            right: Box::new(literal("2")),
+            span: span.clone(),
+        };
+        let mut body = Vec::with_capacity(8);
+        body.push(PlSqlStatement::Return { value: Some(binary), span: span.clone(), trivia: None });
+        let mut name = String::with_capacity(64);
+        name.push_str("Add");
+        let node = AstNode::PlSql(PlSqlNode::Procedure {
+            name: Identifier { name, span: span.clone() },
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: Vec::new(),
+            body,
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+            span,
+        });
+        
+        let expected = std::mem::size_of::<AstNode>()
+            + 64
+            + 8 * std::mem::size_of::<PlSqlStatement>()
+            + 2 * (std::mem::size_of::<Expression>() + 1);
+        assert_eq!(node.approx_size_bytes(), expected);
+    }
+    
+    #[test]
+    fn test_decode_attribute_flags() {
+        let attribute = |flags: &str| EntityAttribute {
+            visibility: AttributeVisibility::Public,
+            name: Identifier { name: "Description".to_string(), span: Span::from([0; 6]) },
+            data_type: "TEXT(200)".to_string(),
+            flags: flags.to_string(),
+            properties: HashMap::new(),
+            span: Span::from([0; 6]),
+        };
+        
+        assert_eq!(
+            attribute("AMIUL").decode_flags(),
+            AttributeFlags {
+                key: false,
+                parent_key: false,
+                mandatory: true,
+                insertable: true,
+                updatable: true,
+                list_of_values: true,
+            }
+        );
+        assert_eq!(
+            attribute("A-IUL").decode_flags(),
+            AttributeFlags {
+                key: false,
+                parent_key: false,
+                mandatory: false,
+                insertable: true,
+                updatable: true,
+                list_of_values: true,
+            }
+        );
+        
+        let key = attribute("KMI-L").decode_flags();
+        assert!(key.key && key.mandatory && !key.updatable);
+        assert_eq!(attribute("").decode_flags(), AttributeFlags::default());
+    }
+    
+    #[cfg(feature = "json-schema")]
+    #[test]
+    fn test_ast_json_schema_defines_node_types() {
+        let schema = serde_json::to_value(AstNode::json_schema()).unwrap();
+        let definitions = schema["definitions"].as_object().unwrap();
+        assert!(definitions.contains_key("PlSqlNode"));
+        assert!(definitions.contains_key("Parameter"));
+    }
 }
@@ -0,0 +1,352 @@
+// Canonical layout for PL/SQL source
+//
+// The formatter re-indents each statement by its block nesting and strips
+// trailing whitespace. Tokens are never added, removed or reordered, so the
+// formatted source has the same lines as the input and the two can be
+// compared line by line.
+
+use crate::parser::lexer::{Lexer, Token, TokenType};
+use crate::parser::{parse_source, Language};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// Number of spaces per nesting level
+pub const INDENT_WIDTH: usize = 3;
+
+/// A line whose layout differs from the canonical formatting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatDifference {
+    /// 1-based line number
+    pub line: usize,
+    pub original: String,
+    pub formatted: String,
+}
+
+/// Re-emit PL/SQL source in canonical layout. Fails when the source does not parse.
+pub fn format_source(source: &str, language: Language) -> Result<String> {
+    if language != Language::PlSql {
+        return Err(anyhow::anyhow!("Formatting is only supported for PL/SQL, not {:?}", language));
+    }
+    parse_source(source, language)?;
+
+    let tokens = Lexer::new(source.to_string(), language).tokenize();
+    let layouts = Indenter::default().layout(source, &tokens);
+
+    let mut formatted = String::with_capacity(source.len());
+    for (line, layout) in source.split_inclusive('\n').zip(&layouts) {
+        let (text, terminator) = split_terminator(line);
+        let text = if layout.strip_trailing {
+            text.trim_end_matches([' ', '\t'])
+        } else {
+            text
+        };
+        if let Some(indent) = layout.indent.filter(|_| !text.trim().is_empty()) {
+            formatted.push_str(&" ".repeat(indent));
+            formatted.push_str(text.trim_start_matches([' ', '\t']));
+        } else {
+            formatted.push_str(text);
+        }
+        formatted.push_str(terminator);
+    }
+    Ok(formatted)
+}
+
+/// The lines of `source` that `format_source` would change
+pub fn check_format(source: &str, language: Language) -> Result<Vec<FormatDifference>> {
+    let formatted = format_source(source, language)?;
+    Ok(source
+        .lines()
+        .zip(formatted.lines())
+        .enumerate()
+        .filter(|(_, (original, formatted))| original != formatted)
+        .map(|(index, (original, formatted))| FormatDifference {
+            line: index + 1,
+            original: original.to_string(),
+            formatted: formatted.to_string(),
+        })
+        .collect())
+}
+
+fn split_terminator(line: &str) -> (&str, &str) {
+    if let Some(text) = line.strip_suffix("\r\n") {
+        (text, "\r\n")
+    } else if let Some(text) = line.strip_suffix('\n') {
+        (text, "\n")
+    } else {
+        (line, "")
+    }
+}
+
+/// Width of the leading whitespace of `line`, counting a tab as one indentation level
+fn leading_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .map(|c| if c == '\t' { INDENT_WIDTH } else { 1 })
+        .sum()
+}
+
+/// How a single line is laid out
+#[derive(Debug, Clone, Copy)]
+struct LineLayout {
+    /// Column the first token should start at; `None` keeps the line's own indentation
+    indent: Option<usize>,
+    /// Whether trailing whitespace is removed; not for lines ending inside a token
+    strip_trailing: bool,
+}
+
+/// Block constructs whose contents are indented one level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    /// Declarations after a subprogram or package header, or after `DECLARE`
+    Declarations,
+    /// Statements after `BEGIN`
+    Body,
+    /// Handlers after the `EXCEPTION` section keyword
+    Handlers,
+    /// Statements of one `WHEN ... THEN` exception handler
+    Handler,
+    If,
+    Loop,
+    Case,
+    /// Statements or value of one `WHEN ... THEN` or `ELSE` branch of a `CASE`
+    CaseBranch,
+}
+
+/// Tracks block nesting while walking the tokens of a source file
+#[derive(Debug, Default)]
+struct Indenter {
+    frames: Vec<Frame>,
+    /// A `PROCEDURE`, `FUNCTION` or `PACKAGE` header whose `IS` or `AS` opens a block
+    in_header: bool,
+    paren_depth: usize,
+    /// The next token begins a statement rather than continuing one
+    continues_statement: bool,
+    previous: Option<TokenType>,
+    /// Columns the first line of the current statement moved by; continuation lines move along
+    shift: isize,
+}
+
+impl Indenter {
+    /// Compute the layout of every line of `source`
+    fn layout(mut self, source: &str, tokens: &[Token]) -> Vec<LineLayout> {
+        let lines: Vec<&str> = source.split_inclusive('\n').collect();
+        let mut layouts = vec![LineLayout { indent: None, strip_trailing: true }; lines.len()];
+
+        let mut last_line = 0;
+        for token in tokens {
+            if matches!(token.token_type, TokenType::Whitespace | TokenType::Newline | TokenType::Eof) {
+                continue;
+            }
+
+            let line = token.position.line;
+            let end_line = token.end_position().line;
+            if line != last_line && line <= lines.len() {
+                let original = leading_width(lines[line - 1]) as isize;
+                let column = match self.line_level(token) {
+                    Some(level) => {
+                        let column = (level * INDENT_WIDTH) as isize;
+                        self.shift = column - original;
+                        column
+                    }
+                    None => (original + self.shift).max(0),
+                };
+                layouts[line - 1].indent = Some(column as usize);
+            } else {
+                self.advance(token);
+            }
+
+            // Lines inside a multi-line string or comment belong to the token
+            for inner in line..end_line.min(lines.len()) {
+                layouts[inner - 1].strip_trailing = false;
+                layouts[inner].indent = None;
+            }
+            last_line = end_line;
+        }
+
+        layouts
+    }
+
+    /// Advance past the first token of a line and return the nesting level the line
+    /// starts at, or `None` when it continues the statement on the line before
+    fn line_level(&mut self, token: &Token) -> Option<usize> {
+        let level = self.frames.len();
+        let starts_statement = !self.continues_statement;
+        match self.advance(token) {
+            Some(level) => Some(level),
+            None if starts_statement => Some(level),
+            None => None,
+        }
+    }
+
+    /// Update the block nesting for `token`. Keywords that sit at the level of the
+    /// block they open, continue or close return that level.
+    fn advance(&mut self, token: &Token) -> Option<usize> {
+        let token_type = &token.token_type;
+        if *token_type == TokenType::Comment || token.value.starts_with('@') {
+            return None;
+        }
+
+        let after_end = self.previous == Some(TokenType::End);
+        let starts_statement = !self.continues_statement;
+        let word = token.value.to_ascii_uppercase();
+        let mut opens_statement = false;
+        let mut level = None;
+
+        match token_type {
+            TokenType::End => level = Some(self.close()),
+            TokenType::If if !after_end => self.frames.push(Frame::If),
+            TokenType::Loop if !after_end => {
+                self.frames.push(Frame::Loop);
+                opens_statement = true;
+            }
+            TokenType::Identifier if word == "CASE" && !after_end => self.frames.push(Frame::Case),
+            TokenType::Then => opens_statement = true,
+            TokenType::ElseIf if self.top() == Some(Frame::If) => level = Some(self.frames.len() - 1),
+            TokenType::Else => {
+                match self.top() {
+                    Some(Frame::If) => level = Some(self.frames.len() - 1),
+                    Some(Frame::Case | Frame::CaseBranch) => level = Some(self.enter_branch(Frame::CaseBranch)),
+                    _ => {}
+                }
+                opens_statement = true;
+            }
+            TokenType::Identifier if word == "WHEN" => match self.top() {
+                Some(Frame::Case | Frame::CaseBranch) => level = Some(self.enter_branch(Frame::CaseBranch)),
+                Some(Frame::Handlers | Frame::Handler) if starts_statement => {
+                    level = Some(self.enter_branch(Frame::Handler))
+                }
+                _ => {}
+            },
+            TokenType::Declare => {
+                self.frames.push(Frame::Declarations);
+                opens_statement = true;
+            }
+            TokenType::Begin => {
+                if self.top() == Some(Frame::Declarations) {
+                    self.frames.pop();
+                }
+                level = Some(self.frames.len());
+                self.frames.push(Frame::Body);
+                opens_statement = true;
+            }
+            TokenType::Exception if starts_statement && self.top() == Some(Frame::Body) => {
+                self.frames.pop();
+                level = Some(self.frames.len());
+                self.frames.push(Frame::Handlers);
+                opens_statement = true;
+            }
+            TokenType::Procedure | TokenType::Function | TokenType::Package
+                if !after_end && self.paren_depth == 0 =>
+            {
+                self.in_header = true
+            }
+            TokenType::Is | TokenType::As if self.in_header && self.paren_depth == 0 => {
+                self.in_header = false;
+                self.frames.push(Frame::Declarations);
+                opens_statement = true;
+            }
+            TokenType::LeftParen => self.paren_depth += 1,
+            TokenType::RightParen => self.paren_depth = self.paren_depth.saturating_sub(1),
+            TokenType::Semicolon => {
+                self.in_header = false;
+                self.paren_depth = 0;
+                opens_statement = true;
+            }
+            TokenType::SlashTerminator => opens_statement = true,
+            // `<<label>>` stands on its own before the statement it labels
+            TokenType::GreaterThan if self.previous == Some(TokenType::GreaterThan) => opens_statement = true,
+            _ => {}
+        }
+
+        self.continues_statement = !opens_statement;
+        self.previous = Some(token_type.clone());
+        level
+    }
+
+    fn top(&self) -> Option<Frame> {
+        self.frames.last().copied()
+    }
+
+    /// Start a `WHEN` or `ELSE` branch, closing the previous branch of the same construct
+    fn enter_branch(&mut self, branch: Frame) -> usize {
+        if self.top() == Some(branch) {
+            self.frames.pop();
+        }
+        let level = self.frames.len();
+        self.frames.push(branch);
+        level
+    }
+
+    /// Close the innermost block at `END` and return the level the `END` sits at
+    fn close(&mut self) -> usize {
+        if matches!(self.top(), Some(Frame::Handler | Frame::CaseBranch)) {
+            self.frames.pop();
+        }
+        self.frames.pop();
+        self.frames.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str) -> String {
+        format_source(source, Language::PlSql).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_source_is_unchanged() {
+        let source = "PROCEDURE Check_Order (\n   order_no_ IN VARCHAR2,\n   line_no_  IN NUMBER ) IS\n   \
+                      count_ NUMBER := 0;\n\
+                      BEGIN\n   \
+                      -- Count the lines\n   \
+                      FOR i IN 1 .. line_no_ LOOP\n      \
+                      IF i > 1 THEN\n         count_ := count_ + 1;\n      \
+                      ELSE\n         NULL;\n      END IF;\n   END LOOP;\n\
+                      EXCEPTION\n   \
+                      WHEN no_data_found THEN\n      NULL;\n\
+                      END Check_Order;\n";
+        assert_eq!(format(source), source);
+        assert!(check_format(source, Language::PlSql).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reindents_nested_blocks() {
+        let source = "PROCEDURE Run IS\nBEGIN\n  IF x_ = 1 THEN\n        Do_It;\n  END IF;   \nEND Run;\n";
+        assert_eq!(
+            format(source),
+            "PROCEDURE Run IS\nBEGIN\n   IF x_ = 1 THEN\n      Do_It;\n   END IF;\nEND Run;\n"
+        );
+
+        let differences = check_format(source, Language::PlSql).unwrap();
+        let lines: Vec<usize> = differences.iter().map(|difference| difference.line).collect();
+        assert_eq!(lines, vec![3, 4, 5]);
+        assert_eq!(differences[1].original, "        Do_It;");
+        assert_eq!(differences[1].formatted, "      Do_It;");
+    }
+
+    #[test]
+    fn test_continuation_lines_keep_their_alignment() {
+        let source = "PROCEDURE Run IS\nBEGIN\n SELECT name\n   INTO name_\n   FROM customer_tab;\nEND Run;\n";
+        assert_eq!(
+            format(source),
+            "PROCEDURE Run IS\nBEGIN\n   SELECT name\n     INTO name_\n     FROM customer_tab;\nEND Run;\n"
+        );
+    }
+
+    #[test]
+    fn test_package_members_are_indented_and_strings_kept() {
+        let source = "PACKAGE BODY Order_API IS\nPROCEDURE Run IS\nBEGIN\nmsg_ := 'first\n  second';\nEND Run;\nEND Order_API;\n";
+        assert_eq!(
+            format(source),
+            "PACKAGE BODY Order_API IS\n   PROCEDURE Run IS\n   BEGIN\n      msg_ := 'first\n  second';\n   END Run;\nEND Order_API;\n"
+        );
+    }
+
+    #[test]
+    fn test_unparsable_source_is_an_error() {
+        assert!(format_source("PROCEDURE Run IS BEGIN", Language::PlSql).is_err());
+        assert!(format_source("<entity/>", Language::Entity).is_err());
+    }
+}
@@ -0,0 +1,508 @@
+// Heap size estimation for AST values
+//
+// Every AST type reports the heap memory it owns: the allocated capacity of
+// its strings, vectors and maps, the inline size of every boxed value, and the
+// memory those values own in turn. Hash map buckets are counted at the size of
+// their entries without the table's control bytes, so the result is an
+// estimate suitable for comparing trees rather than an exact figure.
+
+use super::ast::*;
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::path::PathBuf;
+
+/// Heap memory owned by a value, excluding its own inline size
+pub(crate) trait HeapSize {
+    fn heap_bytes(&self) -> usize;
+}
+
+/// Types that own no heap memory
+macro_rules! no_heap {
+    ($($ty:ty),* $(,)?) => {
+        $(impl HeapSize for $ty {
+            fn heap_bytes(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+no_heap!(
+    bool,
+    Span,
+    IfsAnnotation,
+    ProcedureVisibility,
+    DmlKind,
+    AttributeVisibility,
+    StateType,
+    ViewVisibility,
+    OrderDirection,
+    ParameterMode,
+    BinaryOperator,
+    UnaryOperator,
+);
+
+impl HeapSize for String {
+    fn heap_bytes(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for PathBuf {
+    fn heap_bytes(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_bytes(&self) -> usize {
+        size_of::<T>() + self.as_ref().heap_bytes()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_bytes(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_bytes)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_bytes).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for HashMap<K, V> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * size_of::<(K, V)>()
+            + self.iter().map(|(key, value)| key.heap_bytes() + value.heap_bytes()).sum::<usize>()
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_bytes(&self) -> usize {
+        self.0.heap_bytes() + self.1.heap_bytes()
+    }
+}
+
+impl HeapSize for Identifier {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes()
+    }
+}
+
+impl HeapSize for AstNode {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            AstNode::PlSql(node) => node.heap_bytes(),
+            AstNode::Entity(node) => node.heap_bytes(),
+            AstNode::Enumeration(node) => node.heap_bytes(),
+            AstNode::Views(node) => node.heap_bytes(),
+            AstNode::Storage(node) => node.heap_bytes(),
+            AstNode::MarbleProjection(node) => node.heap_bytes(),
+            AstNode::MarbleClient(node) => node.heap_bytes(),
+            AstNode::SourceFile { path, children, .. } => path.heap_bytes() + children.heap_bytes(),
+        }
+    }
+}
+
+// PL/SQL
+
+impl HeapSize for PlSqlNode {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            PlSqlNode::Package { name, component, annotations, declarations, body, .. } => {
+                name.heap_bytes()
+                    + component.heap_bytes()
+                    + annotations.heap_bytes()
+                    + declarations.heap_bytes()
+                    + body.heap_bytes()
+            }
+            PlSqlNode::Procedure {
+                name,
+                annotations,
+                parameters,
+                body,
+                exception_handlers,
+                end_label,
+                pragmas,
+                declarations,
+                ..
+            } => {
+                name.heap_bytes()
+                    + annotations.heap_bytes()
+                    + parameters.heap_bytes()
+                    + body.heap_bytes()
+                    + exception_handlers.heap_bytes()
+                    + end_label.heap_bytes()
+                    + pragmas.heap_bytes()
+                    + declarations.heap_bytes()
+            }
+            PlSqlNode::Function {
+                name,
+                annotations,
+                parameters,
+                return_type,
+                body,
+                exception_handlers,
+                end_label,
+                pragmas,
+                declarations,
+                ..
+            } => {
+                name.heap_bytes()
+                    + annotations.heap_bytes()
+                    + parameters.heap_bytes()
+                    + return_type.heap_bytes()
+                    + body.heap_bytes()
+                    + exception_handlers.heap_bytes()
+                    + end_label.heap_bytes()
+                    + pragmas.heap_bytes()
+                    + declarations.heap_bytes()
+            }
+        }
+    }
+}
+
+impl HeapSize for Pragma {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.arguments.heap_bytes()
+    }
+}
+
+impl HeapSize for OvertakeDirective {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            OvertakeDirective::Search { code, .. }
+            | OvertakeDirective::Replace { code, .. }
+            | OvertakeDirective::Append { code, .. }
+            | OvertakeDirective::Prepend { code, .. } => code.heap_bytes(),
+            OvertakeDirective::TextSearch { text, .. }
+            | OvertakeDirective::TextReplace { text, .. }
+            | OvertakeDirective::TextAppend { text, .. }
+            | OvertakeDirective::TextPrepend { text, .. } => text.heap_bytes(),
+        }
+    }
+}
+
+impl HeapSize for PlSqlDeclaration {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            PlSqlDeclaration::Variable { name, type_name, default_value, .. } => {
+                name.heap_bytes() + type_name.heap_bytes() + default_value.heap_bytes()
+            }
+            PlSqlDeclaration::Cursor { name, query, .. } => name.heap_bytes() + query.heap_bytes(),
+            PlSqlDeclaration::Exception { name, .. } => name.heap_bytes(),
+            PlSqlDeclaration::SubprogramSpec { name, parameters, return_type, .. } => {
+                name.heap_bytes() + parameters.heap_bytes() + return_type.heap_bytes()
+            }
+            PlSqlDeclaration::Subprogram(node) => node.heap_bytes(),
+        }
+    }
+}
+
+impl HeapSize for PlSqlStatement {
+    fn heap_bytes(&self) -> usize {
+        let own = match self {
+            PlSqlStatement::Assignment { target, value, .. } => target.heap_bytes() + value.heap_bytes(),
+            PlSqlStatement::If { condition, then_branch, else_branch, .. } => {
+                condition.heap_bytes() + then_branch.heap_bytes() + else_branch.heap_bytes()
+            }
+            PlSqlStatement::Loop { body, .. } => body.heap_bytes(),
+            PlSqlStatement::Return { value, .. } => value.heap_bytes(),
+            PlSqlStatement::Call { name, arguments, .. } => name.heap_bytes() + arguments.heap_bytes(),
+            PlSqlStatement::SelectInto { into, query, .. } => into.heap_bytes() + query.heap_bytes(),
+            PlSqlStatement::Block { declarations, body, exception_handlers, .. } => {
+                declarations.heap_bytes() + body.heap_bytes() + exception_handlers.heap_bytes()
+            }
+            PlSqlStatement::Raise { exception, .. } => exception.heap_bytes(),
+            PlSqlStatement::Label { name, .. } => name.heap_bytes(),
+            PlSqlStatement::Goto { label, .. } => label.heap_bytes(),
+            PlSqlStatement::Fetch { cursor, into, limit, .. } => {
+                cursor.heap_bytes() + into.heap_bytes() + limit.heap_bytes()
+            }
+            PlSqlStatement::Dml { target_table, raw, returning, .. } => {
+                target_table.heap_bytes() + raw.heap_bytes() + returning.heap_bytes()
+            }
+        };
+        own + self.trivia().map_or(0, HeapSize::heap_bytes)
+    }
+}
+
+impl HeapSize for ReturningClause {
+    fn heap_bytes(&self) -> usize {
+        self.columns.heap_bytes() + self.into.heap_bytes()
+    }
+}
+
+impl HeapSize for TriviaPiece {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            TriviaPiece::Comment(text) => text.heap_bytes(),
+            TriviaPiece::BlankLine => 0,
+        }
+    }
+}
+
+impl HeapSize for Trivia {
+    fn heap_bytes(&self) -> usize {
+        self.leading.heap_bytes() + self.trailing.heap_bytes()
+    }
+}
+
+impl HeapSize for ExceptionHandler {
+    fn heap_bytes(&self) -> usize {
+        self.exceptions.heap_bytes() + self.body.heap_bytes()
+    }
+}
+
+// Entities and enumerations
+
+impl HeapSize for EntityNode {
+    fn heap_bytes(&self) -> usize {
+        self.entity_name.heap_bytes()
+            + self.component.heap_bytes()
+            + self.code_gen_properties.heap_bytes()
+            + self.attributes.heap_bytes()
+            + self.keys.heap_bytes()
+            + self.references.heap_bytes()
+            + self.state_machine.heap_bytes()
+    }
+}
+
+impl HeapSize for CodeGenProperties {
+    fn heap_bytes(&self) -> usize {
+        self.properties.heap_bytes()
+    }
+}
+
+impl HeapSize for EntityAttribute {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.data_type.heap_bytes() + self.flags.heap_bytes() + self.properties.heap_bytes()
+    }
+}
+
+impl HeapSize for EntityKey {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.columns.heap_bytes()
+    }
+}
+
+impl HeapSize for EntityReference {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.referenced_entity.heap_bytes() + self.foreign_key_columns.heap_bytes()
+    }
+}
+
+impl HeapSize for StateMachine {
+    fn heap_bytes(&self) -> usize {
+        self.states.heap_bytes() + self.transitions.heap_bytes()
+    }
+}
+
+impl HeapSize for State {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes()
+    }
+}
+
+impl HeapSize for StateTransition {
+    fn heap_bytes(&self) -> usize {
+        self.from_state.heap_bytes() + self.to_state.heap_bytes() + self.event.heap_bytes()
+    }
+}
+
+impl HeapSize for EnumerationNode {
+    fn heap_bytes(&self) -> usize {
+        self.enumeration_name.heap_bytes() + self.component.heap_bytes() + self.values.heap_bytes()
+    }
+}
+
+impl HeapSize for EnumerationValue {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.client_value.heap_bytes() + self.label_text.heap_bytes() + self.properties.heap_bytes()
+    }
+}
+
+// Views and storage
+
+impl HeapSize for ViewsNode {
+    fn heap_bytes(&self) -> usize {
+        self.layer.heap_bytes() + self.column_definitions.heap_bytes() + self.views.heap_bytes()
+    }
+}
+
+impl HeapSize for ColumnDefinition {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes()
+            + self.flags.heap_bytes()
+            + self.datatype.heap_bytes()
+            + self.prompt.heap_bytes()
+            + self.reference.heap_bytes()
+            + self.properties.heap_bytes()
+    }
+}
+
+impl HeapSize for ViewDefinition {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.annotations.heap_bytes() + self.columns.heap_bytes() + self.query.heap_bytes()
+    }
+}
+
+impl HeapSize for StorageNode {
+    fn heap_bytes(&self) -> usize {
+        self.layer.heap_bytes() + self.definitions.heap_bytes()
+    }
+}
+
+impl HeapSize for StorageDefinition {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            StorageDefinition::Index { name, table_name, columns, .. } => {
+                name.heap_bytes() + table_name.heap_bytes() + columns.heap_bytes()
+            }
+            StorageDefinition::Sequence { name, properties, .. } => name.heap_bytes() + properties.heap_bytes(),
+            StorageDefinition::Table { name, columns, constraints, .. } => {
+                name.heap_bytes() + columns.heap_bytes() + constraints.heap_bytes()
+            }
+        }
+    }
+}
+
+impl HeapSize for TableColumn {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.data_type.heap_bytes() + self.default_value.heap_bytes()
+    }
+}
+
+impl HeapSize for TableConstraint {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            TableConstraint::PrimaryKey { name, columns, .. }
+            | TableConstraint::UniqueConstraint { name, columns, .. } => name.heap_bytes() + columns.heap_bytes(),
+        }
+    }
+}
+
+impl HeapSize for SqlQuery {
+    fn heap_bytes(&self) -> usize {
+        self.select.heap_bytes()
+            + self.from.heap_bytes()
+            + self.where_clause.heap_bytes()
+            + self.group_by.heap_bytes()
+            + self.having.heap_bytes()
+            + self.order_by.heap_bytes()
+    }
+}
+
+impl HeapSize for SelectItem {
+    fn heap_bytes(&self) -> usize {
+        self.expression.heap_bytes() + self.alias.heap_bytes()
+    }
+}
+
+impl HeapSize for FromItem {
+    fn heap_bytes(&self) -> usize {
+        self.table.heap_bytes() + self.alias.heap_bytes()
+    }
+}
+
+impl HeapSize for OrderByItem {
+    fn heap_bytes(&self) -> usize {
+        self.expression.heap_bytes()
+    }
+}
+
+// Marble
+
+impl HeapSize for MarbleProjectionNode {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.entity.heap_bytes() + self.attributes.heap_bytes() + self.actions.heap_bytes()
+    }
+}
+
+impl HeapSize for ProjectionAttribute {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.source.heap_bytes() + self.data_type.heap_bytes()
+    }
+}
+
+impl HeapSize for ProjectionAction {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.parameters.heap_bytes()
+    }
+}
+
+impl HeapSize for MarbleClientNode {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.layout.heap_bytes() + self.commands.heap_bytes()
+    }
+}
+
+impl HeapSize for LayoutElement {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            LayoutElement::Group { name, elements, .. } => name.heap_bytes() + elements.heap_bytes(),
+            LayoutElement::Field { name, binding, .. } => name.heap_bytes() + binding.heap_bytes(),
+            LayoutElement::List { name, source, columns, .. } => {
+                name.heap_bytes() + source.heap_bytes() + columns.heap_bytes()
+            }
+        }
+    }
+}
+
+impl HeapSize for ClientCommand {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.action.heap_bytes() + self.parameters.heap_bytes()
+    }
+}
+
+// Common types
+
+impl HeapSize for Parameter {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.param_type.heap_bytes() + self.default_value.heap_bytes()
+    }
+}
+
+impl HeapSize for Type {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.parameters.heap_bytes()
+    }
+}
+
+impl HeapSize for Expression {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            Expression::Identifier(identifier) => identifier.heap_bytes(),
+            Expression::Literal { value, .. } => value.heap_bytes(),
+            Expression::Binary { left, right, .. } => left.heap_bytes() + right.heap_bytes(),
+            Expression::Unary { operand, .. } => operand.heap_bytes(),
+            Expression::FunctionCall { name, arguments, .. } => name.heap_bytes() + arguments.heap_bytes(),
+            Expression::Case { operand, branches, default, .. } => {
+                operand.heap_bytes() + branches.heap_bytes() + default.heap_bytes()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vec_elements_are_charged_their_inline_size() {
+        let names = vec!["ab".to_string(), "cde".to_string()];
+        assert_eq!(names.heap_bytes(), 2 * size_of::<String>() + 5);
+
+        let mut spare = Vec::with_capacity(4);
+        spare.push("ab".to_string());
+        assert_eq!(spare.heap_bytes(), 4 * size_of::<String>() + 2);
+    }
+
+    #[test]
+    fn test_enum_elements_are_charged_the_whole_enum() {
+        let values = vec![Expression::Literal { value: "1".to_string(), span: Span::from([0; 6]) }];
+        assert_eq!(values.heap_bytes(), size_of::<Expression>() + 1);
+    }
+}
@@ -65,6 +65,7 @@ impl IncrementalParser {
 
         Ok(AstNode::PlSql(PlSqlNode::Package {
             name,
+            is_body: false,
             component: None,
             annotations: Vec::new(),
             declarations: Vec::new(),
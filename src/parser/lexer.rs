@@ -22,6 +22,26 @@ pub struct Token {
     pub position: TokenPosition,
 }
 
+impl Token {
+    /// Whether this token is whitespace, a newline or a comment
+    pub fn is_trivia(&self) -> bool {
+        self.token_type.is_trivia()
+    }
+    
+    /// Position just past the last character of this token
+    pub fn end_position(&self) -> TokenPosition {
+        let column = match self.value.rfind('\n') {
+            Some(index) => 1 + self.value[index + 1..].chars().count(),
+            None => self.position.column + self.value.chars().count(),
+        };
+        TokenPosition {
+            line: self.position.line + self.value.matches('\n').count(),
+            column,
+            offset: self.position.offset + self.value.len(),
+        }
+    }
+}
+
 /// Token types for all supported languages
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TokenType {
@@ -29,6 +49,8 @@ pub enum TokenType {
     Identifier,
     Number,
     String,
+    True,
+    False,
     Comment,
     Whitespace,
     Newline,
@@ -58,6 +80,13 @@ pub enum TokenType {
     Comma,
     Dot,
     Colon,
+    SlashTerminator,
+    
+    // SQL*Plus substitution variable such as `&module` or `&&cmp`
+    Substitution,
+    
+    // Conditional compilation inquiry directive such as `$$PLSQL_UNIT`
+    InquiryDirective,
     
     // PL/SQL keywords
     Package,
@@ -82,6 +111,8 @@ pub enum TokenType {
     Cursor,
     Type,
     Record,
+    Raise,
+    Goto,
     
     // SQL keywords
     Select,
@@ -159,6 +190,13 @@ pub enum TokenType {
     Error,
 }
 
+impl TokenType {
+    /// Whether tokens of this type carry no syntax: whitespace, newlines and comments
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, TokenType::Whitespace | TokenType::Newline | TokenType::Comment)
+    }
+}
+
 impl fmt::Display for TokenType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -174,14 +212,27 @@ impl fmt::Display for TokenType {
     }
 }
 
+/// Where a line starts relative to multi-line constructs, for line-by-line lexing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LexerState {
+    /// Between tokens
+    #[default]
+    Normal,
+    /// Inside a `/* ... */` comment
+    InBlockComment,
+    /// Inside a `q'[...]'` string, waiting for `close` followed by `'`
+    InQuotedString { close: char },
+}
+
 /// Lexer for tokenizing source code
 pub struct Lexer {
     input: String,
     position: usize,
     line: usize,
     column: usize,
-    #[allow(dead_code)]
     language: Language,
+    /// Where the token currently being scanned starts
+    token_start: TokenPosition,
 }
 
 impl Lexer {
@@ -193,16 +244,18 @@ impl Lexer {
             line: 1,
             column: 1,
             language,
+            token_start: TokenPosition { line: 1, column: 1, offset: 0 },
         }
     }
     
     /// Get the next token from the input
     pub fn next_token(&mut self) -> Token {
+        let start_position = self.current_position();
+        self.token_start = start_position;
         if self.is_at_end() {
             return self.make_token(TokenType::Eof, "");
         }
         
-        let start_position = self.current_position();
         let ch = self.advance();
         
         match ch {
@@ -262,6 +315,16 @@ impl Lexer {
                     }
                     let value = &self.input[start_position.offset..self.position];
                     self.make_token(TokenType::Comment, value)
+                } else if self.peek() == '/' && self.has_slash_line_comments() {
+                    // Line comment in Marble files
+                    while self.peek() != '\n' && !self.is_at_end() {
+                        self.advance();
+                    }
+                    let value = &self.input[start_position.offset..self.position];
+                    self.make_token(TokenType::Comment, value)
+                } else if self.is_alone_on_line(start_position.offset) {
+                    // SQL*Plus block terminator: a lone `/` on its own line
+                    self.make_token(TokenType::SlashTerminator, "/")
                 } else {
                     self.make_token(TokenType::Divide, "/")
                 }
@@ -313,8 +376,35 @@ impl Lexer {
                 self.make_token(TokenType::String, value)
             }
             
+            '"' if self.has_double_quoted_strings() => {
+                while self.peek() != '"' && !self.is_at_end() {
+                    if self.advance() == '\n' {
+                        self.line += 1;
+                        self.column = 1;
+                    }
+                }
+                
+                if self.is_at_end() {
+                    return self.make_token(TokenType::Error, "Unterminated string");
+                }
+                
+                self.advance(); // consume closing "
+                let value = &self.input[start_position.offset..self.position];
+                self.make_token(TokenType::String, value)
+            }
+            
             // Numbers
             ch if ch.is_ascii_digit() => {
+                // Hexadecimal literals such as 0xFF
+                if ch == '0' && matches!(self.peek(), 'x' | 'X') && self.peek_next().is_ascii_hexdigit() {
+                    self.advance(); // consume x
+                    while self.peek().is_ascii_hexdigit() {
+                        self.advance();
+                    }
+                    let value = &self.input[start_position.offset..self.position];
+                    return self.make_token(TokenType::Number, value);
+                }
+                
                 while self.peek().is_ascii_digit() {
                     self.advance();
                 }
@@ -327,6 +417,27 @@ impl Lexer {
                     }
                 }
                 
+                // Exponent, e.g. 1.5e10 or 1E-3
+                if matches!(self.peek(), 'e' | 'E')
+                    && (self.peek_next().is_ascii_digit()
+                        || (matches!(self.peek_next(), '+' | '-') && self.peek_at(2).is_ascii_digit()))
+                {
+                    self.advance(); // consume e
+                    if matches!(self.peek(), '+' | '-') {
+                        self.advance();
+                    }
+                    while self.peek().is_ascii_digit() {
+                        self.advance();
+                    }
+                }
+                
+                // Oracle's BINARY_FLOAT / BINARY_DOUBLE suffixes, e.g. 2.5f or 1d
+                if matches!(self.peek(), 'f' | 'F' | 'd' | 'D')
+                    && !(self.peek_next().is_ascii_alphanumeric() || self.peek_next() == '_')
+                {
+                    self.advance();
+                }
+                
                 let value = &self.input[start_position.offset..self.position];
                 self.make_token(TokenType::Number, value)
             }
@@ -352,6 +463,16 @@ impl Lexer {
             }
             
             '$' => {
+                // Inquiry directives such as $$PLSQL_LINE or $$DEBUG
+                if self.peek() == '$' && self.peek_next().is_ascii_alphabetic() {
+                    self.advance();
+                    while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+                        self.advance();
+                    }
+                    let value = &self.input[start_position.offset..self.position];
+                    return self.make_token(TokenType::InquiryDirective, value);
+                }
+                
                 // Handle overtake directives like $SEARCH, $REPLACE, etc.
                 if self.peek().is_ascii_alphabetic() {
                     while self.peek().is_ascii_alphanumeric() {
@@ -368,6 +489,11 @@ impl Lexer {
                         "$TEXTAPPEND" => TokenType::TextAppend,
                         "$TEXTPREPEND" => TokenType::TextPrepend,
                         "$END" => TokenType::End,
+                        // Conditional compilation reuses the IF tokens, see `Parser::parse_if`
+                        "$IF" => TokenType::If,
+                        "$THEN" => TokenType::Then,
+                        "$ELSIF" => TokenType::ElseIf,
+                        "$ELSE" => TokenType::Else,
                         _ => TokenType::Identifier,
                     };
                     self.make_token(token_type, value)
@@ -376,6 +502,53 @@ impl Lexer {
                 }
             }
             
+            '&' => {
+                // SQL*Plus substitution variables: &name, &&name
+                if self.peek() == '&' {
+                    self.advance();
+                }
+                if self.peek().is_ascii_alphabetic() || self.peek() == '_' {
+                    while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+                        self.advance();
+                    }
+                    // A single period ends the variable name, as in `&AO..Module_API`
+                    if self.peek() == '.' {
+                        self.advance();
+                    }
+                    let value = &self.input[start_position.offset..self.position];
+                    self.make_token(TokenType::Substitution, value)
+                } else {
+                    let value = &self.input[start_position.offset..self.position];
+                    self.make_token(TokenType::Error, value)
+                }
+            }
+            
+            // Oracle alternative quoting, e.g. q'[it's]' or q'!text!'
+            'q' | 'Q'
+                if self.peek() == '\''
+                    && !self.has_double_quoted_strings()
+                    && !self.peek_next().is_whitespace()
+                    && self.peek_next() != '\0' =>
+            {
+                self.advance(); // consume '
+                let close = Self::q_quote_close(self.advance());
+                while !self.is_at_end() && (self.peek() != close || self.peek_next() != '\'') {
+                    if self.advance() == '\n' {
+                        self.line += 1;
+                        self.column = 1;
+                    }
+                }
+                
+                if self.is_at_end() {
+                    return self.make_token(TokenType::Error, "Unterminated string");
+                }
+                
+                self.advance(); // consume closing delimiter
+                self.advance(); // consume closing '
+                let value = &self.input[start_position.offset..self.position];
+                self.make_token(TokenType::String, value)
+            }
+            
             // Identifiers and keywords
             ch if ch.is_ascii_alphabetic() || ch == '_' => {
                 while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
@@ -407,23 +580,174 @@ impl Lexer {
         tokens
     }
     
+    /// Tokenize the entire input and keep only the comments, with their positions
+    pub fn comments(&mut self) -> Vec<Token> {
+        self.tokenize()
+            .into_iter()
+            .filter(|token| token.token_type == TokenType::Comment)
+            .collect()
+    }
+    
+    /// Re-lex the tokens starting in `start_byte..end_byte`, with absolute positions.
+    /// A start inside a token snaps back to the beginning of its line.
+    pub fn tokenize_range(&mut self, start_byte: usize, end_byte: usize) -> Vec<Token> {
+        let mut start = start_byte.min(self.input.len());
+        while !self.input.is_char_boundary(start) {
+            start -= 1;
+        }
+        start = self.input[..start].rfind('\n').map_or(0, |newline| newline + 1);
+        
+        let line = 1 + self.input[..start].matches('\n').count();
+        let column = 1 + self.input[..start].chars().rev().take_while(|&c| c != '\n').count();
+        self.tokenize_range_from(TokenPosition { line, column, offset: start }, end_byte)
+    }
+    
+    /// Re-lex from a known token boundary up to `end_byte`.
+    /// The caller provides the line and column of `start.offset`.
+    pub fn tokenize_range_from(&mut self, start: TokenPosition, end_byte: usize) -> Vec<Token> {
+        self.position = start.offset;
+        self.line = start.line;
+        self.column = start.column;
+        
+        let mut tokens = Vec::new();
+        while self.position < end_byte {
+            let token = self.next_token();
+            if token.token_type == TokenType::Eof {
+                break;
+            }
+            tokens.push(token);
+        }
+        
+        tokens
+    }
+    
+    /// Tokenize a single line, starting inside whatever construct `prev_state`
+    /// says the previous line left open. Returns the line's tokens (without EOF)
+    /// and the state the next line starts in.
+    pub fn tokenize_line(&mut self, prev_state: LexerState, line: &str) -> (Vec<Token>, LexerState) {
+        let line_number = self.line;
+        self.input = line.to_string();
+        self.position = 0;
+        self.column = 1;
+        
+        let mut tokens = Vec::new();
+        let mut state = prev_state;
+        
+        // Finish the construct carried over from the previous line
+        let (token_type, terminator) = match prev_state {
+            LexerState::Normal => (None, String::new()),
+            LexerState::InBlockComment => (Some(TokenType::Comment), "*/".to_string()),
+            LexerState::InQuotedString { close } => (Some(TokenType::String), format!("{}'", close)),
+        };
+        if let Some(token_type) = token_type {
+            let end = match line.find(&terminator) {
+                Some(index) => {
+                    state = LexerState::Normal;
+                    index + terminator.len()
+                }
+                None => line.len(),
+            };
+            self.token_start = self.current_position();
+            self.position = end;
+            self.column = 1 + line[..end].chars().count();
+            if end > 0 {
+                tokens.push(self.make_token(token_type, &line[..end]));
+            }
+        }
+        
+        while state == LexerState::Normal {
+            let start = self.position;
+            let mut token = self.next_token();
+            match token.token_type {
+                TokenType::Eof => break,
+                TokenType::Comment
+                    if token.value.starts_with("/*") && (token.value.len() < 4 || !token.value.ends_with("*/")) =>
+                {
+                    state = LexerState::InBlockComment;
+                }
+                TokenType::Error if token.value == "Unterminated string" => {
+                    let text = &line[start..];
+                    if let Some(open) = text.strip_prefix(['q', 'Q']).and_then(|rest| rest.strip_prefix('\'')) {
+                        let open = open.chars().next().unwrap_or('\0');
+                        state = LexerState::InQuotedString { close: Self::q_quote_close(open) };
+                        token = self.make_token(TokenType::String, text);
+                    }
+                }
+                _ => {}
+            }
+            tokens.push(token);
+        }
+        
+        self.line = line_number + 1;
+        (tokens, state)
+    }
+    
+    /// The closing delimiter of a `q'<open>...<close>'` string
+    fn q_quote_close(open: char) -> char {
+        match open {
+            '[' => ']',
+            '{' => '}',
+            '(' => ')',
+            '<' => '>',
+            other => other,
+        }
+    }
+    
     fn is_at_end(&self) -> bool {
         self.position >= self.input.len()
     }
     
+    // `position` is a byte offset, so look ahead from the remaining input rather than
+    // indexing characters from the start
     fn advance(&mut self) -> char {
-        let ch = self.input.chars().nth(self.position).unwrap_or('\0');
+        let ch = self.peek();
         self.position += ch.len_utf8();
         self.column += 1;
         ch
     }
     
     fn peek(&self) -> char {
-        self.input.chars().nth(self.position).unwrap_or('\0')
+        self.peek_at(0)
     }
     
     fn peek_next(&self) -> char {
-        self.input.chars().nth(self.position + 1).unwrap_or('\0')
+        self.peek_at(1)
+    }
+    
+    fn peek_at(&self, distance: usize) -> char {
+        self.input
+            .get(self.position..)
+            .and_then(|rest| rest.chars().nth(distance))
+            .unwrap_or('\0')
+    }
+    
+    /// Whether `//` starts a line comment in this lexer's language
+    fn has_slash_line_comments(&self) -> bool {
+        matches!(self.language, Language::MarbleProjection | Language::MarbleClient)
+    }
+    
+    /// Whether `"..."` is a string literal rather than a quoted identifier
+    fn has_double_quoted_strings(&self) -> bool {
+        matches!(
+            self.language,
+            Language::Entity | Language::Enumeration | Language::MarbleProjection | Language::MarbleClient
+        )
+    }
+    
+    /// Check whether the single character at `offset` is the only
+    /// non-whitespace content on its line
+    fn is_alone_on_line(&self, offset: usize) -> bool {
+        let is_blank = |c: char| c == ' ' || c == '\t' || c == '\r';
+        let before = self.input[..offset]
+            .chars()
+            .rev()
+            .take_while(|&c| c != '\n')
+            .all(is_blank);
+        let after = self.input[offset + 1..]
+            .chars()
+            .take_while(|&c| c != '\n')
+            .all(is_blank);
+        before && after
     }
     
     fn current_position(&self) -> TokenPosition {
@@ -438,7 +762,7 @@ impl Lexer {
         Token {
             token_type,
             value: value.to_string(),
-            position: self.current_position(),
+            position: self.token_start,
         }
     }
     
@@ -467,6 +791,10 @@ impl Lexer {
             "cursor" => TokenType::Cursor,
             "type" => TokenType::Type,
             "record" => TokenType::Record,
+            "raise" => TokenType::Raise,
+            "goto" => TokenType::Goto,
+            "true" => TokenType::True,
+            "false" => TokenType::False,
             
             // SQL keywords
             "select" => TokenType::Select,
@@ -561,4 +889,196 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::Comment);
         assert_eq!(tokens[0].value, "-- This is a comment");
     }
+    
+    #[test]
+    fn test_slash_line_comment_depends_on_language() {
+        let tokens = Lexer::new("// note\n".to_string(), Language::MarbleProjection).tokenize();
+        assert_eq!(tokens[0].token_type, TokenType::Comment);
+        assert_eq!(tokens[0].value, "// note");
+        
+        let tokens = Lexer::new("// note\n".to_string(), Language::PlSql).tokenize();
+        let types: Vec<&TokenType> = tokens.iter().take(2).map(|t| &t.token_type).collect();
+        assert_eq!(types, vec![&TokenType::Divide, &TokenType::Divide]);
+    }
+    
+    #[test]
+    fn test_slash_terminator_tokenization() {
+        let mut lexer = Lexer::new("END;\n/\nx := a / b;".to_string(), Language::PlSql);
+        let tokens = lexer.tokenize();
+        
+        let slash_types: Vec<&TokenType> = tokens
+            .iter()
+            .filter(|t| t.value == "/")
+            .map(|t| &t.token_type)
+            .collect();
+        assert_eq!(slash_types, vec![&TokenType::SlashTerminator, &TokenType::Divide]);
+    }
+    
+    #[test]
+    fn test_substitution_variable_tokenization() {
+        let tokens = Lexer::new("&x := 1;".to_string(), Language::PlSql).tokenize();
+        assert_eq!(tokens[0].token_type, TokenType::Substitution);
+        assert_eq!(tokens[0].value, "&x");
+        
+        let tokens = Lexer::new("&&y".to_string(), Language::PlSql).tokenize();
+        assert_eq!(tokens[0].token_type, TokenType::Substitution);
+        assert_eq!(tokens[0].value, "&&y");
+        assert_eq!(tokens[1].token_type, TokenType::Eof);
+    }
+    
+    #[test]
+    fn test_boolean_literal_tokenization() {
+        let tokens = Lexer::new("True FALSE truth".to_string(), Language::PlSql).tokenize();
+        let types: Vec<TokenType> = tokens
+            .into_iter()
+            .filter(|t| t.token_type != TokenType::Whitespace)
+            .map(|t| t.token_type)
+            .collect();
+        assert_eq!(types, vec![TokenType::True, TokenType::False, TokenType::Identifier, TokenType::Eof]);
+    }
+    
+    #[test]
+    fn test_raise_keyword_tokenization() {
+        let tokens = Lexer::new("RAISE raise_application_error".to_string(), Language::PlSql).tokenize();
+        assert_eq!(tokens[0].token_type, TokenType::Raise);
+        assert_eq!(tokens[2].token_type, TokenType::Identifier);
+        assert_eq!(tokens[2].value, "raise_application_error");
+    }
+    
+    #[test]
+    fn test_non_ascii_strings_and_comments_tokenize_by_byte() {
+        let input = "x_ := 'ÅÄÖåäö'; -- Ändrad av Åsa\ny_ := 'é';";
+        let tokens = Lexer::new(input.to_string(), Language::PlSql).tokenize();
+        let strings: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.token_type == TokenType::String)
+            .map(|t| t.value.as_str())
+            .collect();
+        assert_eq!(strings, vec!["'ÅÄÖåäö'", "'é'"]);
+        assert!(tokens.iter().any(|t| t.token_type == TokenType::Comment && t.value == "-- Ändrad av Åsa"));
+        assert!(tokens.iter().all(|t| t.token_type != TokenType::Error));
+        
+        let y = tokens.iter().find(|t| t.value == "y_").unwrap();
+        assert_eq!(&input[y.position.offset..y.position.offset + 2], "y_");
+    }
+    
+    #[test]
+    fn test_substitution_variable_consumes_terminating_period() {
+        let tokens = Lexer::new("&AO..Module_API".to_string(), Language::PlSql).tokenize();
+        let values: Vec<&str> = tokens.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, vec!["&AO.", ".", "Module_API", ""]);
+        assert_eq!(tokens[0].token_type, TokenType::Substitution);
+    }
+    
+    #[test]
+    fn test_tokenize_range_uses_absolute_positions() {
+        let input = "PROCEDURE Test_Proc IS\nBEGIN\n   value_ := 42;\nEND;\n";
+        let full = Lexer::new(input.to_string(), Language::PlSql).tokenize();
+        
+        let line_start = input.find("   value_").unwrap();
+        let line_end = input[line_start..].find('\n').unwrap() + line_start + 1;
+        
+        // Start in the middle of `value_` to exercise boundary snapping
+        let mut lexer = Lexer::new(input.to_string(), Language::PlSql);
+        let tokens = lexer.tokenize_range(line_start + 5, line_end);
+        
+        let values: Vec<&str> = tokens.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, vec!["   ", "value_", " ", ":=", " ", "42", ";", "\n"]);
+        
+        let first = full.iter().position(|t| t.value == "   ").unwrap();
+        assert_eq!(tokens, full[first..first + tokens.len()].to_vec());
+        assert!(tokens.iter().all(|t| t.position.line >= 3));
+    }
+    
+    #[test]
+    fn test_number_literal_forms() {
+        for input in ["1.5e10", "1E-3", "0xFF", "2.5f", "42"] {
+            let tokens = Lexer::new(format!("{};", input), Language::PlSql).tokenize();
+            assert_eq!(tokens[0].token_type, TokenType::Number, "{}", input);
+            assert_eq!(tokens[0].value, input);
+            assert_eq!(tokens[1].token_type, TokenType::Semicolon, "{}", input);
+        }
+        
+        // A trailing letter that starts an identifier is not a suffix
+        let tokens = Lexer::new("1days_".to_string(), Language::PlSql).tokenize();
+        assert_eq!(tokens[0].value, "1");
+        let tokens = Lexer::new("10e".to_string(), Language::PlSql).tokenize();
+        assert_eq!(tokens[0].value, "10");
+    }
+    
+    #[test]
+    fn test_is_trivia() {
+        for token_type in [TokenType::Whitespace, TokenType::Newline, TokenType::Comment] {
+            assert!(token_type.is_trivia(), "{:?}", token_type);
+        }
+        for token_type in [
+            TokenType::Identifier,
+            TokenType::Number,
+            TokenType::String,
+            TokenType::Semicolon,
+            TokenType::Begin,
+            TokenType::Substitution,
+            TokenType::SlashTerminator,
+            TokenType::Error,
+            TokenType::Eof,
+        ] {
+            assert!(!token_type.is_trivia(), "{:?}", token_type);
+        }
+        
+        let tokens = Lexer::new("x -- note\n".to_string(), Language::PlSql).tokenize();
+        let trivia: Vec<bool> = tokens.iter().map(Token::is_trivia).collect();
+        assert_eq!(trivia, vec![false, true, true, true, false]);
+    }
+    
+    #[test]
+    fn test_q_quote_string_tokenization() {
+        let tokens = Lexer::new("x := q'[it's]';".to_string(), Language::PlSql).tokenize();
+        let strings: Vec<&str> = tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::String)
+            .map(|token| token.value.as_str())
+            .collect();
+        assert_eq!(strings, vec!["q'[it's]'"]);
+    }
+    
+    #[test]
+    fn test_tokenize_line_carries_block_comment_state() {
+        let mut lexer = Lexer::new(String::new(), Language::PlSql);
+        
+        let (tokens, state) = lexer.tokenize_line(LexerState::Normal, "x := 1; /* first");
+        assert_eq!(state, LexerState::InBlockComment);
+        assert_eq!(tokens.last().unwrap().token_type, TokenType::Comment);
+        assert_eq!(tokens.last().unwrap().value, "/* first");
+        
+        let (tokens, state) = lexer.tokenize_line(state, "second */ y");
+        assert_eq!(state, LexerState::Normal);
+        assert_eq!(tokens[0].token_type, TokenType::Comment);
+        assert_eq!(tokens[0].value, "second */");
+        assert_eq!(tokens[0].position.line, 2);
+        assert_eq!(tokens.last().unwrap().value, "y");
+        
+        let (_, state) = lexer.tokenize_line(LexerState::Normal, "s := q'[open");
+        assert_eq!(state, LexerState::InQuotedString { close: ']' });
+        let (tokens, state) = lexer.tokenize_line(state, "close]';");
+        assert_eq!(state, LexerState::Normal);
+        assert_eq!(tokens[0].token_type, TokenType::String);
+        assert_eq!(tokens[1].token_type, TokenType::Semicolon);
+    }
+    
+    #[test]
+    fn test_inquiry_directive_tokenization() {
+        let tokens = Lexer::new("line_ := $$PLSQL_LINE;".to_string(), Language::PlSql).tokenize();
+        let directive = tokens
+            .iter()
+            .find(|t| t.token_type == TokenType::InquiryDirective)
+            .expect("inquiry directive");
+        assert_eq!(directive.value, "$$PLSQL_LINE");
+        
+        let tokens = Lexer::new("$IF $$DEBUG $THEN".to_string(), Language::PlSql).tokenize();
+        let types: Vec<&TokenType> = tokens.iter().filter(|t| !t.is_trivia()).map(|t| &t.token_type).collect();
+        assert_eq!(
+            types,
+            vec![&TokenType::If, &TokenType::InquiryDirective, &TokenType::Then, &TokenType::Eof]
+        );
+    }
 }
@@ -0,0 +1,91 @@
+// Offset to line/column conversion for source text
+
+use crate::parser::ast::Position;
+
+/// Precomputed line starts for converting between byte offsets and 1-based line/column positions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    source: String,
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Build a line index over the given source
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            source: source.to_string(),
+            line_starts,
+        }
+    }
+    
+    /// Number of lines in the source
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+    
+    /// Convert a byte offset to a position; columns count characters from 1.
+    /// Offsets past the end are clamped to the end of the source.
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let column = self.source[self.line_starts[line]..offset].chars().count() + 1;
+        Position {
+            line: line + 1,
+            column,
+            offset,
+        }
+    }
+    
+    /// Convert a 1-based line and column to a byte offset, if it lies within the source
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.source.len(), |next| next - 1);
+        
+        let text = &self.source[start..end];
+        let column = column.checked_sub(1)?;
+        match text.char_indices().nth(column) {
+            Some((i, _)) => Some(start + i),
+            None if column == text.chars().count() => Some(end),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_offset_to_position() {
+        let index = LineIndex::new("BEGIN\n   NULL;\nEND;");
+        
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(index.position(0), Position { line: 1, column: 1, offset: 0 });
+        assert_eq!(index.position(9), Position { line: 2, column: 4, offset: 9 });
+        assert_eq!(index.position(15), Position { line: 3, column: 1, offset: 15 });
+        assert_eq!(index.position(100).line, 3);
+    }
+    
+    #[test]
+    fn test_position_to_offset_round_trips() {
+        let source = "x := 'é';\ny := 1;\n";
+        let index = LineIndex::new(source);
+        
+        for offset in source.char_indices().map(|(i, _)| i) {
+            let position = index.position(offset);
+            assert_eq!(index.offset(position.line, position.column), Some(offset));
+        }
+        assert_eq!(index.offset(2, 99), None);
+        assert_eq!(index.offset(0, 1), None);
+    }
+}
@@ -7,14 +7,19 @@
 // - Marble DSL
 
 pub mod ast;
+pub mod formatter;
+mod heap_size;
 pub mod incremental;
 pub mod lexer;
+pub mod line_index;
 pub mod parser;
 pub mod tree_sitter_simple;
 
 pub use ast::*;
+pub use formatter::*;
 pub use incremental::*;
 pub use lexer::*;
+pub use line_index::*;
 pub use parser::*;
 pub use tree_sitter_simple::*;
 
@@ -38,6 +43,19 @@ pub enum Language {
 }
 
 impl Language {
+    /// All supported languages
+    pub fn all() -> &'static [Language] {
+        &[
+            Language::PlSql,
+            Language::Entity,
+            Language::Enumeration,
+            Language::Views,
+            Language::Storage,
+            Language::MarbleProjection,
+            Language::MarbleClient,
+        ]
+    }
+
     /// Get the file extensions associated with this language
     pub fn extensions(&self) -> &'static [&'static str] {
         match self {
@@ -64,4 +82,10 @@ impl Language {
             _ => None,
         }
     }
+
+    /// Detect language from the extension of a file path
+    pub fn from_path(path: &std::path::Path) -> Option<Language> {
+        let extension = path.extension()?.to_str()?;
+        Language::from_extension(&format!(".{}", extension))
+    }
 }
@@ -6,14 +6,24 @@
 // - SQL variant
 // - Marble DSL
 
-use crate::parser::{ast::*, lexer::*, Language};
+use crate::parser::{ast::*, lexer::*, line_index::LineIndex, Language};
 use crate::Result;
 use thiserror::Error;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Error, Debug)]
 pub enum ParseError {
-    #[error("Unexpected token: expected {expected}, found {found}")]
-    UnexpectedToken { expected: String, found: String },
+    #[error("Unexpected token: expected {expected}, found '{found}' at line {line}, column {column}")]
+    UnexpectedToken {
+        expected: String,
+        found: String,
+        line: usize,
+        column: usize,
+    },
     
     #[error("Unexpected end of input")]
     UnexpectedEof,
@@ -23,6 +33,97 @@ pub enum ParseError {
     
     #[error("Unsupported language: {language:?}")]
     UnsupportedLanguage { language: Language },
+    
+    #[error("Parse did not finish before its deadline")]
+    TimedOut,
+    
+    #[error("Parsing was cancelled")]
+    Cancelled,
+}
+
+impl ParseError {
+    /// Error for `token` appearing where `expected` was required, located at the token
+    pub fn unexpected(expected: &str, token: &Token) -> Self {
+        let found = match token.token_type {
+            TokenType::Eof => token.token_type.to_string(),
+            _ => token.value.clone(),
+        };
+        ParseError::UnexpectedToken {
+            expected: expected.to_string(),
+            found,
+            line: token.position.line,
+            column: token.position.column,
+        }
+    }
+}
+
+/// Statement keywords that are skipped because they have no AST node yet
+const UNMODELLED_STATEMENT_KEYWORDS: &[&str] = &[
+    "commit", "rollback", "savepoint", "exit", "continue", "open",
+    "close", "execute", "pipe", "forall", "lock", "set",
+];
+
+/// Options controlling how much of the source the parser builds into the AST
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Build statement bodies; when false subprograms are returned with empty bodies
+    pub bodies: bool,
+    /// Attach comments and blank lines to statements instead of discarding them
+    pub preserve_trivia: bool,
+    /// Give up with `ParseError::TimedOut` once this instant has passed
+    pub deadline: Option<Instant>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            bodies: true,
+            preserve_trivia: false,
+            deadline: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Options for a fast symbol outline: signatures, declarations and nesting only
+    pub fn outline() -> Self {
+        Self {
+            bodies: false,
+            ..Self::default()
+        }
+    }
+    
+    /// Options for lossless rewriting: full bodies with trivia kept on statements
+    pub fn lossless() -> Self {
+        Self {
+            preserve_trivia: true,
+            ..Self::default()
+        }
+    }
+    
+    /// Set the deadline to `timeout` from now, so it also covers tokenizing the input
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + timeout),
+            ..self
+        }
+    }
+}
+
+/// Number of consumed tokens between checks of `ParseOptions::deadline`
+const DEADLINE_CHECK_INTERVAL: usize = 256;
+
+/// Optional clauses that may follow a function's return type
+const FUNCTION_CLAUSE_KEYWORDS: &[&str] = &["pipelined", "result_cache", "deterministic", "parallel_enable"];
+
+/// Everything after a subprogram's signature: `IS ... BEGIN ... END [name];`
+#[derive(Default)]
+struct SubprogramBody {
+    pragmas: Vec<Pragma>,
+    declarations: Vec<PlSqlDeclaration>,
+    body: Vec<PlSqlStatement>,
+    exception_handlers: Vec<ExceptionHandler>,
+    end_label: Option<Identifier>,
 }
 
 /// Parser state for tracking current position and tokens
@@ -30,15 +131,28 @@ pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
     language: Language,
+    options: ParseOptions,
+    /// Tokens consumed so far, used to space out deadline checks
+    advances: usize,
+    /// The deadline passed; every later `advance` lands on EOF so the parse unwinds
+    timed_out: bool,
 }
 
 impl Parser {
     /// Create a new parser for the given tokens and language
     pub fn new(tokens: Vec<Token>, language: Language) -> Self {
+        Self::with_options(tokens, language, ParseOptions::default())
+    }
+    
+    /// Create a new parser with explicit parse options
+    pub fn with_options(tokens: Vec<Token>, language: Language, options: ParseOptions) -> Self {
         let mut parser = Self {
             tokens,
             current: 0,
             language,
+            options,
+            advances: 0,
+            timed_out: false,
         };
         // Skip any initial whitespace
         parser.skip_whitespace();
@@ -47,21 +161,75 @@ impl Parser {
     
     /// Parse the tokens into an AST
     pub fn parse(&mut self) -> Result<AstNode> {
-        match self.language {
-            Language::PlSql => Ok(AstNode::PlSql(self.parse_plsql()?)),
-            Language::Entity => Ok(AstNode::Entity(self.parse_entity()?)),
-            Language::Enumeration => Ok(AstNode::Enumeration(self.parse_enumeration()?)),
-            Language::Views => Ok(AstNode::Views(self.parse_views()?)),
-            Language::Storage => Ok(AstNode::Storage(self.parse_storage()?)),
-            Language::MarbleProjection => Ok(AstNode::MarbleProjection(self.parse_marble_projection()?)),
-            Language::MarbleClient => Ok(AstNode::MarbleClient(self.parse_marble_client()?)),
+        let parsed = match self.language {
+            Language::PlSql => self.parse_plsql_file(),
+            Language::Entity => self.parse_entity().map(AstNode::Entity),
+            Language::Enumeration => self.parse_enumeration().map(AstNode::Enumeration),
+            Language::Views => self.parse_views().map(AstNode::Views),
+            Language::Storage => self.parse_storage().map(AstNode::Storage),
+            Language::MarbleProjection => self.parse_marble_projection().map(AstNode::MarbleProjection),
+            Language::MarbleClient => self.parse_marble_client().map(AstNode::MarbleClient),
+        };
+        self.unless_timed_out(parsed)
+    }
+    
+    /// Replace the result of a parse cut short by the deadline with `ParseError::TimedOut`
+    fn unless_timed_out<T>(&self, parsed: Result<T>) -> Result<T> {
+        if self.timed_out {
+            return Err(ParseError::TimedOut.into());
+        }
+        parsed
+    }
+    
+    /// Parse every top-level unit in the input.
+    /// PL/SQL files may hold several subprograms separated by `/` terminators.
+    pub fn parse_all(&mut self) -> Result<Vec<AstNode>> {
+        if self.language != Language::PlSql {
+            return Ok(vec![self.parse()?]);
+        }
+        
+        let mut nodes = Vec::new();
+        loop {
+            while self.match_token(TokenType::SlashTerminator) {}
+            if self.is_at_end() {
+                break;
+            }
+            match self.parse_plsql() {
+                Ok(node) => nodes.push(AstNode::PlSql(node)),
+                Err(e) => return self.unless_timed_out(Err(e)),
+            }
+        }
+        self.unless_timed_out(Ok(nodes))
+    }
+    
+    /// Parse a PL/SQL file, wrapping it in a `SourceFile` when it holds more than one unit
+    fn parse_plsql_file(&mut self) -> Result<AstNode> {
+        let start = self.current_position();
+        if self.is_at_end() {
+            // Report the missing declaration rather than returning an empty file
+            return Ok(AstNode::PlSql(self.parse_plsql()?));
+        }
+        
+        let mut children = self.parse_all()?;
+        if children.len() == 1 {
+            return Ok(children.remove(0));
         }
+        Ok(AstNode::SourceFile {
+            path: None,
+            children,
+            span: self.span_from(start),
+        })
     }
     
     /// Parse PL/SQL source code (IFS-style with direct procedure/function declarations)
     fn parse_plsql(&mut self) -> Result<PlSqlNode> {
         self.skip_whitespace();
         
+        let annotations = self.parse_annotations();
+        if !annotations.is_empty() {
+            return Ok(self.parse_annotated_subprogram()?.with_annotations(annotations));
+        }
+        
         // For IFS Cloud, we expect direct function/procedure declarations, not packages
         if self.match_token(TokenType::Function) {
             self.parse_function()
@@ -71,40 +239,39 @@ impl Parser {
             // Still support legacy package format for compatibility
             self.parse_package()
         } else {
-            Err(ParseError::InvalidSyntax {
-                message: "Expected function or procedure declaration".to_string(),
-            }.into())
+            Err(ParseError::unexpected("FUNCTION, PROCEDURE or PACKAGE", self.peek_non_whitespace()).into())
         }
     }
     
     fn parse_package(&mut self) -> Result<PlSqlNode> {
         let start_pos = self.previous().position;
+        let is_body = self.match_token(TokenType::Body);
         let name = self.consume_identifier("Expected package name")?;
         
-        // Accept either 'AS' or 'IS' after package name
-        if !self.match_token(TokenType::As) && !self.match_token(TokenType::Is) {
-            return Err(ParseError::UnexpectedToken {
-                expected: "AS or IS".to_string(),
-                found: format!("{:?}", self.peek().token_type),
-            }.into());
-        }
+        self.expect_one_of(&[TokenType::As, TokenType::Is], "after package name")?;
         
-        let declarations = Vec::new();
+        let mut declarations = Vec::new();
         let mut body = None;
         
+        if is_body {
+            declarations = self.parse_declarations()?;
+            if self.match_token(TokenType::Begin) {
+                // Package initialization section
+                body = Some(self.parse_statements()?);
+            }
+        }
+        
         // Parse declarations until we hit END or BODY
-        while !self.check(TokenType::End) && !self.check(TokenType::Body) && !self.is_at_end() {
+        while !is_body && !self.check(TokenType::End) && !self.check(TokenType::Body) && !self.is_at_end() {
             self.skip_whitespace();
             if self.check(TokenType::Procedure) || self.check(TokenType::Function) {
-                // For now, skip procedure/function declarations in package spec
-                self.advance();
-                self.skip_until_semicolon();
+                declarations.push(self.parse_subprogram_declaration(Vec::new())?);
             } else {
                 self.skip_until_semicolon();
             }
         }
         
-        if self.match_token(TokenType::Body) {
+        if !is_body && self.match_token(TokenType::Body) {
             // Parse package body (simplified)
             while !self.check(TokenType::End) && !self.is_at_end() {
                 self.skip_until_semicolon();
@@ -121,10 +288,11 @@ impl Parser {
         
         self.consume(TokenType::Semicolon, "Expected ';'")?;
         
-        let end_pos = self.previous().position;
+        let end_pos = self.previous().end_position();
         
         Ok(PlSqlNode::Package {
             name,
+            is_body,
             declarations,
             body,
             component: None, // Default no component
@@ -144,35 +312,201 @@ impl Parser {
         })
     }
     
-    fn parse_procedure(&mut self) -> Result<PlSqlNode> {
-        let start_pos = self.previous().position;
-        let name = self.consume_identifier("Expected procedure name")?;
+    /// Parse the declarations of a `PACKAGE BODY` or `DECLARE` section up to
+    /// the following `BEGIN` or `END`, keeping subprogram implementations
+    fn parse_declarations(&mut self) -> Result<Vec<PlSqlDeclaration>> {
+        let mut declarations = Vec::new();
+        while !self.check(TokenType::End) && !self.check(TokenType::Begin) && !self.is_at_end() {
+            if let Some(declaration) = self.parse_declaration()? {
+                declarations.push(declaration);
+            }
+        }
+        Ok(declarations)
+    }
+    
+    /// Parse one declaration, or skip a type or other declaration that the AST
+    /// does not model and return `None`
+    fn parse_declaration(&mut self) -> Result<Option<PlSqlDeclaration>> {
+        let annotations = self.parse_annotations();
+        let declaration = if self.check(TokenType::Procedure) || self.check(TokenType::Function) {
+            self.parse_subprogram_declaration(annotations)?
+        } else if !annotations.is_empty() {
+            return Err(ParseError::unexpected("PROCEDURE or FUNCTION after annotation", self.peek_non_whitespace()).into());
+        } else if self.check(TokenType::Cursor) {
+            self.parse_cursor_declaration()?
+        } else if self.check(TokenType::Identifier) && !self.check_keyword("pragma") && !self.check_keyword("subtype") {
+            self.parse_variable_declaration()?
+        } else {
+            // Types and other package-level declarations
+            self.skip_until_semicolon();
+            return Ok(None);
+        };
+        Ok(Some(declaration))
+    }
+    
+    /// Parse a procedure or function in a declaration section. A forward declaration,
+    /// whose signature ends in `;`, becomes a `SubprogramSpec`.
+    fn parse_subprogram_declaration(&mut self, annotations: Vec<IfsAnnotation>) -> Result<PlSqlDeclaration> {
+        let checkpoint = self.current;
+        let start = self.current_position();
+        let is_function = self.match_token(TokenType::Function);
+        if !is_function {
+            self.consume(TokenType::Procedure, "Expected 'PROCEDURE'")?;
+        }
+        let name = self.consume_identifier("Expected subprogram name")?;
+        let parameters = self.parse_parameter_list()?;
+        let return_type = if is_function {
+            self.consume(TokenType::Return, "Expected 'RETURN'")?;
+            let return_type = self.parse_return_type();
+            self.parse_function_clauses()?;
+            Some(return_type)
+        } else {
+            None
+        };
         
-        let parameters = Vec::new();
-        if self.match_token(TokenType::LeftParen) {
-            if !self.check(TokenType::RightParen) {
-                // Parse parameters (simplified)
-                loop {
-                    self.skip_until_comma_or_paren();
-                    if !self.match_token(TokenType::Comma) {
-                        break;
-                    }
-                }
+        if self.match_token(TokenType::Semicolon) {
+            return Ok(PlSqlDeclaration::SubprogramSpec {
+                name,
+                parameters,
+                return_type,
+                span: self.span_from(start),
+            });
+        }
+        
+        // An implementation: parse it again from the keyword
+        self.current = checkpoint;
+        let subprogram = if self.match_token(TokenType::Function) {
+            self.parse_function()?
+        } else {
+            self.consume(TokenType::Procedure, "Expected 'PROCEDURE'")?;
+            self.parse_procedure()?
+        };
+        Ok(PlSqlDeclaration::Subprogram(Box::new(subprogram.with_annotations(annotations))))
+    }
+    
+    /// Parse `CURSOR name [(parameters)] [RETURN type] IS query;`, noting a trailing
+    /// `FOR UPDATE [OF columns] [NOWAIT | WAIT n | SKIP LOCKED]` clause
+    fn parse_cursor_declaration(&mut self) -> Result<PlSqlDeclaration> {
+        let start = self.current_position();
+        self.consume(TokenType::Cursor, "Expected 'CURSOR'")?;
+        let name = self.consume_identifier("Expected cursor name")?;
+        while !self.check(TokenType::Is) && !self.check(TokenType::Semicolon) && !self.is_at_end() {
+            self.advance();
+        }
+        // A cursor specification in a package spec has no query
+        if self.match_token(TokenType::Semicolon) {
+            return Ok(PlSqlDeclaration::Cursor {
+                name,
+                query: String::new(),
+                for_update: false,
+                span: self.span_from(start),
+            });
+        }
+        self.consume(TokenType::Is, "Expected 'IS'")?;
+        
+        let start_index = self.current;
+        let mut depth = 0usize;
+        let mut for_update = false;
+        while !self.is_at_end() && (depth > 0 || !self.check(TokenType::Semicolon)) {
+            if depth == 0 && self.match_phrase(&["for", "update"]) {
+                for_update = true;
+                continue;
             }
-            self.consume(TokenType::RightParen, "Expected ')'")?;
+            match self.advance().token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        let query = self.source_text(start_index, self.current);
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        Ok(PlSqlDeclaration::Cursor {
+            name,
+            query,
+            for_update,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse `name [CONSTANT] type [NOT NULL] [:= | DEFAULT expression];`
+    /// or an exception declaration `name EXCEPTION;`
+    fn parse_variable_declaration(&mut self) -> Result<PlSqlDeclaration> {
+        let start = self.current_position();
+        let name = self.consume_identifier("Expected variable name")?;
+        
+        if self.match_token(TokenType::Exception) {
+            self.consume(TokenType::Semicolon, "Expected ';'")?;
+            return Ok(PlSqlDeclaration::Exception {
+                name,
+                span: self.span_from(start),
+            });
         }
         
-        if self.match_token(TokenType::Identifier) {
-            // Skip IS/AS
+        self.match_token(TokenType::Constant);
+        let type_name = self.parse_declared_type(&[TokenType::Semicolon]);
+        self.match_phrase(&["not", "null"]);
+        
+        let default_value = if self.match_token(TokenType::Assignment) || self.match_keyword("default") {
+            Some(self.parse_expression_until(&[TokenType::Semicolon]))
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        Ok(PlSqlDeclaration::Variable {
+            name,
+            type_name,
+            default_value,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse IFS annotations such as `@Override` preceding a subprogram
+    fn parse_annotations(&mut self) -> Vec<IfsAnnotation> {
+        let mut annotations = Vec::new();
+        loop {
+            if self.match_token(TokenType::Override) {
+                annotations.push(IfsAnnotation::Override);
+            } else if self.match_token(TokenType::Overtake) {
+                annotations.push(IfsAnnotation::Overtake);
+            } else if self.match_token(TokenType::UncheckedAccess) {
+                annotations.push(IfsAnnotation::UncheckedAccess);
+            } else {
+                return annotations;
+            }
+        }
+    }
+    
+    /// Parse the procedure or function an annotation applies to. Comments and
+    /// blank lines between them are skipped like any other trivia.
+    fn parse_annotated_subprogram(&mut self) -> Result<PlSqlNode> {
+        if self.match_token(TokenType::Procedure) {
+            self.parse_procedure()
+        } else if self.match_token(TokenType::Function) {
+            self.parse_function()
+        } else {
+            Err(ParseError::unexpected("PROCEDURE or FUNCTION after annotation", self.peek_non_whitespace()).into())
         }
+    }
+    
+    fn parse_procedure(&mut self) -> Result<PlSqlNode> {
+        let start_pos = self.previous().position;
+        let name = self.consume_identifier("Expected procedure name")?;
         
-        let body = Vec::new(); // Placeholder
-        let end_pos = self.current_position();
+        let parameters = self.parse_parameter_list()?;
+        
+        let SubprogramBody { pragmas, declarations, body, exception_handlers, end_label } = self.parse_subprogram_body()?;
+        let end_pos = self.previous().end_position();
         
         Ok(PlSqlNode::Procedure {
             name: name.clone(),
             parameters,
             body,
+            exception_handlers,
+            end_label,
+            pragmas,
+            declarations,
             visibility: self.determine_visibility(&name.name),
             annotations: Vec::new(),           // Default empty annotations
             span: Span {
@@ -194,23 +528,26 @@ impl Parser {
         let start_pos = self.previous().position;
         let name = self.consume_identifier("Expected function name")?;
         
-        let parameters = Vec::new(); // Placeholder
-        let return_type = Type {
-            name: "VARCHAR2".to_string(),
-            parameters: Vec::new(),
-            span: Span {
-                start: Position { line: 1, column: 1, offset: 0 },
-                end: Position { line: 1, column: 1, offset: 0 },
-            },
-        }; // Placeholder
-        let body = Vec::new(); // Placeholder
-        let end_pos = self.current_position();
+        let parameters = self.parse_parameter_list()?;
+        
+        self.consume(TokenType::Return, "Expected 'RETURN'")?;
+        let return_type = self.parse_return_type();
+        let (is_pipelined, result_cached) = self.parse_function_clauses()?;
+        
+        let SubprogramBody { pragmas, declarations, body, exception_handlers, end_label } = self.parse_subprogram_body()?;
+        let end_pos = self.previous().end_position();
         
         Ok(PlSqlNode::Function {
             name: name.clone(),
             parameters,
             return_type,
+            is_pipelined,
+            result_cached,
             body,
+            exception_handlers,
+            end_label,
+            pragmas,
+            declarations,
             visibility: self.determine_visibility(&name.name),
             annotations: Vec::new(),           // Default empty annotations
             span: Span {
@@ -228,330 +565,2834 @@ impl Parser {
         })
     }
     
-    #[allow(dead_code)]
-    fn parse_xml_entity(&mut self) -> Result<EntityNode> {
-        // Placeholder implementation
-        Ok(EntityNode {
-            entity_name: Identifier {
-                name: "placeholder".to_string(),
-                span: self.current_span(),
-            },
-            component: "".to_string(),
-            code_gen_properties: None,
-            attributes: Vec::new(),
-            keys: Vec::new(),
-            references: Vec::new(),
-            state_machine: None,
-            span: self.current_span(),
-        })
+    fn parse_parameter_list(&mut self) -> Result<Vec<Parameter>> {
+        let mut parameters = Vec::new();
+        if self.match_token(TokenType::LeftParen) {
+            if !self.check(TokenType::RightParen) {
+                loop {
+                    parameters.push(self.parse_parameter()?);
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenType::RightParen, "Expected ')'")?;
+        }
+        Ok(parameters)
     }
-
-    fn parse_entity(&mut self) -> Result<EntityNode> {
-        // Parse entity definition - placeholder implementation
-        Ok(EntityNode {
-            entity_name: Identifier {
-                name: "placeholder_entity".to_string(),
-                span: self.current_span(),
-            },
-            component: "".to_string(),
-            code_gen_properties: None,
-            attributes: Vec::new(),
-            keys: Vec::new(),
-            references: Vec::new(),
-            state_machine: None,
-            span: self.current_span(),
+    
+    /// Parse `name [IN] [OUT] [NOCOPY] type [DEFAULT | := expression]`
+    fn parse_parameter(&mut self) -> Result<Parameter> {
+        let start = self.current_position();
+        let name = self.consume_identifier("Expected parameter name")?;
+        
+        let mode = if self.match_phrase(&["in", "out"]) {
+            ParameterMode::InOut
+        } else if self.match_keyword("out") {
+            ParameterMode::Out
+        } else {
+            self.match_keyword("in");
+            ParameterMode::In
+        };
+        self.match_keyword("nocopy");
+        
+        let param_type = self.parse_declared_type(&[TokenType::Comma, TokenType::RightParen]);
+        
+        let default_value = if self.match_token(TokenType::Assignment) || self.match_keyword("default") {
+            Some(self.parse_expression_until(&[TokenType::Comma, TokenType::RightParen]))
+        } else {
+            None
+        };
+        
+        Ok(Parameter {
+            name,
+            param_type,
+            mode,
+            default_value,
+            span: self.span_from(start),
         })
     }
-
-    fn parse_enumeration(&mut self) -> Result<EnumerationNode> {
-        // Parse enumeration definition - placeholder implementation
-        Ok(EnumerationNode {
-            enumeration_name: Identifier {
-                name: "placeholder_enum".to_string(),
-                span: self.current_span(),
+    
+    /// Parse the type of a parameter or variable up to a default value or one of
+    /// `terminators`, e.g. `VARCHAR2(30)` or `customer_tab.id%TYPE`
+    fn parse_declared_type(&mut self, terminators: &[TokenType]) -> Type {
+        let start = self.current_position();
+        let mut name = String::new();
+        let mut parameters = Vec::new();
+        let mut depth = 0usize;
+        while !self.is_at_end()
+            && (depth > 0
+                || !(terminators.iter().any(|t| self.check(t.clone()))
+                    || self.check(TokenType::Assignment)
+                    || self.check_keyword("default")
+                    || self.check_phrase(&["not", "null"])))
+        {
+            if depth == 0 && parameters.is_empty() && !name.is_empty() && self.check(TokenType::LeftParen) {
+                parameters = self.parse_type_parameters();
+                continue;
+            }
+            let token = self.advance();
+            match token.token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth -= 1,
+                _ => {}
+            }
+            name.push_str(&token.value);
+        }
+        Type {
+            name,
+            parameters,
+            span: Span {
+                start,
+                end: self.last_position(),
             },
-            component: "".to_string(),
-            values: Vec::new(),
-            span: self.current_span(),
-        })
-    }
-
-    fn parse_views(&mut self) -> Result<ViewsNode> {
-        // Parse views definition - placeholder implementation
-        Ok(ViewsNode {
-            layer: None,
-            column_definitions: Vec::new(),
-            views: Vec::new(),
-            span: self.current_span(),
-        })
+        }
     }
-
-    fn parse_storage(&mut self) -> Result<StorageNode> {
-        // Parse storage definition - placeholder implementation
-        Ok(StorageNode {
-            layer: None,
-            definitions: Vec::new(),
-            span: self.current_span(),
-        })
+    
+    /// Parse the size or precision of a type, e.g. `(10, 2)` or `(30 CHAR)`,
+    /// into one entry per comma-separated parameter
+    fn parse_type_parameters(&mut self) -> Vec<String> {
+        self.advance(); // consume (
+        let mut parameters = Vec::new();
+        let mut words: Vec<String> = Vec::new();
+        while !self.is_at_end() {
+            let token = self.advance();
+            match token.token_type {
+                TokenType::RightParen => break,
+                TokenType::Comma => parameters.push(std::mem::take(&mut words).join(" ")),
+                _ => words.push(token.value.clone()),
+            }
+        }
+        if !words.is_empty() {
+            parameters.push(words.join(" "));
+        }
+        parameters
     }
     
-    #[allow(dead_code)]
-    fn parse_xml_enumeration(&mut self) -> Result<EnumerationNode> {
-        // Placeholder implementation - this method is deprecated
-        // Use parse_enumeration instead
-        Ok(EnumerationNode {
-            enumeration_name: Identifier {
-                name: "placeholder_enum".to_string(),
-                span: self.current_span(),
+    /// Parse a function return type, e.g. `VARCHAR2` or `customer_tab.id%TYPE`
+    fn parse_return_type(&mut self) -> Type {
+        let start = self.current_position();
+        let mut name = String::new();
+        while !self.check(TokenType::Is)
+            && !self.check(TokenType::As)
+            && !self.check(TokenType::Semicolon)
+            && !FUNCTION_CLAUSE_KEYWORDS.iter().any(|keyword| self.check_keyword(keyword))
+            && !self.is_at_end()
+        {
+            name.push_str(&self.advance().value);
+        }
+        Type {
+            name,
+            parameters: Vec::new(),
+            span: Span {
+                start,
+                end: self.current_position(),
             },
-            component: "".to_string(),
-            values: Vec::new(),
-            span: self.current_span(),
-        })
+        }
     }
     
-    #[allow(dead_code)]
-    fn parse_sql_view(&mut self) -> Result<ViewsNode> {
-        // Placeholder implementation - this method is deprecated
-        // Use parse_views instead
-        Ok(ViewsNode {
-            layer: None,
-            column_definitions: Vec::new(),
-            views: Vec::new(),
-            span: self.current_span(),
-        })
+    /// Parse the clauses between a function's return type and its body.
+    /// Returns whether the function is pipelined and whether it is result cached.
+    fn parse_function_clauses(&mut self) -> Result<(bool, bool)> {
+        let mut is_pipelined = false;
+        let mut result_cached = false;
+        loop {
+            if self.match_keyword("pipelined") {
+                is_pipelined = true;
+            } else if self.match_keyword("result_cache") {
+                result_cached = true;
+                if self.match_keyword("relies_on") {
+                    self.skip_parenthesized()?;
+                }
+            } else if self.match_keyword("parallel_enable") {
+                if self.check(TokenType::LeftParen) {
+                    self.skip_parenthesized()?;
+                }
+            } else if !self.match_keyword("deterministic") {
+                return Ok((is_pipelined, result_cached));
+            }
+        }
     }
     
-    fn parse_marble_projection(&mut self) -> Result<MarbleProjectionNode> {
-        // Placeholder implementation
-        Ok(MarbleProjectionNode {
-            name: Identifier {
-                name: "placeholder".to_string(),
-                span: self.current_span(),
+    /// Skip a balanced `( ... )` group
+    fn skip_parenthesized(&mut self) -> Result<()> {
+        self.consume(TokenType::LeftParen, "Expected '('")?;
+        let mut depth = 1usize;
+        while depth > 0 {
+            if self.is_at_end() {
+                return Err(ParseError::UnexpectedEof.into());
+            }
+            match self.advance().token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    
+    /// Parse the `IS ... BEGIN ... END [name];` part of a subprogram.
+    /// A bare `;` is a forward declaration without a body.
+    fn parse_subprogram_body(&mut self) -> Result<SubprogramBody> {
+        if self.match_token(TokenType::Semicolon) {
+            return Ok(SubprogramBody::default());
+        }
+        
+        self.expect_one_of(&[TokenType::Is, TokenType::As], "before subprogram body")?;
+        
+        let (declarations, pragmas) = self.parse_subprogram_declarations()?;
+        self.consume(TokenType::Begin, "Expected 'BEGIN'")?;
+        
+        if !self.options.bodies {
+            self.skip_through_end(1)?;
+            let end_label = self.check(TokenType::Identifier).then(|| token_identifier(self.advance()));
+            self.consume(TokenType::Semicolon, "Expected ';'")?;
+            return Ok(SubprogramBody { pragmas, declarations, end_label, ..SubprogramBody::default() });
+        }
+        
+        let body = self.parse_statements()?;
+        let exception_handlers = self.parse_exception_handlers()?;
+        let end_label = self.consume_block_end()?;
+        
+        Ok(SubprogramBody { pragmas, declarations, body, exception_handlers, end_label })
+    }
+    
+    /// Parse a subprogram's declaration section up to BEGIN, keeping local
+    /// declarations and nested subprograms apart from the pragmas
+    fn parse_subprogram_declarations(&mut self) -> Result<(Vec<PlSqlDeclaration>, Vec<Pragma>)> {
+        let mut declarations = Vec::new();
+        let mut pragmas = Vec::new();
+        while !self.check(TokenType::Begin) && !self.is_at_end() {
+            if self.check_keyword("pragma") {
+                pragmas.push(self.parse_pragma()?);
+            } else if let Some(declaration) = self.parse_declaration()? {
+                declarations.push(declaration);
+            }
+        }
+        Ok((declarations, pragmas))
+    }
+    
+    /// Parse `PRAGMA name[(arguments)];`
+    fn parse_pragma(&mut self) -> Result<Pragma> {
+        let start = self.current_position();
+        self.advance();
+        let name = self.consume_name("Expected pragma name")?;
+        let arguments = self.parse_arguments()?;
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        Ok(Pragma {
+            name,
+            arguments,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Consume `END [label];`, returning the label
+    fn consume_block_end(&mut self) -> Result<Option<Identifier>> {
+        self.consume(TokenType::End, "Expected 'END'")?;
+        let label = self.check(TokenType::Identifier).then(|| token_identifier(self.advance()));
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        Ok(label)
+    }
+    
+    /// Parse statements until the end of the enclosing block, branch or handler
+    fn parse_statements(&mut self) -> Result<Vec<PlSqlStatement>> {
+        let mut statements = Vec::new();
+        while !self.is_at_end() && !self.at_statement_list_end() {
+            if let Some(statement) = self.parse_statement()? {
+                statements.push(statement);
+            }
+        }
+        Ok(statements)
+    }
+    
+    fn at_statement_list_end(&self) -> bool {
+        matches!(
+            self.peek_non_whitespace().token_type,
+            TokenType::End | TokenType::Exception | TokenType::Else | TokenType::ElseIf
+        ) || self.check_keyword("when")
+    }
+    
+    /// Parse a single statement. Statements without an AST representation yield `None`.
+    fn parse_statement(&mut self) -> Result<Option<PlSqlStatement>> {
+        if !self.options.preserve_trivia {
+            return self.parse_statement_inner();
+        }
+        
+        let (_, leading) = self.pending_trivia();
+        let mut statement = self.parse_statement_inner()?;
+        if let Some(statement) = statement.as_mut() {
+            let (trailing, _) = self.pending_trivia();
+            let trivia = Trivia { leading, trailing };
+            if !trivia.is_empty() {
+                *statement.trivia_mut() = Some(trivia);
+            }
+        }
+        Ok(statement)
+    }
+    
+    fn parse_statement_inner(&mut self) -> Result<Option<PlSqlStatement>> {
+        let start = self.current_position();
+        let token_type = self.peek_non_whitespace().token_type.clone();
+        
+        let statement = match token_type {
+            TokenType::Begin => {
+                self.advance();
+                Some(self.parse_block(start, Vec::new())?)
+            }
+            TokenType::Declare => {
+                self.advance();
+                let declarations = self.parse_declarations()?;
+                self.consume(TokenType::Begin, "Expected 'BEGIN'")?;
+                Some(self.parse_block(start, declarations)?)
+            }
+            TokenType::If => {
+                let conditional = self.advance().value.starts_with('$');
+                Some(self.parse_if(start, conditional)?)
+            }
+            TokenType::Loop | TokenType::While | TokenType::For => Some(self.parse_loop(start)?),
+            TokenType::Return => {
+                self.advance();
+                let value = if self.check(TokenType::Semicolon) {
+                    None
+                } else {
+                    Some(self.parse_expression_until(&[TokenType::Semicolon]))
+                };
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+                Some(PlSqlStatement::Return {
+                    value,
+                    span: self.span_from(start),
+                    trivia: None,
+                })
+            }
+            TokenType::Raise => {
+                self.advance();
+                let exception = if self.check(TokenType::Semicolon) {
+                    None
+                } else {
+                    Some(self.parse_qualified_name()?)
+                };
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+                Some(PlSqlStatement::Raise {
+                    exception,
+                    span: self.span_from(start),
+                    trivia: None,
+                })
+            }
+            TokenType::Select => self.parse_select_into(start)?,
+            TokenType::Identifier | TokenType::Substitution => {
+                self.parse_identifier_statement(start)?
+            }
+            TokenType::Super => {
+                let name = self.parse_super_name();
+                let arguments = self.parse_arguments()?;
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+                Some(PlSqlStatement::Call {
+                    name,
+                    arguments,
+                    span: self.span_from(start),
+                    trivia: None,
+                })
+            }
+            TokenType::Goto => {
+                self.advance();
+                let label = self.consume_identifier("Expected label name")?;
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+                Some(PlSqlStatement::Goto {
+                    label,
+                    span: self.span_from(start),
+                    trivia: None,
+                })
+            }
+            TokenType::LessThan => {
+                // Statement label such as <<retry>>
+                while self.match_token(TokenType::LessThan) {}
+                let name = self.consume_identifier("Expected label name")?;
+                while !self.check(TokenType::GreaterThan) && !self.is_at_end() {
+                    self.advance();
+                }
+                self.match_token(TokenType::GreaterThan);
+                self.match_token(TokenType::GreaterThan);
+                Some(PlSqlStatement::Label {
+                    name,
+                    span: self.span_from(start),
+                    trivia: None,
+                })
+            }
+            _ => {
+                self.skip_until_semicolon();
+                None
+            }
+        };
+        
+        Ok(statement)
+    }
+    
+    /// Parse the rest of a `BEGIN ... [EXCEPTION ...] END;` block
+    fn parse_block(&mut self, start: Position, declarations: Vec<PlSqlDeclaration>) -> Result<PlSqlStatement> {
+        let body = self.parse_statements()?;
+        let exception_handlers = self.parse_exception_handlers()?;
+        self.consume_block_end()?;
+        
+        Ok(PlSqlStatement::Block {
+            declarations,
+            body,
+            exception_handlers,
+            span: self.span_from(start),
+            trivia: None,
+        })
+    }
+    
+    /// Parse an optional `EXCEPTION WHEN ... THEN ...` section
+    fn parse_exception_handlers(&mut self) -> Result<Vec<ExceptionHandler>> {
+        let mut handlers = Vec::new();
+        if !self.match_token(TokenType::Exception) {
+            return Ok(handlers);
+        }
+        
+        loop {
+            let start = self.current_position();
+            if !self.match_keyword("when") {
+                break;
+            }
+            
+            let mut exceptions = vec![self.parse_qualified_name()?];
+            while self.match_keyword("or") {
+                exceptions.push(self.parse_qualified_name()?);
+            }
+            self.consume(TokenType::Then, "Expected 'THEN'")?;
+            
+            let body = self.parse_statements()?;
+            handlers.push(ExceptionHandler {
+                exceptions,
+                body,
+                span: self.span_from(start),
+            });
+        }
+        
+        Ok(handlers)
+    }
+    
+    /// Parse the rest of an IF statement. Conditional compilation blocks
+    /// (`$IF ... $THEN ... $ELSE ... $END`) lex to the same tokens and are
+    /// kept as IF statements, ending at `$END` instead of `END IF;`.
+    fn parse_if(&mut self, start: Position, conditional: bool) -> Result<PlSqlStatement> {
+        let mut statement = self.parse_if_branches(start.clone())?;
+        if conditional {
+            self.consume(TokenType::End, "Expected '$END'")?;
+        } else {
+            self.consume(TokenType::End, "Expected 'END'")?;
+            self.consume(TokenType::If, "Expected 'IF'")?;
+            self.consume(TokenType::Semicolon, "Expected ';'")?;
+        }
+        
+        if let PlSqlStatement::If { span, .. } = &mut statement {
+            *span = self.span_from(start);
+        }
+        Ok(statement)
+    }
+    
+    /// Parse a condition and its branches; ELSIF chains become nested IFs in the else branch
+    fn parse_if_branches(&mut self, start: Position) -> Result<PlSqlStatement> {
+        let condition = self.parse_expression_until(&[TokenType::Then]);
+        self.consume(TokenType::Then, "Expected 'THEN'")?;
+        let then_branch = self.parse_statements()?;
+        
+        let else_branch = if self.check(TokenType::ElseIf) {
+            let elsif_start = self.current_position();
+            self.advance();
+            Some(vec![self.parse_if_branches(elsif_start)?])
+        } else if self.match_token(TokenType::Else) {
+            Some(self.parse_statements()?)
+        } else {
+            None
+        };
+        
+        Ok(PlSqlStatement::If {
+            condition,
+            then_branch,
+            else_branch,
+            span: self.span_from(start),
+            trivia: None,
+        })
+    }
+    
+    /// Parse `[WHILE ... | FOR ...] LOOP ... END LOOP [label];`
+    fn parse_loop(&mut self, start: Position) -> Result<PlSqlStatement> {
+        while !self.check(TokenType::Loop) && !self.is_at_end() {
+            self.advance();
+        }
+        self.consume(TokenType::Loop, "Expected 'LOOP'")?;
+        
+        let body = self.parse_statements()?;
+        self.consume(TokenType::End, "Expected 'END'")?;
+        self.consume(TokenType::Loop, "Expected 'LOOP'")?;
+        if self.check(TokenType::Identifier) {
+            self.advance();
+        }
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        Ok(PlSqlStatement::Loop {
+            body,
+            span: self.span_from(start),
+            trivia: None,
+        })
+    }
+    
+    /// Parse `SELECT ... INTO target[, target] FROM ...;`
+    fn parse_select_into(&mut self, start: Position) -> Result<Option<PlSqlStatement>> {
+        let start_index = self.current;
+        let mut into = Vec::new();
+        let mut depth = 0usize;
+        
+        while !self.is_at_end() && (depth > 0 || !self.check(TokenType::Semicolon)) {
+            if depth == 0 && self.match_keyword("into") {
+                into.push(self.parse_qualified_name()?);
+                while self.match_token(TokenType::Comma) {
+                    into.push(self.parse_qualified_name()?);
+                }
+                continue;
+            }
+            match self.advance().token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        
+        let query = self.source_text(start_index, self.current);
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        if into.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(PlSqlStatement::SelectInto {
+            into,
+            query,
+            span: self.span_from(start),
+            trivia: None,
+        }))
+    }
+    
+    /// Parse a statement starting with an identifier: DML, assignment or call
+    fn parse_identifier_statement(&mut self, start: Position) -> Result<Option<PlSqlStatement>> {
+        if let Some(kind) = self.peek_dml_kind() {
+            return Ok(Some(self.parse_dml(kind, start)?));
+        }
+        
+        if self.match_keyword("null") {
+            self.consume(TokenType::Semicolon, "Expected ';'")?;
+            return Ok(None);
+        }
+        
+        if self.check_keyword("case") {
+            self.skip_case_statement()?;
+            self.consume(TokenType::Semicolon, "Expected ';'")?;
+            return Ok(None);
+        }
+        
+        if self.check_keyword("fetch") {
+            return Ok(Some(self.parse_fetch(start)?));
+        }
+        
+        if UNMODELLED_STATEMENT_KEYWORDS
+            .iter()
+            .any(|keyword| self.check_keyword(keyword))
+        {
+            self.skip_until_semicolon();
+            return Ok(None);
+        }
+        
+        let name = self.parse_qualified_name()?;
+        
+        if self.check(TokenType::LeftParen) || self.check(TokenType::Semicolon) {
+            let arguments = self.parse_arguments()?;
+            if self.match_token(TokenType::Semicolon) {
+                return Ok(Some(PlSqlStatement::Call {
+                    name,
+                    arguments,
+                    span: self.span_from(start),
+                    trivia: None,
+                }));
+            }
+        }
+        
+        if self.match_token(TokenType::Assignment) {
+            let value = self.parse_expression_until(&[TokenType::Semicolon]);
+            self.consume(TokenType::Semicolon, "Expected ';'")?;
+            return Ok(Some(PlSqlStatement::Assignment {
+                target: name,
+                value,
+                span: self.span_from(start),
+                trivia: None,
+            }));
+        }
+        
+        self.skip_until_semicolon();
+        Ok(None)
+    }
+    
+    /// Parse `FETCH cursor [BULK COLLECT] INTO target[, target] [LIMIT rows];`
+    fn parse_fetch(&mut self, start: Position) -> Result<PlSqlStatement> {
+        self.advance(); // FETCH
+        let cursor = self.parse_qualified_name()?;
+        let bulk_collect = self.match_phrase(&["bulk", "collect"]);
+        if !self.match_keyword("into") {
+            return Err(ParseError::unexpected("INTO", self.peek_non_whitespace()).into());
+        }
+        
+        let mut into = vec![self.parse_qualified_name()?];
+        while self.match_token(TokenType::Comma) {
+            into.push(self.parse_qualified_name()?);
+        }
+        let limit = if self.match_keyword("limit") {
+            Some(self.parse_expression_until(&[TokenType::Semicolon]))
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        Ok(PlSqlStatement::Fetch {
+            cursor,
+            into,
+            bulk_collect,
+            limit,
+            span: self.span_from(start),
+            trivia: None,
+        })
+    }
+    
+    fn peek_dml_kind(&self) -> Option<DmlKind> {
+        if self.check_keyword("insert") {
+            Some(DmlKind::Insert)
+        } else if self.check_keyword("update") {
+            Some(DmlKind::Update)
+        } else if self.check_keyword("delete") {
+            Some(DmlKind::Delete)
+        } else if self.check_keyword("merge") {
+            Some(DmlKind::Merge)
+        } else {
+            None
+        }
+    }
+    
+    /// Parse `INSERT INTO`, `UPDATE`, `DELETE [FROM]` or `MERGE INTO` up to its `;`
+    fn parse_dml(&mut self, kind: DmlKind, start: Position) -> Result<PlSqlStatement> {
+        let start_index = self.current;
+        self.advance();
+        
+        match kind {
+            DmlKind::Insert | DmlKind::Merge => {
+                if !self.match_keyword("into") {
+                    return Err(ParseError::unexpected("INTO", self.peek_non_whitespace()).into());
+                }
+            }
+            DmlKind::Delete => {
+                self.match_token(TokenType::From);
+            }
+            DmlKind::Update => {}
+        }
+        
+        let target_table = self.parse_qualified_name()?;
+        let mut returning = None;
+        let mut depth = 0usize;
+        while !self.check(TokenType::Semicolon) && !self.is_at_end() {
+            if depth == 0 && (self.check_keyword("returning") || self.check(TokenType::Return)) {
+                returning = Some(self.parse_returning_clause()?);
+                break;
+            }
+            match self.advance().token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        
+        let raw = self.source_text(start_index, self.current);
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        Ok(PlSqlStatement::Dml {
+            kind,
+            target_table,
+            raw,
+            returning,
+            span: self.span_from(start),
+            trivia: None,
+        })
+    }
+    
+    /// Parse `RETURNING|RETURN column[, ...] [BULK COLLECT] INTO target[, ...]`
+    fn parse_returning_clause(&mut self) -> Result<ReturningClause> {
+        let start = self.current_position();
+        self.advance();
+        
+        let mut columns = vec![self.parse_expression()?];
+        while self.match_token(TokenType::Comma) {
+            columns.push(self.parse_expression()?);
+        }
+        
+        let bulk_collect = self.match_keyword("bulk");
+        if bulk_collect && !self.match_keyword("collect") {
+            return Err(ParseError::unexpected("COLLECT", self.peek_non_whitespace()).into());
+        }
+        if !self.match_keyword("into") {
+            return Err(ParseError::unexpected("INTO", self.peek_non_whitespace()).into());
+        }
+        
+        let mut into = vec![self.parse_qualified_name()?];
+        while self.match_token(TokenType::Comma) {
+            into.push(self.parse_qualified_name()?);
+        }
+        
+        Ok(ReturningClause {
+            columns,
+            into,
+            bulk_collect,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Skip a CASE statement through its matching `END CASE`, including nested blocks
+    fn skip_case_statement(&mut self) -> Result<()> {
+        self.skip_through_end(0)
+    }
+    
+    /// Skip tokens through the END closing `depth` already open levels.
+    /// Nested BEGIN and CASE open further levels.
+    fn skip_through_end(&mut self, mut depth: usize) -> Result<()> {
+        while !self.is_at_end() {
+            if self.match_token(TokenType::Begin) || self.match_keyword("case") {
+                depth += 1;
+            } else if self.match_token(TokenType::End) {
+                // END IF and END LOOP close constructs that never opened a level
+                if self.match_token(TokenType::If) || self.match_token(TokenType::Loop) {
+                    continue;
+                }
+                self.match_keyword("case");
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Ok(());
+                }
+            } else {
+                self.advance();
+            }
+        }
+        Err(ParseError::UnexpectedEof.into())
+    }
+    
+    /// Parse an optional parenthesized argument list
+    fn parse_arguments(&mut self) -> Result<Vec<Expression>> {
+        let mut arguments = Vec::new();
+        if !self.match_token(TokenType::LeftParen) {
+            return Ok(arguments);
+        }
+        
+        if !self.check(TokenType::RightParen) {
+            loop {
+                arguments.push(self.parse_expression_until(&[TokenType::Comma, TokenType::RightParen]));
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expected ')'")?;
+        
+        Ok(arguments)
+    }
+    
+    /// Parse a dotted name such as `Customer_API.Get_Name` into a single identifier
+    fn parse_qualified_name(&mut self) -> Result<Identifier> {
+        let mut identifier = self.consume_identifier("Expected identifier")?;
+        while self.check(TokenType::Dot) {
+            self.advance();
+            let part = self.consume_identifier("Expected identifier after '.'")?;
+            identifier.name = format!("{}.{}", identifier.name, part.name);
+            identifier.span.end = part.span.end;
+        }
+        Ok(identifier)
+    }
+    
+    /// Parse an expression that must be followed by one of `terminators`.
+    /// Syntax the expression parser does not understand yet is kept as raw text.
+    fn parse_expression_until(&mut self, terminators: &[TokenType]) -> Expression {
+        let checkpoint = self.current;
+        let start = self.current_position();
+        
+        match self.parse_expression() {
+            Ok(expression) if terminators.iter().any(|t| self.check(t.clone())) => expression,
+            _ => {
+                self.current = checkpoint;
+                self.raw_expression_until(terminators, start)
+            }
+        }
+    }
+    
+    fn raw_expression_until(&mut self, terminators: &[TokenType], start: Position) -> Expression {
+        let start_index = self.current;
+        let mut depth = 0usize;
+        
+        while !self.is_at_end() {
+            let token_type = self.peek_non_whitespace().token_type.clone();
+            if depth == 0 && (terminators.contains(&token_type) || token_type == TokenType::Semicolon) {
+                break;
+            }
+            match token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen if depth == 0 => break,
+                TokenType::RightParen => depth -= 1,
+                _ => {}
+            }
+            self.advance();
+        }
+        
+        Expression::Literal {
+            value: self.source_text(start_index, self.current),
+            span: self.span_from(start),
+        }
+    }
+    
+    fn parse_expression(&mut self) -> Result<Expression> {
+        self.parse_binary(0)
+    }
+    
+    /// Precedence-climbing parser for binary operators
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expression> {
+        let start = self.current_position();
+        let mut left = self.parse_unary()?;
+        
+        loop {
+            // IS [NOT] NULL binds like a comparison
+            if min_precedence <= BinaryOperator::COMPARISON_PRECEDENCE {
+                let null_test = if self.match_phrase(&["is", "not", "null"]) {
+                    Some(UnaryOperator::IsNotNull)
+                } else if self.match_phrase(&["is", "null"]) {
+                    Some(UnaryOperator::IsNull)
+                } else {
+                    None
+                };
+                if let Some(operator) = null_test {
+                    left = Expression::Unary {
+                        operator,
+                        operand: Box::new(left),
+                        span: self.span_from(start.clone()),
+                    };
+                    continue;
+                }
+            }
+            
+            let Some(operator) = self.peek_binary_operator() else {
+                break;
+            };
+            let precedence = operator.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            self.advance();
+            
+            let right = if operator == BinaryOperator::In {
+                self.parse_in_list()?
+            } else {
+                self.parse_binary(precedence + 1)?
+            };
+            left = Expression::Binary {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+                span: self.span_from(start.clone()),
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    fn parse_unary(&mut self) -> Result<Expression> {
+        let start = self.current_position();
+        let operator = if self.match_keyword("not") {
+            UnaryOperator::Not
+        } else if self.match_token(TokenType::Minus) {
+            UnaryOperator::Minus
+        } else if self.match_token(TokenType::Plus) {
+            UnaryOperator::Plus
+        } else {
+            return self.parse_primary();
+        };
+        
+        // NOT binds looser than comparisons, sign operators bind to a single operand
+        let operand = if operator == UnaryOperator::Not {
+            self.parse_binary(3)?
+        } else {
+            self.parse_unary()?
+        };
+        
+        Ok(Expression::Unary {
+            operator,
+            operand: Box::new(operand),
+            span: self.span_from(start),
+        })
+    }
+    
+    fn parse_primary(&mut self) -> Result<Expression> {
+        let start = self.current_position();
+        let token_type = self.peek_non_whitespace().token_type.clone();
+        
+        match token_type {
+            TokenType::Number | TokenType::String => {
+                let value = self.advance().value.clone();
+                Ok(Expression::Literal {
+                    value,
+                    span: self.span_from(start),
+                })
+            }
+            TokenType::LeftParen => {
+                self.advance();
+                let expression = self.parse_expression()?;
+                self.consume(TokenType::RightParen, "Expected ')'")?;
+                Ok(expression)
+            }
+            TokenType::True | TokenType::False | TokenType::InquiryDirective => {
+                let value = self.advance().value.to_uppercase();
+                Ok(Expression::Literal {
+                    value,
+                    span: self.span_from(start),
+                })
+            }
+            TokenType::Identifier if self.check_keyword("case") => self.parse_case_expression(start),
+            TokenType::Super => {
+                let name = self.parse_super_name();
+                let arguments = self.parse_arguments()?;
+                Ok(Expression::FunctionCall {
+                    name,
+                    arguments,
+                    span: self.span_from(start),
+                })
+            }
+            TokenType::Identifier | TokenType::Substitution => {
+                if self.check_keyword("null") {
+                    let value = self.advance().value.to_uppercase();
+                    return Ok(Expression::Literal {
+                        value,
+                        span: self.span_from(start),
+                    });
+                }
+                
+                let name = self.parse_qualified_name()?;
+                if self.check(TokenType::LeftParen) {
+                    let arguments = self.parse_arguments()?;
+                    Ok(Expression::FunctionCall {
+                        name,
+                        arguments,
+                        span: self.span_from(start),
+                    })
+                } else {
+                    Ok(Expression::Identifier(name))
+                }
+            }
+            _ => Err(ParseError::unexpected("expression", self.peek_non_whitespace()).into()),
+        }
+    }
+    
+    /// Consume the `super` keyword, naming the call with the synthetic [`Identifier::SUPER`]
+    fn parse_super_name(&mut self) -> Identifier {
+        let token = token_identifier(self.advance());
+        Identifier {
+            name: Identifier::SUPER.to_string(),
+            span: token.span,
+        }
+    }
+    
+    /// Parse a simple or searched CASE expression through its END
+    fn parse_case_expression(&mut self, start: Position) -> Result<Expression> {
+        self.advance();
+        let operand = if self.check_keyword("when") {
+            None
+        } else {
+            Some(Box::new(self.parse_expression()?))
+        };
+        
+        let mut branches = Vec::new();
+        while self.match_keyword("when") {
+            let condition = self.parse_expression()?;
+            self.consume(TokenType::Then, "Expected 'THEN'")?;
+            branches.push((condition, self.parse_expression()?));
+        }
+        if branches.is_empty() {
+            return Err(ParseError::unexpected("WHEN", self.peek_non_whitespace()).into());
+        }
+        
+        let default = if self.match_token(TokenType::Else) {
+            Some(Box::new(self.parse_expression()?))
+        } else {
+            None
+        };
+        self.consume(TokenType::End, "Expected 'END'")?;
+        
+        Ok(Expression::Case {
+            operand,
+            branches,
+            default,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse the parenthesized list after IN, kept as raw text
+    fn parse_in_list(&mut self) -> Result<Expression> {
+        let start = self.current_position();
+        let start_index = self.current;
+        self.consume(TokenType::LeftParen, "Expected '('")?;
+        
+        let mut depth = 1usize;
+        while depth > 0 && !self.is_at_end() {
+            match self.advance().token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth -= 1,
+                _ => {}
+            }
+        }
+        
+        Ok(Expression::Literal {
+            value: self.source_text(start_index, self.current),
+            span: self.span_from(start),
+        })
+    }
+    
+    fn peek_binary_operator(&self) -> Option<BinaryOperator> {
+        let token = self.peek_non_whitespace();
+        let operator = match token.token_type {
+            TokenType::Plus => BinaryOperator::Add,
+            TokenType::Minus => BinaryOperator::Subtract,
+            TokenType::Multiply => BinaryOperator::Multiply,
+            TokenType::Divide => BinaryOperator::Divide,
+            TokenType::Equal => BinaryOperator::Equal,
+            TokenType::NotEqual => BinaryOperator::NotEqual,
+            TokenType::LessThan => BinaryOperator::LessThan,
+            TokenType::LessThanOrEqual => BinaryOperator::LessThanOrEqual,
+            TokenType::GreaterThan => BinaryOperator::GreaterThan,
+            TokenType::GreaterThanOrEqual => BinaryOperator::GreaterThanOrEqual,
+            TokenType::Identifier => match token.value.to_lowercase().as_str() {
+                "and" => BinaryOperator::And,
+                "or" => BinaryOperator::Or,
+                "like" => BinaryOperator::Like,
+                "in" => BinaryOperator::In,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(operator)
+    }
+    
+    #[allow(dead_code)]
+    fn parse_xml_entity(&mut self) -> Result<EntityNode> {
+        // Placeholder implementation
+        Ok(EntityNode {
+            entity_name: Identifier {
+                name: "placeholder".to_string(),
+                span: self.current_span(),
+            },
+            component: "".to_string(),
+            code_gen_properties: None,
+            attributes: Vec::new(),
+            keys: Vec::new(),
+            references: Vec::new(),
+            state_machine: None,
+            span: self.current_span(),
+        })
+    }
+
+    /// Parse `entityname Name; component X; codegenproperties { ... } statemachine { ... }`.
+    /// Attribute, key and reference sections are not modelled yet and are skipped.
+    fn parse_entity(&mut self) -> Result<EntityNode> {
+        let start = self.current_position();
+        self.consume(TokenType::EntityName, "Expected 'entityname'")?;
+        let entity_name = self.consume_name("Expected entity name")?;
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        let mut component = String::new();
+        let mut code_gen_properties = None;
+        let mut state_machine = None;
+        while !self.is_at_end() {
+            if self.match_token(TokenType::Component) {
+                component = self.consume_name("Expected component name")?.name;
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+            } else if self.check(TokenType::CodeGenProperties) {
+                code_gen_properties = Some(self.parse_code_gen_properties()?);
+            } else if self.check_keyword("statemachine") {
+                state_machine = Some(self.parse_state_machine()?);
+            } else {
+                self.skip_entity_section()?;
+            }
+        }
+        
+        Ok(EntityNode {
+            entity_name,
+            component,
+            code_gen_properties,
+            attributes: Vec::new(),
+            keys: Vec::new(),
+            references: Vec::new(),
+            state_machine,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse `statemachine { [initial | final] state Name (; | { transitions }) ... }`
+    fn parse_state_machine(&mut self) -> Result<StateMachine> {
+        let start = self.current_position();
+        self.advance();
+        self.consume(TokenType::LeftBrace, "Expected '{'")?;
+        
+        let mut states = Vec::new();
+        let mut transitions = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let state_start = self.current_position();
+            let state_type = if self.match_keyword("initial") {
+                StateType::Initial
+            } else if self.match_keyword("final") {
+                StateType::Final
+            } else {
+                StateType::Normal
+            };
+            if !self.match_keyword("state") {
+                return Err(ParseError::unexpected("state", self.peek_non_whitespace()).into());
+            }
+            let name = self.consume_name("Expected state name")?;
+            
+            if self.match_token(TokenType::LeftBrace) {
+                while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+                    transitions.push(self.parse_state_transition(&name)?);
+                }
+                self.consume(TokenType::RightBrace, "Expected '}'")?;
+            } else {
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+            }
+            
+            states.push(State {
+                name,
+                state_type,
+                span: self.span_from(state_start),
+            });
+        }
+        self.consume(TokenType::RightBrace, "Expected '}'")?;
+        
+        Ok(StateMachine {
+            states,
+            transitions,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse `[event Name] to Target;` inside the block of state `from_state`
+    fn parse_state_transition(&mut self, from_state: &Identifier) -> Result<StateTransition> {
+        let start = self.current_position();
+        let event = if self.match_keyword("event") {
+            Some(self.consume_name("Expected event name")?.name)
+        } else {
+            None
+        };
+        if !self.match_keyword("to") {
+            return Err(ParseError::unexpected("to", self.peek_non_whitespace()).into());
+        }
+        let to_state = self.consume_name("Expected target state")?;
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        Ok(StateTransition {
+            from_state: from_state.clone(),
+            to_state,
+            event,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse `codegenproperties { Key "value"; ... }`. Values that are not a
+    /// single string literal keep their source text.
+    fn parse_code_gen_properties(&mut self) -> Result<CodeGenProperties> {
+        let start = self.current_position();
+        self.consume(TokenType::CodeGenProperties, "Expected 'codegenproperties'")?;
+        self.consume(TokenType::LeftBrace, "Expected '{'")?;
+        
+        let mut properties = HashMap::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let key = self.consume_name("Expected property name")?.name;
+            self.match_token(TokenType::Equal);
+            let value_start = self.current;
+            while !self.check(TokenType::Semicolon) && !self.check(TokenType::RightBrace) && !self.is_at_end() {
+                self.advance();
+            }
+            let value = unquote(&self.source_text(value_start, self.current)).to_string();
+            self.match_token(TokenType::Semicolon);
+            properties.insert(key, value);
+        }
+        self.consume(TokenType::RightBrace, "Expected '}'")?;
+        
+        Ok(CodeGenProperties {
+            properties,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Skip an entity header line up to `;`, or a section up to its closing `}`
+    fn skip_entity_section(&mut self) -> Result<()> {
+        while !self.check(TokenType::Semicolon) && !self.check(TokenType::LeftBrace) {
+            if self.is_at_end() {
+                return Ok(());
+            }
+            self.advance();
+        }
+        if self.match_token(TokenType::Semicolon) {
+            return Ok(());
+        }
+        
+        self.advance();
+        let mut depth = 1usize;
+        while depth > 0 {
+            if self.is_at_end() {
+                return Err(ParseError::UnexpectedEof.into());
+            }
+            match self.advance().token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse `enumerationname Name; component X; ... values { value Name { ... } ... }`
+    fn parse_enumeration(&mut self) -> Result<EnumerationNode> {
+        let start = self.current_position();
+        self.consume(TokenType::EnumerationName, "Expected 'enumerationname'")?;
+        let enumeration_name = self.consume_name("Expected enumeration name")?;
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        
+        let mut component = String::new();
+        let mut values = Vec::new();
+        while !self.is_at_end() {
+            if self.match_token(TokenType::Component) {
+                component = self.consume_name("Expected component name")?.name;
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+            } else if self.match_token(TokenType::Values) {
+                self.consume(TokenType::LeftBrace, "Expected '{'")?;
+                while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+                    values.push(self.parse_enumeration_value()?);
+                }
+                self.consume(TokenType::RightBrace, "Expected '}'")?;
+            } else {
+                // Header properties such as layer and description
+                self.skip_until_semicolon();
+            }
+        }
+        
+        Ok(EnumerationNode {
+            enumeration_name,
+            component,
+            values,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse `value Name [{ Property [=] "text"; ... }]`
+    fn parse_enumeration_value(&mut self) -> Result<EnumerationValue> {
+        let start = self.current_position();
+        if !self.match_keyword("value") {
+            return Err(ParseError::unexpected("value", self.peek_non_whitespace()).into());
+        }
+        let name = self.consume_name("Expected enumeration value name")?;
+        
+        let mut properties = HashMap::new();
+        if self.match_token(TokenType::LeftBrace) {
+            while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+                let key = self.consume_name("Expected property name")?.name;
+                self.match_token(TokenType::Equal);
+                let value = unquote(&self.advance().value).to_string();
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+                properties.insert(key, value);
+            }
+            self.consume(TokenType::RightBrace, "Expected '}'")?;
+        } else {
+            self.match_token(TokenType::Semicolon);
+        }
+        
+        let property = |key: &str| {
+            properties
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.clone())
+        };
+        Ok(EnumerationValue {
+            client_value: property("clientvalue"),
+            label_text: property("labeltext"),
+            name,
+            properties,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse a `.views` file: `layer`, common `COLUMN` definitions and `VIEW`
+    /// definitions, each optionally marked `PUBLIC` or `PRIVATE`
+    fn parse_views(&mut self) -> Result<ViewsNode> {
+        let start = self.current_position();
+        let mut layer = None;
+        let mut column_definitions = Vec::new();
+        let mut views = Vec::new();
+        
+        while !self.is_at_end() {
+            if self.match_token(TokenType::Layer) {
+                layer = Some(self.consume_name("Expected layer name")?.name);
+                self.consume(TokenType::Semicolon, "Expected ';'")?;
+                continue;
+            }
+            
+            let definition_start = self.current_position();
+            let annotations = self.parse_view_annotations();
+            let visibility = self.parse_view_visibility().unwrap_or_default();
+            if self.match_token(TokenType::Column) {
+                column_definitions.push(self.parse_column_definition(definition_start, visibility)?);
+            } else if self.match_token(TokenType::View) {
+                views.push(self.parse_view_definition(definition_start, visibility, annotations)?);
+            } else {
+                self.skip_until_semicolon();
+            }
+        }
+        
+        Ok(ViewsNode {
+            layer,
+            column_definitions,
+            views,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse an optional `PUBLIC` or `PRIVATE` marker
+    fn parse_view_visibility(&mut self) -> Option<ViewVisibility> {
+        if self.match_token(TokenType::Public) {
+            Some(ViewVisibility::Public)
+        } else if self.match_token(TokenType::Private) {
+            Some(ViewVisibility::Private)
+        } else {
+            None
+        }
+    }
+    
+    /// Parse annotations before a view. Annotations without an AST representation,
+    /// such as `@SecurityCheck Company.UserAuthorized(company)`, are skipped.
+    fn parse_view_annotations(&mut self) -> Vec<IfsAnnotation> {
+        let mut annotations = self.parse_annotations();
+        while self.peek_non_whitespace().value.starts_with('@') {
+            self.advance();
+            while !self.is_at_end()
+                && !self.check(TokenType::View)
+                && !self.check(TokenType::Public)
+                && !self.check(TokenType::Private)
+                && !self.peek_non_whitespace().value.starts_with('@')
+            {
+                self.advance();
+            }
+            annotations.extend(self.parse_annotations());
+        }
+        annotations
+    }
+    
+    /// Parse `name IS Key = 'value' ... ;` after `COLUMN`
+    fn parse_column_definition(&mut self, start: Position, visibility: ViewVisibility) -> Result<ColumnDefinition> {
+        let name = self.consume_name("Expected column name")?;
+        self.consume(TokenType::Is, "Expected 'IS'")?;
+        let mut properties = HashMap::new();
+        while !self.check(TokenType::Semicolon) && !self.is_at_end() {
+            let (key, value) = self.parse_view_property()?;
+            properties.insert(key, value);
+        }
+        self.consume(TokenType::Semicolon, "Expected ';'")?;
+        Ok(column_definition(name, visibility, properties, self.span_from(start)))
+    }
+    
+    /// Parse `name IS` view properties and column overrides such as
+    /// `[PRIVATE] Name.Flags = 'A----'` after `VIEW`, followed by the defining query
+    fn parse_view_definition(
+        &mut self,
+        start: Position,
+        visibility: ViewVisibility,
+        annotations: Vec<IfsAnnotation>,
+    ) -> Result<ViewDefinition> {
+        let name = self.consume_name("Expected view name")?;
+        self.consume(TokenType::Is, "Expected 'IS'")?;
+        
+        // Overridden columns in order of first appearance
+        let mut overrides: Vec<(Identifier, ViewVisibility, HashMap<String, String>)> = Vec::new();
+        while !self.check(TokenType::Select) && !self.is_at_end() {
+            let marker = self.parse_view_visibility();
+            let target = self.consume_name("Expected property or column name")?;
+            if !self.match_token(TokenType::Dot) {
+                // View-level property such as `Prompt = 'Customer'`
+                self.consume(TokenType::Equal, "Expected '='")?;
+                self.advance();
+                continue;
+            }
+            
+            let (key, value) = self.parse_view_property()?;
+            let index = match overrides.iter().position(|(column, ..)| column.normalized() == target.normalized()) {
+                Some(index) => index,
+                None => {
+                    overrides.push((target, visibility, HashMap::new()));
+                    overrides.len() - 1
+                }
+            };
+            let (_, column_visibility, properties) = &mut overrides[index];
+            if let Some(marker) = marker {
+                *column_visibility = marker;
+            }
+            properties.insert(key, value);
+        }
+        
+        let columns = overrides
+            .into_iter()
+            .map(|(name, visibility, properties)| {
+                let span = name.span.clone();
+                column_definition(name, visibility, properties, span)
+            })
+            .collect();
+        let query = self.parse_sql_query()?;
+        self.match_token(TokenType::Semicolon);
+        
+        Ok(ViewDefinition {
+            name,
+            visibility,
+            annotations,
+            columns,
+            query,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Parse `Key = 'value'` or `Key(value)`, returning the key and unquoted value
+    fn parse_view_property(&mut self) -> Result<(String, String)> {
+        let key = self.consume_name("Expected property name")?.name;
+        if self.match_token(TokenType::LeftParen) {
+            let start = self.current;
+            while !self.check(TokenType::RightParen) && !self.is_at_end() {
+                self.advance();
+            }
+            let value = self.source_text(start, self.current);
+            self.consume(TokenType::RightParen, "Expected ')'")?;
+            return Ok((key, value));
+        }
+        self.consume(TokenType::Equal, "Expected '='")?;
+        let value = unquote(&self.advance().value).to_string();
+        Ok((key, value))
+    }
+    
+    /// Parse `SELECT items FROM tables [WHERE condition]`. Joins and the
+    /// clauses after WHERE are skipped up to the terminating `;`.
+    fn parse_sql_query(&mut self) -> Result<SqlQuery> {
+        let start = self.current_position();
+        self.consume(TokenType::Select, "Expected 'SELECT'")?;
+        self.match_token(TokenType::Distinct);
+        
+        let mut select = Vec::new();
+        loop {
+            let item_start = self.current_position();
+            let expression = match self.parse_select_star() {
+                Some(star) => star,
+                None => self.parse_expression_until(&[TokenType::Comma, TokenType::From, TokenType::As, TokenType::Identifier]),
+            };
+            self.match_token(TokenType::As);
+            let alias = if self.check(TokenType::Identifier) {
+                Some(token_identifier(self.advance()))
+            } else {
+                None
+            };
+            select.push(SelectItem {
+                expression,
+                alias,
+                span: self.span_from(item_start),
+            });
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        
+        self.consume(TokenType::From, "Expected 'FROM'")?;
+        let mut from = Vec::new();
+        loop {
+            let item_start = self.current_position();
+            let table = self.consume_identifier("Expected table name")?;
+            let alias = if self.check(TokenType::Identifier) {
+                Some(token_identifier(self.advance()))
+            } else {
+                None
+            };
+            from.push(FromItem {
+                table,
+                alias,
+                span: self.span_from(item_start),
+            });
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        
+        let where_clause = if self.match_token(TokenType::Where) {
+            Some(self.parse_expression_until(&[TokenType::GroupBy, TokenType::OrderBy, TokenType::Union]))
+        } else {
+            None
+        };
+        while !self.check(TokenType::Semicolon) && !self.is_at_end() {
+            self.advance();
+        }
+        
+        Ok(SqlQuery {
+            select,
+            from,
+            where_clause,
+            group_by: Vec::new(),
+            having: None,
+            order_by: Vec::new(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse a `*` or `alias.*` select item as a literal
+    fn parse_select_star(&mut self) -> Option<Expression> {
+        let checkpoint = self.current;
+        let start = self.current_position();
+        if self.match_token(TokenType::Identifier) && !self.match_token(TokenType::Dot) {
+            self.current = checkpoint;
+            return None;
+        }
+        if !self.match_token(TokenType::Multiply) {
+            self.current = checkpoint;
+            return None;
+        }
+        Some(Expression::Literal {
+            value: self.source_text(checkpoint, self.current),
+            span: self.span_from(start),
+        })
+    }
+    
+    fn parse_storage(&mut self) -> Result<StorageNode> {
+        // Parse storage definition - placeholder implementation
+        Ok(StorageNode {
+            layer: None,
+            definitions: Vec::new(),
+            span: self.current_span(),
+        })
+    }
+    
+    #[allow(dead_code)]
+    fn parse_xml_enumeration(&mut self) -> Result<EnumerationNode> {
+        // Placeholder implementation - this method is deprecated
+        // Use parse_enumeration instead
+        Ok(EnumerationNode {
+            enumeration_name: Identifier {
+                name: "placeholder_enum".to_string(),
+                span: self.current_span(),
+            },
+            component: "".to_string(),
+            values: Vec::new(),
+            span: self.current_span(),
+        })
+    }
+    
+    #[allow(dead_code)]
+    fn parse_sql_view(&mut self) -> Result<ViewsNode> {
+        // Placeholder implementation - this method is deprecated
+        // Use parse_views instead
+        Ok(ViewsNode {
+            layer: None,
+            column_definitions: Vec::new(),
+            views: Vec::new(),
+            span: self.current_span(),
+        })
+    }
+    
+    fn parse_marble_projection(&mut self) -> Result<MarbleProjectionNode> {
+        // Placeholder implementation
+        Ok(MarbleProjectionNode {
+            name: Identifier {
+                name: "placeholder".to_string(),
+                span: self.current_span(),
+            },
+            entity: Identifier {
+                name: "placeholder".to_string(),
+                span: self.current_span(),
+            },
+            attributes: Vec::new(),
+            actions: Vec::new(),
+            span: self.current_span(),
+        })
+    }
+    
+    fn parse_marble_client(&mut self) -> Result<MarbleClientNode> {
+        // Placeholder implementation
+        Ok(MarbleClientNode {
+            name: Identifier {
+                name: "placeholder".to_string(),
+                span: self.current_span(),
+            },
+            layout: Vec::new(),
+            commands: Vec::new(),
+            span: self.current_span(),
+        })
+    }
+    
+    // Helper methods
+    
+    fn advance(&mut self) -> &Token {
+        if self.deadline_passed() {
+            self.current = self.tokens.len() - 1;
+            return &self.tokens[self.current]; // Return EOF
+        }
+        
+        // Store the token we want to return before skipping whitespace
+        let token_index = if !self.is_at_end() {
+            let current_index = self.current;
+            self.current += 1;
+            current_index
+        } else {
+            return &self.tokens[self.tokens.len() - 1]; // Return EOF
+        };
+        
+        // Skip whitespace after advancing
+        while self.current < self.tokens.len() && self.tokens[self.current].is_trivia() {
+            self.current += 1;
+        }
+        
+        // Return the actual token we advanced over, not the whitespace
+        &self.tokens[token_index]
+    }
+    
+    /// Whether `ParseOptions::deadline` has passed, reading the clock every
+    /// `DEADLINE_CHECK_INTERVAL` tokens
+    fn deadline_passed(&mut self) -> bool {
+        let Some(deadline) = self.options.deadline else {
+            return false;
+        };
+        if !self.timed_out {
+            self.advances += 1;
+            self.timed_out = self.advances % DEADLINE_CHECK_INTERVAL == 1 && Instant::now() >= deadline;
+        }
+        self.timed_out
+    }
+    
+    fn is_at_end(&self) -> bool {
+        self.peek_non_whitespace().token_type == TokenType::Eof
+    }
+    
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
+    }
+    
+    /// Peek at the next non-whitespace token
+    fn peek_non_whitespace(&self) -> &Token {
+        let mut pos = self.current;
+        while pos < self.tokens.len() && self.tokens[pos].is_trivia() {
+            pos += 1;
+        }
+        if pos >= self.tokens.len() {
+            &self.tokens[self.tokens.len() - 1] // Should be EOF
+        } else {
+            &self.tokens[pos]
+        }
+    }
+    
+    fn previous(&self) -> &Token {
+        &self.tokens[self.current.saturating_sub(1)]
+    }
+    
+    fn check(&self, token_type: TokenType) -> bool {
+        if self.is_at_end() {
+            false
+        } else {
+            let found = &self.peek_non_whitespace().token_type;
+            // Substitution variables stand in for an identifier in script files
+            *found == token_type
+                || (token_type == TokenType::Identifier && *found == TokenType::Substitution)
+        }
+    }
+    
+    fn match_token(&mut self, token_type: TokenType) -> bool {
+        if self.check(token_type) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+    
+    /// Check for an identifier token with the given text, ignoring case
+    fn check_keyword(&self, keyword: &str) -> bool {
+        let token = self.peek_non_whitespace();
+        token.token_type == TokenType::Identifier && token.value.eq_ignore_ascii_case(keyword)
+    }
+    
+    /// Check for a multi-word keyword phrase such as `IS NOT NULL`, ignoring case.
+    /// Whitespace and comments between the words are allowed.
+    fn check_phrase(&self, words: &[&str]) -> bool {
+        let mut pos = self.current;
+        for word in words {
+            while pos < self.tokens.len() && self.tokens[pos].is_trivia() {
+                pos += 1;
+            }
+            match self.tokens.get(pos) {
+                Some(token)
+                    if !matches!(token.token_type, TokenType::String | TokenType::Substitution | TokenType::Eof)
+                        && token.value.eq_ignore_ascii_case(word) =>
+                {
+                    pos += 1;
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+    
+    /// Match a multi-word keyword phrase, consuming every word
+    fn match_phrase(&mut self, words: &[&str]) -> bool {
+        if !self.check_phrase(words) {
+            return false;
+        }
+        for _ in words {
+            self.advance();
+        }
+        true
+    }
+    
+    /// Match an identifier token with the given text, ignoring case
+    fn match_keyword(&mut self, keyword: &str) -> bool {
+        if self.check_keyword(keyword) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+    
+    fn consume(&mut self, token_type: TokenType, _message: &str) -> Result<&Token> {
+        if self.check(token_type.clone()) {
+            Ok(self.advance())
+        } else {
+            Err(ParseError::unexpected(&token_type.to_string(), self.peek_non_whitespace()).into())
+        }
+    }
+    
+    /// Consume a token of any of `token_types`, or fail naming all of them,
+    /// e.g. "expected AS or IS after package name"
+    fn expect_one_of(&mut self, token_types: &[TokenType], context: &str) -> Result<&Token> {
+        if token_types.iter().any(|token_type| self.check(token_type.clone())) {
+            return Ok(self.advance());
+        }
+        
+        let names: Vec<String> = token_types
+            .iter()
+            .map(|token_type| token_type.to_string().to_uppercase())
+            .collect();
+        let expected = match names.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => String::new(),
+        };
+        let expected = format!("{} {}", expected, context);
+        Err(ParseError::unexpected(expected.trim(), self.peek_non_whitespace()).into())
+    }
+    
+    fn consume_identifier(&mut self, _message: &str) -> Result<Identifier> {
+        if self.check(TokenType::Identifier) {
+            Ok(token_identifier(self.advance()))
+        } else {
+            Err(ParseError::unexpected("identifier", self.peek_non_whitespace()).into())
+        }
+    }
+    
+    /// Consume a word token as a name, accepting keywords such as `Public` or `Key`
+    /// that are ordinary names in model files
+    fn consume_name(&mut self, _message: &str) -> Result<Identifier> {
+        let token = self.peek_non_whitespace();
+        if token.token_type != TokenType::Eof
+            && token.value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        {
+            Ok(token_identifier(self.advance()))
+        } else {
+            Err(ParseError::unexpected("name", token).into())
+        }
+    }
+    
+    fn skip_whitespace(&mut self) {
+        while !self.is_at_end() && self.peek().is_trivia() {
+            self.current += 1;
+        }
+    }
+    
+    /// Trivia between the last consumed token and the current one, split into
+    /// the pieces on the last token's line and those on the lines below it
+    fn pending_trivia(&self) -> (Vec<TriviaPiece>, Vec<TriviaPiece>) {
+        let mut start = self.current;
+        while start > 0 && self.tokens[start - 1].is_trivia() {
+            start -= 1;
+        }
+        
+        let mut same_line = Vec::new();
+        let mut below = Vec::new();
+        let mut on_last_line = start > 0;
+        let mut newlines = if start == 0 { 1 } else { 0 };
+        for token in &self.tokens[start..self.current] {
+            match token.token_type {
+                TokenType::Newline => {
+                    on_last_line = false;
+                    newlines += 1;
+                    if newlines > 1 && below.last() != Some(&TriviaPiece::BlankLine) {
+                        below.push(TriviaPiece::BlankLine);
+                    }
+                }
+                TokenType::Comment => {
+                    let piece = TriviaPiece::Comment(token.value.trim_end().to_string());
+                    if on_last_line {
+                        same_line.push(piece);
+                    } else {
+                        below.push(piece);
+                    }
+                    newlines = 0;
+                }
+                _ => {}
+            }
+        }
+        (same_line, below)
+    }
+    
+    fn skip_until_semicolon(&mut self) {
+        while !self.check(TokenType::Semicolon) && !self.is_at_end() {
+            self.advance();
+        }
+        if self.check(TokenType::Semicolon) {
+            self.advance();
+        }
+    }
+    
+    /// Start of the next non-trivia token
+    fn current_position(&self) -> Position {
+        let token = self.peek_non_whitespace();
+        Position {
+            line: token.position.line,
+            column: token.position.column,
+            offset: token.position.offset,
+        }
+    }
+    
+    /// Position just past the last consumed non-trivia token
+    fn last_position(&self) -> Position {
+        self.tokens[..self.current]
+            .iter()
+            .rev()
+            .find(|token| !token.is_trivia())
+            .map(|token| {
+                let end = token.end_position();
+                Position {
+                    line: end.line,
+                    column: end.column,
+                    offset: end.offset,
+                }
+            })
+            .unwrap_or_else(|| self.current_position())
+    }
+    
+    /// Span from `start` to the last consumed token
+    fn span_from(&self, start: Position) -> Span {
+        Span {
+            start,
+            end: self.last_position(),
+        }
+    }
+    
+    /// Source text of the tokens in `start..end`, without surrounding whitespace
+    fn source_text(&self, start: usize, end: usize) -> String {
+        self.tokens[start..end]
+            .iter()
+            .map(|token| token.value.as_str())
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+    
+    fn current_span(&self) -> Span {
+        let pos = self.current_position();
+        Span {
+            start: pos.clone(),
+            end: pos,
+        }
+    }
+    
+    /// Determine procedure/function visibility based on IFS naming convention
+    fn determine_visibility(&self, name: &str) -> ProcedureVisibility {
+        if name.ends_with("___") {
+            ProcedureVisibility::Private
+        } else if name.ends_with("__") {
+            ProcedureVisibility::Protected
+        } else {
+            ProcedureVisibility::Public
+        }
+    }
+}
+
+/// Identifier covering a single token
+fn token_identifier(token: &Token) -> Identifier {
+    Identifier {
+        name: token.value.clone(),
+        span: Span {
+            start: Position {
+                line: token.position.line,
+                column: token.position.column,
+                offset: token.position.offset,
             },
-            entity: Identifier {
-                name: "placeholder".to_string(),
-                span: self.current_span(),
+            end: Position {
+                line: token.position.line,
+                column: token.position.column + token.value.chars().count(),
+                offset: token.position.offset + token.value.len(),
             },
-            attributes: Vec::new(),
-            actions: Vec::new(),
-            span: self.current_span(),
-        })
+        },
+    }
+}
+
+/// Strip the quotes from a `"..."` or `'...'` string literal
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .or_else(|| text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')))
+        .unwrap_or(text)
+}
+
+/// Build a column definition, lifting the well-known properties out of `properties`
+fn column_definition(
+    name: Identifier,
+    visibility: ViewVisibility,
+    properties: HashMap<String, String>,
+    span: Span,
+) -> ColumnDefinition {
+    let property = |key: &str| {
+        properties
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.clone())
+    };
+    ColumnDefinition {
+        flags: property("flags"),
+        datatype: property("datatype"),
+        prompt: property("prompt"),
+        // `Ref = 'CustomerInfo(company)/NOCHECK'` references the CustomerInfo entity
+        reference: property("ref")
+            .and_then(|target| target.split(&['(', '/'][..]).next().map(|name| name.trim().to_string()))
+            .filter(|name| !name.is_empty()),
+        name,
+        visibility,
+        properties,
+        span,
+    }
+}
+
+/// Convenience function to tokenize source code. Without `include_trivia`, whitespace,
+/// newline and comment tokens are dropped, leaving the tokens the parser acts on.
+pub fn tokenize(source: &str, language: Language, include_trivia: bool) -> Vec<Token> {
+    let mut tokens = Lexer::new(source.to_string(), language).tokenize();
+    if !include_trivia {
+        tokens.retain(|token| !token.is_trivia());
+    }
+    tokens
+}
+
+/// Convenience function to parse source code
+pub fn parse_source(input: &str, language: Language) -> Result<AstNode> {
+    parse_source_with_options(input, language, ParseOptions::default())
+}
+
+/// Parse only signatures, declarations and nesting, leaving subprogram bodies empty
+pub fn parse_source_outline(input: &str, language: Language) -> Result<AstNode> {
+    parse_source_with_options(input, language, ParseOptions::outline())
+}
+
+/// Parse source code with explicit options, e.g. `ParseOptions::default().with_timeout(..)`
+/// to fail with `ParseError::TimedOut` instead of blocking on a pathological input
+pub fn parse_source_with_options(input: &str, language: Language, options: ParseOptions) -> Result<AstNode> {
+    let mut lexer = Lexer::new(input.to_string(), language);
+    let tokens = lexer.tokenize();
+    let mut parser = Parser::with_options(tokens, language, options);
+    parser.parse()
+}
+
+/// Shared flag used to cancel an in-flight parse batch or indexing run from another thread
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every run holding a clone of this token
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Parse a batch of `(path, content)` pairs, detecting each language from the
+/// path extension and falling back to PL/SQL. Results keep the input order.
+/// With the `parallel` feature the inputs are parsed on the rayon thread pool.
+/// Once `cancellation` is cancelled no further input is parsed and the batch fails
+/// with `ParseError::Cancelled`.
+pub fn parse_many(
+    inputs: &[(PathBuf, String)],
+    cancellation: Option<&CancellationToken>,
+) -> Result<Vec<(PathBuf, Result<AstNode>)>> {
+    let is_cancelled = || cancellation.is_some_and(CancellationToken::is_cancelled);
+    let parse = |(path, content): &(PathBuf, String)| {
+        if is_cancelled() {
+            return (path.clone(), Err(ParseError::Cancelled.into()));
+        }
+        let language = Language::from_path(path).unwrap_or(Language::PlSql);
+        (path.clone(), parse_source(content, language))
+    };
+    
+    #[cfg(feature = "parallel")]
+    let results: Vec<_> = {
+        use rayon::prelude::*;
+        inputs.par_iter().map(parse).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<_> = inputs.iter().map(parse).collect();
+    
+    if is_cancelled() {
+        return Err(ParseError::Cancelled.into());
+    }
+    Ok(results)
+}
+
+/// Parse source code and build a line index over it for later offset/position conversion
+pub fn parse_source_with_index(input: &str, language: Language) -> Result<(AstNode, LineIndex)> {
+    let ast = parse_source(input, language)?;
+    Ok((ast, LineIndex::new(input)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_package() {
+        let input = "PACKAGE test_pkg IS END;";
+        let result = parse_source(input, Language::PlSql);
+        
+        assert!(result.is_ok());
+        if let AstNode::PlSql(PlSqlNode::Package { name, .. }) = result.unwrap() {
+            assert_eq!(name.name, "test_pkg");
+        } else {
+            panic!("Expected package node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_procedure() {
+        let input = "PROCEDURE test_proc IS BEGIN NULL; END;";
+        let result = parse_source(input, Language::PlSql);
+        
+        assert!(result.is_ok());
+        if let AstNode::PlSql(PlSqlNode::Procedure { name, .. }) = result.unwrap() {
+            assert_eq!(name.name, "test_proc");
+        } else {
+            panic!("Expected procedure node");
+        }
+    }
+    
+    #[test]
+    fn test_parse_past_deadline_fails_with_timed_out() {
+        let input = format!("PROCEDURE Slow_Proc IS\nBEGIN\n{}END Slow_Proc;", "   NULL;\n".repeat(200_000));
+        let options = ParseOptions::default().with_timeout(Duration::ZERO);
+        let error = parse_source_with_options(&input, Language::PlSql, options).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ParseError>(), Some(ParseError::TimedOut)), "{}", error);
+        
+        let options = ParseOptions::default().with_timeout(Duration::from_secs(60));
+        assert!(parse_source_with_options("PROCEDURE Fast_Proc IS\nBEGIN\n   NULL;\nEND;", Language::PlSql, options).is_ok());
+    }
+    
+    #[test]
+    fn test_parse_procedure_with_non_ascii_text() {
+        let input = "PROCEDURE Set_Name IS\n   -- Ändrad av Åsa\n   x_ VARCHAR2(20);\nBEGIN\n   x_ := 'ÅÄÖåäö';\nEND Set_Name;";
+        let result = parse_source(input, Language::PlSql);
+        
+        assert!(matches!(result, Ok(AstNode::PlSql(PlSqlNode::Procedure { .. }))), "{:?}", result);
+    }
+    
+    #[test]
+    fn test_parse_slash_separated_procedures() {
+        let input = "PROCEDURE First_Proc IS\nBEGIN\n   NULL;\nEND First_Proc;\n/\n\nPROCEDURE Second_Proc IS\nBEGIN\n   IF a_ > 0 THEN\n      b_ := a_ / 2;\n   END IF;\nEND Second_Proc;\n/\n";
+        let mut lexer = Lexer::new(input.to_string(), Language::PlSql);
+        let mut parser = Parser::new(lexer.tokenize(), Language::PlSql);
+        let nodes = parser.parse_all().unwrap();
+        
+        let names: Vec<String> = nodes
+            .iter()
+            .map(|node| match node {
+                AstNode::PlSql(PlSqlNode::Procedure { name, .. }) => name.name.clone(),
+                other => panic!("Expected procedure node, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(names, vec!["First_Proc", "Second_Proc"]);
+    }
+    
+    fn parse_procedure_body(input: &str) -> Vec<PlSqlStatement> {
+        match parse_source(input, Language::PlSql).unwrap() {
+            AstNode::PlSql(PlSqlNode::Procedure { body, .. }) => body,
+            other => panic!("Expected procedure node, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_parse_update_statement() {
+        let body = parse_procedure_body(
+            "PROCEDURE Rename_Customer IS\nBEGIN\n   UPDATE customer_tab\n      SET name = 'New Name'\n    WHERE customer_id = '10';\nEND Rename_Customer;",
+        );
+        
+        assert_eq!(body.len(), 1);
+        if let PlSqlStatement::Dml { kind, target_table, raw, .. } = &body[0] {
+            assert_eq!(*kind, DmlKind::Update);
+            assert_eq!(target_table.name, "customer_tab");
+            assert!(raw.starts_with("UPDATE customer_tab"));
+            assert!(raw.ends_with("customer_id = '10'"));
+        } else {
+            panic!("Expected DML statement, got {:?}", body[0]);
+        }
+    }
+    
+    #[test]
+    fn test_parse_substitution_variables_as_identifiers() {
+        let body = parse_procedure_body(
+            "PROCEDURE Install IS\nBEGIN\n   &AO..Module_API.Register(&&cmp, '&module');\nEND;",
+        );
+        
+        assert_eq!(body.len(), 1);
+        if let PlSqlStatement::Call { name, arguments, .. } = &body[0] {
+            assert_eq!(name.name, "&AO..Module_API.Register");
+            assert_eq!(arguments.len(), 2);
+            assert!(matches!(&arguments[0], Expression::Identifier(id) if id.name == "&&cmp"));
+        } else {
+            panic!("Expected call statement, got {:?}", body[0]);
+        }
+    }
+    
+    #[test]
+    fn test_parse_boolean_literal_assignment() {
+        let body = parse_procedure_body("PROCEDURE Flag IS\nBEGIN\n   b_ := TRUE;\nEND;");
+        
+        assert_eq!(body.len(), 1);
+        if let PlSqlStatement::Assignment { value, .. } = &body[0] {
+            assert!(matches!(value, Expression::Literal { value, .. } if value == "TRUE"));
+        } else {
+            panic!("Expected assignment statement, got {:?}", body[0]);
+        }
+    }
+    
+    #[test]
+    fn test_parse_raise_statements() {
+        let body = parse_procedure_body(
+            "PROCEDURE Fail IS\nBEGIN\n   RAISE no_data_found;\n   RAISE;\n   RAISE_APPLICATION_ERROR(-20001, 'Failed');\nEND;",
+        );
+        
+        assert_eq!(body.len(), 3);
+        assert!(matches!(
+            &body[0],
+            PlSqlStatement::Raise { exception: Some(name), .. } if name.name == "no_data_found"
+        ));
+        assert!(matches!(&body[1], PlSqlStatement::Raise { exception: None, .. }));
+        assert!(matches!(
+            &body[2],
+            PlSqlStatement::Call { name, arguments, .. }
+                if name.name == "RAISE_APPLICATION_ERROR" && arguments.len() == 2
+        ));
+        assert!(body.iter().all(PlSqlStatement::is_terminating));
+    }
+    
+    #[test]
+    fn test_phrases_tolerate_comments_between_words() {
+        let ast = parse_source(
+            "PROCEDURE Update_Qty (qty_ IN /* updated */ OUT NUMBER) IS\nBEGIN\n   IF qty_ IS -- may be missing\n      NOT NULL THEN\n      qty_ := 0;\n   END IF;\nEND;",
+            Language::PlSql,
+        )
+        .unwrap();
+        
+        let AstNode::PlSql(PlSqlNode::Procedure { parameters, body, .. }) = ast else {
+            panic!("Expected procedure node");
+        };
+        assert_eq!(parameters[0].mode, ParameterMode::InOut);
+        assert_eq!(parameters[0].param_type.name, "NUMBER");
+        
+        let PlSqlStatement::If { condition, .. } = &body[0] else {
+            panic!("Expected IF statement, got {:?}", body[0]);
+        };
+        assert!(matches!(
+            condition,
+            Expression::Unary { operator: UnaryOperator::IsNotNull, operand, .. }
+                if matches!(operand.as_ref(), Expression::Identifier(id) if id.name == "qty_")
+        ));
+    }
+    
+    #[test]
+    fn test_parse_is_null_inside_boolean_expression() {
+        let body = parse_procedure_body("PROCEDURE Check IS\nBEGIN\n   ok_ := a_ IS NULL AND b_ = 1;\nEND;");
+        
+        let PlSqlStatement::Assignment { value, .. } = &body[0] else {
+            panic!("Expected assignment statement, got {:?}", body[0]);
+        };
+        let Expression::Binary { left, operator: BinaryOperator::And, .. } = value else {
+            panic!("Expected AND expression, got {:?}", value);
+        };
+        assert!(matches!(left.as_ref(), Expression::Unary { operator: UnaryOperator::IsNull, .. }));
     }
     
-    fn parse_marble_client(&mut self) -> Result<MarbleClientNode> {
-        // Placeholder implementation
-        Ok(MarbleClientNode {
-            name: Identifier {
-                name: "placeholder".to_string(),
-                span: self.current_span(),
-            },
-            layout: Vec::new(),
-            commands: Vec::new(),
-            span: self.current_span(),
-        })
+    fn assignment_value(statement: &PlSqlStatement) -> &Expression {
+        match statement {
+            PlSqlStatement::Assignment { value, .. } => value,
+            other => panic!("Expected assignment statement, got {:?}", other),
+        }
     }
     
-    // Helper methods
+    #[test]
+    fn test_parse_searched_case_expression() {
+        let body = parse_procedure_body(
+            "PROCEDURE Size IS\nBEGIN\n   size_ := CASE WHEN qty_ > 10 THEN 'LARGE' WHEN qty_ = 0 THEN 'NONE' ELSE 'SMALL' END;\nEND;",
+        );
+        
+        let Expression::Case { operand, branches, default, .. } = assignment_value(&body[0]) else {
+            panic!("Expected CASE expression, got {:?}", body[0]);
+        };
+        assert!(operand.is_none());
+        assert_eq!(branches.len(), 2);
+        assert!(matches!(&branches[0].0, Expression::Binary { operator: BinaryOperator::GreaterThan, .. }));
+        assert!(matches!(&branches[1].1, Expression::Literal { value, .. } if value == "'NONE'"));
+        assert!(matches!(default.as_deref(), Some(Expression::Literal { value, .. }) if value == "'SMALL'"));
+    }
     
-    fn advance(&mut self) -> &Token {
-        // Store the token we want to return before skipping whitespace
-        let token_index = if !self.is_at_end() {
-            let current_index = self.current;
-            self.current += 1;
-            current_index
-        } else {
-            return &self.tokens[self.tokens.len() - 1]; // Return EOF
+    #[test]
+    fn test_parse_simple_case_expression() {
+        let body = parse_procedure_body(
+            "PROCEDURE Code IS\nBEGIN\n   code_ := CASE state_ WHEN 'Planned' THEN 1 WHEN 'Released' THEN 2 END + 1;\nEND;",
+        );
+        
+        let Expression::Binary { left, operator: BinaryOperator::Add, .. } = assignment_value(&body[0]) else {
+            panic!("Expected addition, got {:?}", body[0]);
+        };
+        let Expression::Case { operand, branches, default, .. } = left.as_ref() else {
+            panic!("Expected CASE expression, got {:?}", left);
         };
+        assert!(matches!(operand.as_deref(), Some(Expression::Identifier(id)) if id.name == "state_"));
+        assert_eq!(branches.len(), 2);
+        assert!(default.is_none());
+    }
+    
+    #[test]
+    fn test_parse_dml_kinds_and_targets() {
+        let body = parse_procedure_body(
+            "PROCEDURE Sync IS\nBEGIN\n   INSERT INTO order_tab (id) VALUES (1);\n   IF done_ THEN\n      DELETE FROM appowner.order_line_tab WHERE id = 1;\n   END IF;\n   MERGE INTO stock_tab s USING dual ON (1 = 1) WHEN MATCHED THEN UPDATE SET qty = 0;\nEND;",
+        );
         
-        // Skip whitespace after advancing
-        while self.current < self.tokens.len() {
-            match self.tokens[self.current].token_type {
-                TokenType::Whitespace | TokenType::Newline | TokenType::Comment => {
-                    self.current += 1;
+        let mut targets = Vec::new();
+        for statement in &body {
+            match statement {
+                PlSqlStatement::Dml { kind, target_table, .. } => targets.push((*kind, target_table.name.clone())),
+                PlSqlStatement::If { then_branch, .. } => {
+                    if let PlSqlStatement::Dml { kind, target_table, .. } = &then_branch[0] {
+                        targets.push((*kind, target_table.name.clone()));
+                    }
                 }
-                _ => break,
+                other => panic!("Unexpected statement {:?}", other),
             }
         }
         
-        // Return the actual token we advanced over, not the whitespace
-        &self.tokens[token_index]
+        assert_eq!(
+            targets,
+            vec![
+                (DmlKind::Insert, "order_tab".to_string()),
+                (DmlKind::Delete, "appowner.order_line_tab".to_string()),
+                (DmlKind::Merge, "stock_tab".to_string()),
+            ]
+        );
     }
     
-    fn is_at_end(&self) -> bool {
-        self.peek_non_whitespace().token_type == TokenType::Eof
+    #[test]
+    fn test_parse_source_with_index() {
+        let input = "PROCEDURE Do_It IS\nBEGIN\n   NULL;\nEND;";
+        let (ast, line_index) = parse_source_with_index(input, Language::PlSql).unwrap();
+        
+        assert!(matches!(ast, AstNode::PlSql(PlSqlNode::Procedure { .. })));
+        let null_offset = input.find("NULL").unwrap();
+        let position = line_index.position(null_offset);
+        assert_eq!((position.line, position.column), (3, 4));
     }
     
-    fn peek(&self) -> &Token {
-        &self.tokens[self.current]
+    #[test]
+    fn test_parse_parameters() {
+        let input = "PROCEDURE Modify(\n   info_ OUT VARCHAR2,\n   objid_ IN VARCHAR2,\n   attr_ IN OUT NOCOPY VARCHAR2,\n   action_ IN VARCHAR2 DEFAULT 'DO') IS\nBEGIN\n   NULL;\nEND;";
+        
+        if let AstNode::PlSql(PlSqlNode::Procedure { parameters, .. }) = parse_source(input, Language::PlSql).unwrap() {
+            let summary: Vec<(&str, ParameterMode, &str)> = parameters
+                .iter()
+                .map(|p| (p.name.name.as_str(), p.mode.clone(), p.param_type.name.as_str()))
+                .collect();
+            assert_eq!(
+                summary,
+                vec![
+                    ("info_", ParameterMode::Out, "VARCHAR2"),
+                    ("objid_", ParameterMode::In, "VARCHAR2"),
+                    ("attr_", ParameterMode::InOut, "VARCHAR2"),
+                    ("action_", ParameterMode::In, "VARCHAR2"),
+                ]
+            );
+            assert!(parameters[3].default_value.is_some());
+            assert!(parameters[0].default_value.is_none());
+        } else {
+            panic!("Expected procedure node");
+        }
     }
     
-    /// Peek at the next non-whitespace token
-    fn peek_non_whitespace(&self) -> &Token {
-        let mut pos = self.current;
-        while pos < self.tokens.len() {
-            match self.tokens[pos].token_type {
-                TokenType::Whitespace | TokenType::Newline | TokenType::Comment => {
-                    pos += 1;
-                }
-                _ => break,
-            }
+    #[test]
+    fn test_outline_skips_bodies() {
+        let input = "PROCEDURE Process_Order(order_no_ IN VARCHAR2) IS\n   PROCEDURE Helper IS\n   BEGIN\n      NULL;\n   END Helper;\nBEGIN\n   IF order_no_ IS NULL THEN\n      RETURN;\n   END IF;\n   FOR i IN 1 .. 10 LOOP\n      Helper;\n   END LOOP;\n   BEGIN\n      UPDATE order_tab SET state = 'Done' WHERE order_no = order_no_;\n   EXCEPTION\n      WHEN OTHERS THEN\n         NULL;\n   END;\n   x_ := CASE WHEN y_ > 0 THEN 1 ELSE 0 END;\nEXCEPTION\n   WHEN NO_DATA_FOUND THEN\n      NULL;\nEND Process_Order;";
+        
+        let full = parse_source(input, Language::PlSql).unwrap();
+        if let AstNode::PlSql(PlSqlNode::Procedure { body, .. }) = &full {
+            assert!(!body.is_empty());
         }
-        if pos >= self.tokens.len() {
-            &self.tokens[self.tokens.len() - 1] // Should be EOF
+        
+        let outline = parse_source_outline(input, Language::PlSql).unwrap();
+        if let AstNode::PlSql(PlSqlNode::Procedure { name, parameters, body, exception_handlers, .. }) = outline {
+            assert_eq!(name.name, "Process_Order");
+            assert_eq!(parameters.len(), 1);
+            assert_eq!(parameters[0].name.name, "order_no_");
+            assert!(body.is_empty());
+            assert!(exception_handlers.is_empty());
         } else {
-            &self.tokens[pos]
+            panic!("Expected procedure node");
         }
     }
     
-    fn previous(&self) -> &Token {
-        &self.tokens[self.current.saturating_sub(1)]
-    }
-    
-    fn check(&self, token_type: TokenType) -> bool {
-        if self.is_at_end() {
-            false
+    #[test]
+    fn test_parse_pipelined_function() {
+        let input = "FUNCTION Get_Lines(order_no_ IN VARCHAR2) RETURN line_table PIPELINED IS\nBEGIN\n   RETURN;\nEND Get_Lines;";
+        
+        if let AstNode::PlSql(PlSqlNode::Function { return_type, is_pipelined, result_cached, .. }) = parse_source(input, Language::PlSql).unwrap() {
+            assert_eq!(return_type.name, "line_table");
+            assert!(is_pipelined);
+            assert!(!result_cached);
         } else {
-            self.peek_non_whitespace().token_type == token_type
+            panic!("Expected function node");
         }
     }
     
-    fn match_token(&mut self, token_type: TokenType) -> bool {
-        if self.check(token_type) {
-            self.advance();
-            true
+    #[test]
+    fn test_parse_result_cached_function() {
+        let input = "FUNCTION Get_Rate(currency_ IN VARCHAR2) RETURN NUMBER RESULT_CACHE RELIES_ON (currency_rate_tab) IS\nBEGIN\n   RETURN 1;\nEND Get_Rate;";
+        
+        if let AstNode::PlSql(PlSqlNode::Function { return_type, is_pipelined, result_cached, .. }) = parse_source(input, Language::PlSql).unwrap() {
+            assert_eq!(return_type.name, "NUMBER");
+            assert!(!is_pipelined);
+            assert!(result_cached);
         } else {
-            false
+            panic!("Expected function node");
         }
     }
     
-    fn consume(&mut self, token_type: TokenType, _message: &str) -> Result<&Token> {
-        if self.check(token_type.clone()) {
-            Ok(self.advance())
+    #[test]
+    fn test_preserve_trivia_round_trips_comments() {
+        let input = "PROCEDURE Set_Total IS\nBEGIN\n   -- Start from a clean sum\n   total_ := 0; -- running total\n   Add_Lines(total_);\nEND Set_Total;";
+        
+        if let AstNode::PlSql(PlSqlNode::Procedure { body, .. }) = parse_source(input, Language::PlSql).unwrap() {
+            assert!(body.iter().all(|statement| statement.trivia().is_none()));
+        }
+        
+        let tokens = Lexer::new(input.to_string(), Language::PlSql).tokenize();
+        let mut parser = Parser::with_options(tokens, Language::PlSql, ParseOptions::lossless());
+        if let AstNode::PlSql(PlSqlNode::Procedure { body, .. }) = parser.parse().unwrap() {
+            let trivia = body[0].trivia().unwrap();
+            assert_eq!(trivia.leading, vec![TriviaPiece::Comment("-- Start from a clean sum".to_string())]);
+            assert_eq!(
+                trivia.wrap("total_ := 0;", "   "),
+                "   -- Start from a clean sum\n   total_ := 0; -- running total"
+            );
+            assert!(body[1].trivia().is_none());
         } else {
-            Err(ParseError::UnexpectedToken {
-                expected: format!("{:?}", token_type),
-                found: format!("{:?}", self.peek().token_type),
-            }.into())
+            panic!("Expected procedure node");
         }
     }
     
-    fn consume_identifier(&mut self, _message: &str) -> Result<Identifier> {
-        if self.check(TokenType::Identifier) {
-            let token = self.advance();
-            Ok(Identifier {
-                name: token.value.clone(),
-                span: Span {
-                    start: Position {
-                        line: token.position.line,
-                        column: token.position.column,
-                        offset: token.position.offset,
-                    },
-                    end: Position {
-                        line: token.position.line,
-                        column: token.position.column + token.value.len(),
-                        offset: token.position.offset + token.value.len(),
-                    },
-                },
-            })
-        } else {
-            Err(ParseError::UnexpectedToken {
-                expected: "identifier".to_string(),
-                found: format!("{:?}", self.peek().token_type),
-            }.into())
-        }
+    #[test]
+    fn test_unexpected_token_error_shows_value_and_location() {
+        let error = parse_source("PROCEDURE Broken_Proc\nBEGIN\n   NULL;\nEND;", Language::PlSql).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("expected IS or AS"), "{}", message);
+        assert!(message.contains("found 'BEGIN'"), "{}", message);
+        assert!(message.contains("line 2"), "{}", message);
     }
     
-    fn skip_whitespace(&mut self) {
-        while !self.is_at_end() {
-            match self.peek().token_type {
-                TokenType::Whitespace | TokenType::Newline | TokenType::Comment => {
-                    self.current += 1;
+    #[test]
+    fn test_parse_standalone_package_body() {
+        let input = "PACKAGE BODY Customer_API IS\n   default_name_ CONSTANT VARCHAR2(10) := 'Unknown';\n\n   FUNCTION Get_Name(id_ IN VARCHAR2) RETURN VARCHAR2 IS\n   BEGIN\n      RETURN default_name_;\n   END Get_Name;\n\n   @Override\n   PROCEDURE Check_Insert___(id_ IN VARCHAR2) IS\n   BEGIN\n      NULL;\n   END Check_Insert___;\nEND Customer_API;";
+        
+        if let AstNode::PlSql(PlSqlNode::Package { name, is_body, declarations, .. }) = parse_source(input, Language::PlSql).unwrap() {
+            assert_eq!(name.name, "Customer_API");
+            assert!(is_body);
+            assert_eq!(declarations.len(), 3);
+            assert!(matches!(&declarations[0], PlSqlDeclaration::Variable { name, .. } if name.name == "default_name_"));
+            match &declarations[1] {
+                PlSqlDeclaration::Subprogram(function) => {
+                    assert!(matches!(function.as_ref(), PlSqlNode::Function { name, body, .. } if name.name == "Get_Name" && body.len() == 1));
+                }
+                other => panic!("Expected subprogram, got {:?}", other),
+            }
+            match &declarations[2] {
+                PlSqlDeclaration::Subprogram(procedure) => {
+                    assert!(matches!(
+                        procedure.as_ref(),
+                        PlSqlNode::Procedure { name, annotations, .. }
+                            if name.name == "Check_Insert___" && annotations == &vec![IfsAnnotation::Override]
+                    ));
                 }
-                _ => break,
+                other => panic!("Expected subprogram, got {:?}", other),
             }
+        } else {
+            panic!("Expected package node");
         }
     }
     
-    fn skip_until_semicolon(&mut self) {
-        while !self.check(TokenType::Semicolon) && !self.is_at_end() {
-            self.advance();
-        }
-        if self.check(TokenType::Semicolon) {
-            self.advance();
-        }
+    #[test]
+    fn test_parse_forward_declarations_as_subprogram_specs() {
+        let body = "PACKAGE BODY Customer_API IS\n   PROCEDURE Check___(id_ IN VARCHAR2);\n   FUNCTION Get_Name(id_ IN VARCHAR2) RETURN VARCHAR2;\n\n   PROCEDURE Check___(id_ IN VARCHAR2) IS\n   BEGIN\n      NULL;\n   END Check___;\nEND Customer_API;";
+        let AstNode::PlSql(PlSqlNode::Package { declarations, .. }) = parse_source(body, Language::PlSql).unwrap() else {
+            panic!("Expected package node");
+        };
+        assert_eq!(declarations.len(), 3);
+        assert!(matches!(
+            &declarations[0],
+            PlSqlDeclaration::SubprogramSpec { name, parameters, return_type: None, .. }
+                if name.name == "Check___" && parameters.len() == 1
+        ));
+        assert!(matches!(
+            &declarations[1],
+            PlSqlDeclaration::SubprogramSpec { name, return_type: Some(return_type), .. }
+                if name.name == "Get_Name" && return_type.name == "VARCHAR2"
+        ));
+        assert!(matches!(&declarations[2], PlSqlDeclaration::Subprogram(_)));
+        
+        let spec = "PACKAGE Customer_API IS\n   PROCEDURE Check___(id_ IN VARCHAR2);\n   FUNCTION Get_Name(id_ IN VARCHAR2) RETURN VARCHAR2;\nEND Customer_API;";
+        let AstNode::PlSql(PlSqlNode::Package { declarations, .. }) = parse_source(spec, Language::PlSql).unwrap() else {
+            panic!("Expected package node");
+        };
+        assert_eq!(declarations.len(), 2);
+        assert!(declarations.iter().all(|declaration| matches!(declaration, PlSqlDeclaration::SubprogramSpec { .. })));
     }
     
-    fn skip_until_comma_or_paren(&mut self) {
-        while !self.check(TokenType::Comma) && !self.check(TokenType::RightParen) && !self.is_at_end() {
-            self.advance();
+    #[test]
+    fn test_parse_variable_initializers() {
+        let input = "PACKAGE BODY Order_API IS\n   count_ NUMBER := 0;\n   status_ VARCHAR2(30) DEFAULT 'x';\n   limit_ CONSTANT NUMBER NOT NULL := 10 * 2;\n   plain_ DATE;\n   no_data EXCEPTION;\nEND Order_API;";
+        
+        if let AstNode::PlSql(PlSqlNode::Package { declarations, .. }) = parse_source(input, Language::PlSql).unwrap() {
+            assert_eq!(declarations.len(), 5);
+            let variables: Vec<(&str, &str, Option<&Expression>)> = declarations
+                .iter()
+                .filter_map(|declaration| match declaration {
+                    PlSqlDeclaration::Variable { name, type_name, default_value, .. } => {
+                        Some((name.name.as_str(), type_name.name.as_str(), default_value.as_ref()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            
+            assert!(matches!(variables[0], ("count_", "NUMBER", Some(Expression::Literal { value, .. })) if value == "0"));
+            assert!(matches!(variables[1], ("status_", "VARCHAR2", Some(Expression::Literal { value, .. })) if value == "'x'"));
+            assert!(matches!(variables[2], ("limit_", "NUMBER", Some(Expression::Binary { .. }))));
+            assert!(matches!(variables[3], ("plain_", "DATE", None)));
+            assert!(matches!(&declarations[4], PlSqlDeclaration::Exception { name, .. } if name.name == "no_data"));
+        } else {
+            panic!("Expected package node");
         }
     }
     
-    fn current_position(&self) -> Position {
-        let token = self.peek();
-        Position {
-            line: token.position.line,
-            column: token.position.column,
-            offset: token.position.offset,
+    #[test]
+    fn test_parse_views_with_mixed_visibility() {
+        let input = "layer Core;\n\nPUBLIC COLUMN Customer_No IS\n   Flags    = 'KMI-L'\n   Datatype = 'STRING(20)/UPPERCASE'\n   Prompt   = 'Customer No';\n\nPRIVATE COLUMN Internal_Note IS\n   Flags    = 'A----'\n   Datatype = 'STRING(2000)';\n\n@SecurityCheck Company.UserAuthorized(company)\nVIEW Customer_Pub IS\n   Prompt = 'Customer'\n   Customer_No.Flags = 'K---L'\n   Customer_No.Datatype = 'STRING(20)'\n   PRIVATE Internal_Note.Prompt = 'Note'\nSELECT customer_no    customer_no,\n       internal_note  internal_note\nFROM   customer_tab;\n\nPRIVATE VIEW Customer_Internal IS\n   Name.Prompt = 'Name'\nSELECT name name\nFROM   customer_tab;\n";
+        
+        if let AstNode::Views(views) = parse_source(input, Language::Views).unwrap() {
+            assert_eq!(views.layer.as_deref(), Some("Core"));
+            let common: Vec<(&str, ViewVisibility)> =
+                views.column_definitions.iter().map(|c| (c.name.name.as_str(), c.visibility)).collect();
+            assert_eq!(common, vec![("Customer_No", ViewVisibility::Public), ("Internal_Note", ViewVisibility::Private)]);
+            assert_eq!(views.column_definitions[0].datatype.as_deref(), Some("STRING(20)/UPPERCASE"));
+            
+            assert_eq!(views.views.len(), 2);
+            let public_view = &views.views[0];
+            assert_eq!(public_view.name.name, "Customer_Pub");
+            assert_eq!(public_view.visibility, ViewVisibility::Public);
+            let columns: Vec<(&str, ViewVisibility)> =
+                public_view.columns.iter().map(|c| (c.name.name.as_str(), c.visibility)).collect();
+            assert_eq!(columns, vec![("Customer_No", ViewVisibility::Public), ("Internal_Note", ViewVisibility::Private)]);
+            assert_eq!(public_view.columns[0].flags.as_deref(), Some("K---L"));
+            assert_eq!(public_view.columns[0].datatype.as_deref(), Some("STRING(20)"));
+            assert_eq!(public_view.query.select.len(), 2);
+            assert_eq!(public_view.query.from[0].table.name, "customer_tab");
+            
+            let private_view = &views.views[1];
+            assert_eq!(private_view.visibility, ViewVisibility::Private);
+            assert_eq!(private_view.columns[0].visibility, ViewVisibility::Private);
+        } else {
+            panic!("Expected views node");
         }
     }
     
-    fn current_span(&self) -> Span {
-        let pos = self.current_position();
-        Span {
-            start: pos.clone(),
-            end: pos,
+    #[test]
+    fn test_parse_view_column_reference_target() {
+        let input = "COLUMN Customer_No IS\n   Flags = 'KMI-L'\n   Ref(Customer);\n\nCOLUMN Company IS\n   Ref = 'CompanyFinance(company)/NOCHECK';\n";
+        
+        if let AstNode::Views(views) = parse_source(input, Language::Views).unwrap() {
+            let references: Vec<Option<&str>> =
+                views.column_definitions.iter().map(|column| column.reference.as_deref()).collect();
+            assert_eq!(references, vec![Some("Customer"), Some("CompanyFinance")]);
+            assert_eq!(views.column_definitions[1].properties["Ref"], "CompanyFinance(company)/NOCHECK");
+        } else {
+            panic!("Expected views node");
         }
     }
     
-    /// Determine procedure/function visibility based on IFS naming convention
-    fn determine_visibility(&self, name: &str) -> ProcedureVisibility {
-        if name.ends_with("___") {
-            ProcedureVisibility::Private
-        } else if name.ends_with("__") {
-            ProcedureVisibility::Protected
-        } else {
-            ProcedureVisibility::Public
+    #[test]
+    fn test_trivia_between_tokens_does_not_change_parse() {
+        let plain = parse_procedure_body("PROCEDURE Touch IS BEGIN count_ := count_ + 1; Log_API.Write(count_); END;");
+        let commented = parse_procedure_body(
+            "PROCEDURE /* name */ Touch\n\nIS -- no declarations\nBEGIN\n   count_ /* target */ :=\n      count_ + -- step\n      1;\n\n   Log_API.Write( count_ ) ;\nEND;",
+        );
+        
+        assert_eq!(plain.len(), 2);
+        assert_eq!(commented.len(), 2);
+        assert_eq!(assignment_value(&plain[0]).to_string(), assignment_value(&commented[0]).to_string());
+        match (&plain[1], &commented[1]) {
+            (
+                PlSqlStatement::Call { name: a, arguments: x, .. },
+                PlSqlStatement::Call { name: b, arguments: y, .. },
+            ) => {
+                assert_eq!(a.name, b.name);
+                assert_eq!(x.len(), y.len());
+            }
+            other => panic!("Expected call statements, got {:?}", other),
         }
     }
+    
+    #[test]
+    fn test_parse_nested_block_with_exception_handler() {
+        let body = parse_procedure_body(
+            "PROCEDURE Check_Part IS\nBEGIN\n   DECLARE\n      dummy_ NUMBER;\n   BEGIN\n      SELECT 1 INTO dummy_ FROM part_tab;\n   EXCEPTION\n      WHEN no_data_found THEN\n         Error_SYS.Record_Not_Exist(lu_name_);\n   END;\n   Done___;\nEND;",
+        );
+        
+        assert_eq!(body.len(), 2);
+        let PlSqlStatement::Block { declarations, body: inner, exception_handlers, .. } = &body[0] else {
+            panic!("Expected nested block, got {:?}", body[0]);
+        };
+        assert!(matches!(&declarations[..], [PlSqlDeclaration::Variable { name, .. }] if name.name == "dummy_"));
+        assert!(matches!(&inner[..], [PlSqlStatement::SelectInto { .. }]));
+        assert_eq!(exception_handlers.len(), 1);
+        assert_eq!(exception_handlers[0].exceptions[0].name, "no_data_found");
+        assert!(matches!(&exception_handlers[0].body[..], [PlSqlStatement::Call { name, .. }] if name.name == "Error_SYS.Record_Not_Exist"));
+        assert!(matches!(&body[1], PlSqlStatement::Call { name, .. } if name.name == "Done___"));
+    }
+    
+    #[test]
+    fn test_parse_entity_code_gen_properties() {
+        let input = r#"entityname CustomerOrder;
+component ORDER;
+layer Core;
+codegenproperties {
+   Objversion "to_char(rowversion,'YYYYMMDDHH24MISS')";
+   DbTransient TRUE;
 }
-
-/// Convenience function to parse source code
-pub fn parse_source(input: &str, language: Language) -> Result<AstNode> {
-    let mut lexer = Lexer::new(input.to_string(), language);
-    let tokens = lexer.tokenize();
-    let mut parser = Parser::new(tokens, language);
-    parser.parse()
+attributes {
+   public key OrderNo TEXT(12)/KEY UPPERCASE;
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
+"#;
+        let AstNode::Entity(entity) = parse_source(input, Language::Entity).unwrap() else {
+            panic!("Expected entity node");
+        };
+        
+        assert_eq!(entity.entity_name.name, "CustomerOrder");
+        assert_eq!(entity.component, "ORDER");
+        let properties = &entity.code_gen_properties.expect("missing codegenproperties").properties;
+        assert_eq!(
+            properties.get("Objversion").map(String::as_str),
+            Some("to_char(rowversion,'YYYYMMDDHH24MISS')")
+        );
+        assert_eq!(properties.get("DbTransient").map(String::as_str), Some("TRUE"));
+    }
+    
     #[test]
-    fn test_parse_simple_package() {
-        let input = "PACKAGE test_pkg IS END;";
-        let result = parse_source(input, Language::PlSql);
+    fn test_parse_entity_state_machine() {
+        let input = "entityname ShopOrder;\nstatemachine {\n   initial state Planned {\n      event Release to Released;\n   }\n   state Released {\n      to Closed;\n   }\n   final state Closed;\n}\n";
+        let AstNode::Entity(entity) = parse_source(input, Language::Entity).unwrap() else {
+            panic!("Expected entity node");
+        };
         
-        assert!(result.is_ok());
-        if let AstNode::PlSql(PlSqlNode::Package { name, .. }) = result.unwrap() {
-            assert_eq!(name.name, "test_pkg");
-        } else {
-            panic!("Expected package node");
-        }
+        let state_machine = entity.state_machine.expect("missing statemachine");
+        let states: Vec<(&str, &StateType)> = state_machine
+            .states
+            .iter()
+            .map(|state| (state.name.name.as_str(), &state.state_type))
+            .collect();
+        assert_eq!(
+            states,
+            vec![("Planned", &StateType::Initial), ("Released", &StateType::Normal), ("Closed", &StateType::Final)]
+        );
+        assert_eq!(state_machine.transitions.len(), 2);
+        assert_eq!(state_machine.transitions[0].from_state.name, "Planned");
+        assert_eq!(state_machine.transitions[0].to_state.name, "Released");
+        assert_eq!(state_machine.transitions[0].event.as_deref(), Some("Release"));
+        assert_eq!(state_machine.transitions[1].event, None);
     }
     
     #[test]
-    fn test_parse_procedure() {
-        let input = "PROCEDURE test_proc IS BEGIN NULL; END;";
-        let result = parse_source(input, Language::PlSql);
+    fn test_parse_many_detects_language_and_keeps_errors() {
+        let inputs = vec![
+            (PathBuf::from("Customer.plsql"), "PROCEDURE Check_Customer IS BEGIN NULL; END;".to_string()),
+            (PathBuf::from("Status.enumeration"), "enumerationname Status;\nvalues {\n   value Open;\n}\n".to_string()),
+            (PathBuf::from("Broken.entity"), "component ORDER;\n".to_string()),
+        ];
         
-        assert!(result.is_ok());
-        if let AstNode::PlSql(PlSqlNode::Procedure { name, .. }) = result.unwrap() {
-            assert_eq!(name.name, "test_proc");
-        } else {
+        let results = parse_many(&inputs, None).unwrap();
+        let paths: Vec<&PathBuf> = results.iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, inputs.iter().map(|(path, _)| path).collect::<Vec<_>>());
+        assert!(matches!(&results[0].1, Ok(AstNode::PlSql(PlSqlNode::Procedure { .. }))));
+        assert!(matches!(&results[1].1, Ok(AstNode::Enumeration(_))));
+        assert!(results[2].1.is_err());
+    }
+    
+    #[test]
+    fn test_parse_many_stops_once_cancelled() {
+        let inputs = vec![(PathBuf::from("Customer.plsql"), "PROCEDURE Check_Customer IS BEGIN NULL; END;".to_string())];
+        let token = CancellationToken::new();
+        assert!(parse_many(&inputs, Some(&token)).is_ok());
+        
+        token.cancel();
+        let error = parse_many(&inputs, Some(&token)).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ParseError>(), Some(ParseError::Cancelled)));
+    }
+    
+    #[test]
+    fn test_parse_super_calls() {
+        let body = parse_procedure_body(
+            "PROCEDURE Check_Insert___ IS\nBEGIN\n   x_ := Super(a_);\n   super(a_, b_);\nEND Check_Insert___;",
+        );
+        
+        assert_eq!(body.len(), 2);
+        let Expression::FunctionCall { name, arguments, .. } = assignment_value(&body[0]) else {
+            panic!("Expected function call, got {:?}", body[0]);
+        };
+        assert!(name.is_super());
+        assert_eq!(name.span.start.line, 3);
+        assert_eq!(arguments.len(), 1);
+        
+        let PlSqlStatement::Call { name, arguments, .. } = &body[1] else {
+            panic!("Expected call statement, got {:?}", body[1]);
+        };
+        assert!(name.is_super());
+        assert_eq!(arguments.len(), 2);
+    }
+    
+    #[test]
+    fn test_parse_subprogram_pragmas() {
+        let input = "PROCEDURE Log_Error IS\n   PRAGMA AUTONOMOUS_TRANSACTION;\n   PRAGMA EXCEPTION_INIT(deadlock_, -60);\n   deadlock_ EXCEPTION;\nBEGIN\n   COMMIT;\nEND Log_Error;";
+        let AstNode::PlSql(PlSqlNode::Procedure { pragmas, .. }) = parse_source(input, Language::PlSql).unwrap() else {
             panic!("Expected procedure node");
+        };
+        
+        assert_eq!(pragmas.len(), 2);
+        assert!(pragmas[0].is_autonomous_transaction());
+        assert!(pragmas[0].arguments.is_empty());
+        assert_eq!(pragmas[0].span.start.line, 2);
+        assert_eq!(pragmas[1].name.name, "EXCEPTION_INIT");
+        assert_eq!(pragmas[1].arguments.len(), 2);
+        assert!(!pragmas[1].is_autonomous_transaction());
+    }
+    
+    #[test]
+    fn test_parse_subprogram_keeps_local_declarations() {
+        let input = "PROCEDURE Outer IS\n   count_ NUMBER := 0;\n   CURSOR get_rows IS SELECT 1 FROM dual;\n   PRAGMA AUTONOMOUS_TRANSACTION;\n   PROCEDURE Inner___ IS\n      local_ VARCHAR2(10);\n   BEGIN\n      NULL;\n   END Inner___;\nBEGIN\n   Inner___;\nEND Outer;";
+        for options in [ParseOptions::default(), ParseOptions::outline()] {
+            let AstNode::PlSql(PlSqlNode::Procedure { declarations, pragmas, .. }) =
+                parse_source_with_options(input, Language::PlSql, options).unwrap()
+            else {
+                panic!("Expected procedure node");
+            };
+            
+            assert_eq!(pragmas.len(), 1);
+            assert_eq!(declarations.len(), 3);
+            assert!(matches!(&declarations[0], PlSqlDeclaration::Variable { name, .. } if name.name == "count_"));
+            assert!(matches!(&declarations[1], PlSqlDeclaration::Cursor { name, .. } if name.name == "get_rows"));
+            let PlSqlDeclaration::Subprogram(inner) = &declarations[2] else {
+                panic!("Expected nested procedure, got {:?}", declarations[2]);
+            };
+            let PlSqlNode::Procedure { name, declarations, .. } = inner.as_ref() else {
+                panic!("Expected procedure node");
+            };
+            assert_eq!(name.name, "Inner___");
+            assert!(matches!(&declarations[..], [PlSqlDeclaration::Variable { name, .. }] if name.name == "local_"));
         }
     }
+    
+    #[test]
+    fn test_parse_sized_type_parameters() {
+        let input = "PROCEDURE Sized(\n   a_ IN VARCHAR2(30),\n   b_ IN NUMBER(10, 2),\n   c_ IN VARCHAR2(30 CHAR)) IS\nBEGIN\n   DECLARE\n      d_ NUMBER(5) := 0;\n   BEGIN\n      NULL;\n   END;\nEND;";
+        
+        let AstNode::PlSql(PlSqlNode::Procedure { parameters, body, .. }) = parse_source(input, Language::PlSql).unwrap() else {
+            panic!("Expected procedure node");
+        };
+        let types: Vec<(&str, Vec<&str>)> = parameters
+            .iter()
+            .map(|p| (p.param_type.name.as_str(), p.param_type.parameters.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("VARCHAR2", vec!["30"]),
+                ("NUMBER", vec!["10", "2"]),
+                ("VARCHAR2", vec!["30 CHAR"]),
+            ]
+        );
+        assert_eq!(parameters[1].param_type.to_string(), "NUMBER(10,2)");
+        
+        let PlSqlStatement::Block { declarations, .. } = &body[0] else {
+            panic!("Expected block, got {:?}", body[0]);
+        };
+        let PlSqlDeclaration::Variable { type_name, default_value, .. } = &declarations[0] else {
+            panic!("Expected variable declaration, got {:?}", declarations[0]);
+        };
+        assert_eq!(type_name.to_string(), "NUMBER(5)");
+        assert!(default_value.is_some());
+    }
+    
+    #[test]
+    fn test_parse_for_update_cursor_and_bulk_collect_fetch() {
+        let input = "PACKAGE BODY Order_API IS\n   CURSOR get_lines IS\n      SELECT line_no FROM order_line_tab WHERE order_no = 'A' FOR UPDATE NOWAIT;\n   CURSOR get_all RETURN order_line_tab%ROWTYPE;\n   PROCEDURE Process IS\n   BEGIN\n      OPEN get_lines;\n      FETCH get_lines BULK COLLECT INTO lines_ LIMIT 500;\n      FETCH get_lines INTO line_;\n      CLOSE get_lines;\n   END Process;\nEND Order_API;";
+        
+        let AstNode::PlSql(PlSqlNode::Package { declarations, .. }) = parse_source(input, Language::PlSql).unwrap() else {
+            panic!("Expected package node");
+        };
+        let PlSqlDeclaration::Cursor { name, query, for_update, .. } = &declarations[0] else {
+            panic!("Expected cursor declaration, got {:?}", declarations[0]);
+        };
+        assert_eq!(name.name, "get_lines");
+        assert!(query.starts_with("SELECT line_no") && query.ends_with("FOR UPDATE NOWAIT"), "{}", query);
+        assert!(*for_update);
+        assert!(matches!(&declarations[1], PlSqlDeclaration::Cursor { for_update: false, query, .. } if query.is_empty()));
+        
+        let PlSqlDeclaration::Subprogram(procedure) = &declarations[2] else {
+            panic!("Expected subprogram, got {:?}", declarations[2]);
+        };
+        let PlSqlNode::Procedure { body, .. } = procedure.as_ref() else {
+            panic!("Expected procedure");
+        };
+        assert_eq!(body.len(), 2);
+        let PlSqlStatement::Fetch { cursor, into, bulk_collect, limit, .. } = &body[0] else {
+            panic!("Expected FETCH, got {:?}", body[0]);
+        };
+        assert_eq!(cursor.name, "get_lines");
+        assert_eq!(into[0].name, "lines_");
+        assert!(*bulk_collect);
+        assert!(matches!(limit, Some(Expression::Literal { value, .. }) if value == "500"));
+        assert!(matches!(&body[1], PlSqlStatement::Fetch { bulk_collect: false, limit: None, .. }));
+    }
+    
+    #[test]
+    fn test_package_without_as_or_is_names_both() {
+        let error = parse_source("PACKAGE Customer_API\nBEGIN\n   NULL;\nEND Customer_API;", Language::PlSql).unwrap_err();
+        let message = error.to_string();
+        
+        assert!(message.contains("expected AS or IS after package name"), "{}", message);
+        assert!(message.contains("found 'BEGIN' at line 2"), "{}", message);
+    }
+    
+    #[test]
+    fn test_annotation_attaches_across_comments_and_blank_lines() {
+        let input = "@Override\n\n-- Validate the new row before insert\nPROCEDURE Check_Insert___ IS\nBEGIN\n   NULL;\nEND;";
+        let AstNode::PlSql(PlSqlNode::Procedure { name, annotations, .. }) = parse_source(input, Language::PlSql).unwrap() else {
+            panic!("Expected procedure node");
+        };
+        assert_eq!(name.name, "Check_Insert___");
+        assert_eq!(annotations, vec![IfsAnnotation::Override]);
+        
+        let input = "PACKAGE BODY Customer_API IS\n   @Overtake\n   /* Core version */\n\n   FUNCTION Get_Name RETURN VARCHAR2 IS\n   BEGIN\n      RETURN NULL;\n   END Get_Name;\nEND Customer_API;";
+        let AstNode::PlSql(PlSqlNode::Package { declarations, .. }) = parse_source(input, Language::PlSql).unwrap() else {
+            panic!("Expected package node");
+        };
+        assert!(matches!(
+            &declarations[0],
+            PlSqlDeclaration::Subprogram(function) if matches!(function.as_ref(), PlSqlNode::Function { annotations, .. } if annotations == &vec![IfsAnnotation::Overtake])
+        ));
+    }
+    
+    #[test]
+    fn test_stray_annotation_is_an_error() {
+        let input = "PACKAGE BODY Customer_API IS\n   @Override\n   count_ NUMBER;\nEND Customer_API;";
+        let message = parse_source(input, Language::PlSql).unwrap_err().to_string();
+        assert!(message.contains("expected PROCEDURE or FUNCTION after annotation"), "{}", message);
+        assert!(message.contains("found 'count_'"), "{}", message);
+    }
+    
+    #[test]
+    fn test_multiple_procedures_parse_into_source_file() {
+        let input = "PROCEDURE First_ IS\nBEGIN\n   NULL;\nEND First_;\n\nPROCEDURE Second_ IS\nBEGIN\n   NULL;\nEND Second_;";
+        let AstNode::SourceFile { path, children, .. } = parse_source(input, Language::PlSql).unwrap() else {
+            panic!("expected a source file");
+        };
+        assert_eq!(path, None);
+        let names: Vec<&str> = children
+            .iter()
+            .map(|child| match child {
+                AstNode::PlSql(PlSqlNode::Procedure { name, .. }) => name.name.as_str(),
+                other => panic!("expected a procedure, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(names, ["First_", "Second_"]);
+        
+        // A single unit is still returned unwrapped
+        let single = parse_source("PROCEDURE First_ IS\nBEGIN\n   NULL;\nEND First_;", Language::PlSql).unwrap();
+        assert!(matches!(single, AstNode::PlSql(PlSqlNode::Procedure { .. })));
+    }
+    
+    #[test]
+    fn test_conditional_compilation_block() {
+        let input = "PROCEDURE Trace_ IS\nBEGIN\n   $IF $$DEBUG $THEN\n      Log_SYS.Trace($$PLSQL_UNIT);\n   $ELSE\n      NULL;\n   $END\n   Done_;\nEND Trace_;";
+        let AstNode::PlSql(PlSqlNode::Procedure { body, .. }) = parse_source(input, Language::PlSql).unwrap() else {
+            panic!("expected a procedure");
+        };
+        assert_eq!(body.len(), 2);
+        
+        let PlSqlStatement::If { condition, then_branch, else_branch, .. } = &body[0] else {
+            panic!("expected a conditional block, got {:?}", body[0]);
+        };
+        assert!(matches!(condition, Expression::Literal { value, .. } if value == "$$DEBUG"));
+        let PlSqlStatement::Call { arguments, .. } = &then_branch[0] else {
+            panic!("expected a call, got {:?}", then_branch[0]);
+        };
+        assert!(matches!(&arguments[0], Expression::Literal { value, .. } if value == "$$PLSQL_UNIT"));
+        assert!(else_branch.is_some());
+        assert!(matches!(&body[1], PlSqlStatement::Call { name, .. } if name.name == "Done_"));
+    }
+    
+    #[test]
+    fn test_tokenize_filters_trivia() {
+        let source = "PROCEDURE Run_ IS -- entry point\nBEGIN\n   NULL;\nEND Run_;";
+        let all = tokenize(source, Language::PlSql, true);
+        let significant = tokenize(source, Language::PlSql, false);
+        
+        assert!(all.iter().any(|token| token.token_type == TokenType::Whitespace));
+        assert!(significant.iter().all(|token| !token.is_trivia()));
+        assert_eq!(significant.len(), all.iter().filter(|token| !token.is_trivia()).count());
+        
+        let values: Vec<&str> = significant.iter().map(|token| token.value.as_str()).collect();
+        assert_eq!(values, ["PROCEDURE", "Run_", "IS", "BEGIN", "NULL", ";", "END", "Run_", ";", ""]);
+    }
 }
@@ -1,6 +1,19 @@
 use tree_sitter::{Parser, Node};
 use crate::parser::ast::*;
+use crate::utils::file_utils::content_hash;
 use anyhow::{anyhow, Result};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+
+/// A source range tree-sitter could not parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorRange {
+    pub span: Span,
+    /// True when tree-sitter inserted a missing node rather than skipping unparseable text
+    pub is_missing: bool,
+    /// Kind of the error node, or the kind of the node that was expected when missing
+    pub node_kind: String,
+}
 
 pub struct TreeSitterParser {
     parser: Parser,
@@ -16,14 +29,62 @@ impl TreeSitterParser {
         Ok(Self { parser })
     }
 
+    /// Give up on a parse after `timeout_micros` microseconds; zero removes the limit
+    pub fn set_timeout_micros(&mut self, timeout_micros: u64) {
+        self.parser.set_timeout_micros(timeout_micros);
+    }
+
+    /// Error for a parse that returned no tree. A parse stopped by the timeout would resume
+    /// on the next call, so the parser is reset to start the next source from scratch.
+    fn parse_failure(&mut self) -> anyhow::Error {
+        self.parser.reset();
+        match self.parser.timeout_micros() {
+            0 => anyhow!("Failed to parse source"),
+            timeout_micros => anyhow!("Parse did not finish within {} microseconds", timeout_micros),
+        }
+    }
+
     pub fn parse(&mut self, source: &str) -> Result<AstNode> {
-        let tree = self.parser.parse(source, None)
-            .ok_or_else(|| anyhow!("Failed to parse source"))?;
+        let Some(tree) = self.parser.parse(source, None) else {
+            return Err(self.parse_failure());
+        };
         
         let root_node = tree.root_node();
         self.convert_node(&root_node, source)
     }
 
+    /// Parse and also report every error or missing node in the tree
+    pub fn parse_with_errors(&mut self, source: &str) -> Result<(AstNode, Vec<ErrorRange>)> {
+        let Some(tree) = self.parser.parse(source, None) else {
+            return Err(self.parse_failure());
+        };
+
+        let root_node = tree.root_node();
+        let mut errors = Vec::new();
+        if root_node.has_error() {
+            self.collect_errors(&root_node, &mut errors);
+        }
+
+        Ok((self.convert_node(&root_node, source)?, errors))
+    }
+
+    fn collect_errors(&self, node: &Node, errors: &mut Vec<ErrorRange>) {
+        if node.is_error() || node.is_missing() {
+            errors.push(ErrorRange {
+                span: self.node_to_span(node),
+                is_missing: node.is_missing(),
+                node_kind: node.kind().to_string(),
+            });
+            return;
+        }
+
+        for child in node.children(&mut node.walk()) {
+            if child.has_error() {
+                self.collect_errors(&child, errors);
+            }
+        }
+    }
+
     fn convert_node(&self, node: &Node, source: &str) -> Result<AstNode> {
         match node.kind() {
             "source_file" => {
@@ -56,6 +117,7 @@ impl TreeSitterParser {
 
         Ok(AstNode::PlSql(PlSqlNode::Package {
             name,
+            is_body: false,
             component: None,
             annotations: Vec::new(),
             declarations: Vec::new(),
@@ -96,6 +158,7 @@ impl TreeSitterParser {
         
         Ok(AstNode::PlSql(PlSqlNode::Package {
             name,
+            is_body: false,
             component,
             annotations,
             declarations,
@@ -140,6 +203,10 @@ impl TreeSitterParser {
             annotations,
             parameters,
             body,
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
             span: self.node_to_span(node),
         }))
     }
@@ -187,7 +254,13 @@ impl TreeSitterParser {
             annotations,
             parameters,
             return_type,
+            is_pipelined: false,
+            result_cached: false,
             body,
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
             span: self.node_to_span(node),
         }))
     }
@@ -252,18 +325,126 @@ impl TreeSitterParser {
     }
 }
 
+/// Hit and miss counts of the parsed-tree cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
 pub struct IfsPlsqlParser {
     tree_sitter: TreeSitterParser,
+    /// Parse results keyed by content hash, when caching is enabled
+    cache: Option<LruCache<String, (AstNode, Vec<ErrorRange>)>>,
+    hits: u64,
+    misses: u64,
 }
 
 impl IfsPlsqlParser {
     pub fn new() -> Result<Self> {
         Ok(Self {
             tree_sitter: TreeSitterParser::new()?,
+            cache: None,
+            hits: 0,
+            misses: 0,
         })
     }
 
+    /// Keep the results of the last `capacity` distinct sources, evicting the
+    /// least recently used. A capacity of zero disables the cache.
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache = NonZeroUsize::new(capacity).map(LruCache::new);
+        self
+    }
+
+    /// Fail parses that take longer than `timeout_micros` microseconds instead of
+    /// letting one pathological input block the caller
+    pub fn with_timeout_micros(mut self, timeout_micros: u64) -> Self {
+        self.tree_sitter.set_timeout_micros(timeout_micros);
+        self
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.cache.as_ref().map_or(0, |cache| cache.len()),
+            capacity: self.cache.as_ref().map_or(0, |cache| cache.cap().get()),
+        }
+    }
+
     pub fn parse(&mut self, input: &str) -> Result<AstNode> {
-        self.tree_sitter.parse(input)
+        if self.cache.is_none() {
+            return self.tree_sitter.parse(input);
+        }
+        Ok(self.parse_with_errors(input)?.0)
+    }
+
+    pub fn parse_with_errors(&mut self, input: &str) -> Result<(AstNode, Vec<ErrorRange>)> {
+        let Some(cache) = self.cache.as_mut() else {
+            return self.tree_sitter.parse_with_errors(input);
+        };
+
+        let key = content_hash(input.as_bytes());
+        if let Some(cached) = cache.get(&key) {
+            self.hits += 1;
+            return Ok(cached.clone());
+        }
+
+        self.misses += 1;
+        let result = self.tree_sitter.parse_with_errors(input)?;
+        cache.put(key, result.clone());
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_errors_reports_broken_input() {
+        let mut parser = IfsPlsqlParser::new().unwrap();
+        let source = "PROCEDURE Broken IS\nBEGIN\n   x_ := ;\n   IF THEN\nEND;\n";
+
+        let (_, errors) = parser.parse_with_errors(source).unwrap();
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|e| e.span.start.offset <= e.span.end.offset && e.span.end.offset <= source.len()));
+    }
+
+    #[test]
+    fn test_parse_with_errors_is_empty_for_valid_input() {
+        let mut parser = IfsPlsqlParser::new().unwrap();
+        let source = "PROCEDURE Valid IS\nBEGIN\n   NULL;\nEND Valid;\n";
+
+        let (_, errors) = parser.parse_with_errors(source).unwrap();
+        assert_eq!(errors, Vec::new());
+    }
+
+    #[test]
+    fn test_timeout_fails_slow_parse() {
+        let mut parser = IfsPlsqlParser::new().unwrap().with_timeout_micros(1);
+        let source = format!("PROCEDURE Slow IS\nBEGIN\n{}END Slow;\n", "   NULL;\n".repeat(200_000));
+
+        let error = parser.parse(&source).unwrap_err();
+        assert!(error.to_string().contains("did not finish"), "{}", error);
+    }
+
+    #[test]
+    fn test_cache_hits_identical_content_only() {
+        let mut parser = IfsPlsqlParser::new().unwrap().with_cache(4);
+        let source = "PROCEDURE Valid IS\nBEGIN\n   NULL;\nEND Valid;\n";
+
+        let first = parser.parse(source).unwrap();
+        let second = parser.parse(source).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(parser.cache_stats().hits, 1);
+        assert_eq!(parser.cache_stats().misses, 1);
+
+        parser.parse("PROCEDURE Changed IS\nBEGIN\n   NULL;\nEND Changed;\n").unwrap();
+        let stats = parser.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries, stats.capacity), (1, 2, 2, 4));
     }
 }
@@ -0,0 +1,45 @@
+// Refactoring support built on the index
+//
+// This module computes source edits for refactorings such as renaming a
+// symbol. Edits are returned to the caller; files are never modified here.
+
+pub mod rename;
+
+pub use rename::*;
+
+use crate::parser::ast::Span;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Replace the text covered by `span` with `new_text`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub span: Span,
+    pub new_text: String,
+}
+
+/// Apply `edits` to `text` by byte offset. Edits must not overlap.
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    sorted.sort_by_key(|edit| edit.span.start.offset);
+    
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for edit in sorted {
+        result.push_str(&text[copied..edit.span.start.offset]);
+        result.push_str(&edit.new_text);
+        copied = edit.span.end.offset;
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+/// Errors that prevent a refactoring from being computed
+#[derive(Debug, Error, PartialEq)]
+pub enum RefactorError {
+    #[error("Invalid identifier: {name}")]
+    InvalidIdentifier { name: String },
+    
+    #[error("Symbol {name} is not stored in the index")]
+    SymbolNotIndexed { name: String },
+}
@@ -0,0 +1,249 @@
+// Rename a symbol across every indexed file
+
+use crate::index::search::SymbolSearcher;
+use crate::index::symbols::SymbolInfo;
+use crate::parser::ast::{Position, Span};
+use crate::refactor::{RefactorError, TextEdit};
+use crate::Result;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Longest identifier Oracle accepts
+const MAX_IDENTIFIER_LENGTH: usize = 128;
+
+/// Words that cannot be used as unquoted identifiers
+const RESERVED_WORDS: &[&str] = &[
+    "begin", "declare", "else", "elsif", "end", "exception", "function", "if", "is", "loop",
+    "null", "package", "procedure", "return", "select", "then", "when", "where", "while",
+];
+
+/// Compute the edits that rename `symbol` and every stored reference to it.
+/// The trailing-underscore visibility suffix of the old name is kept unless
+/// `new_name` ends with an underscore itself.
+pub fn rename_edits(
+    searcher: &SymbolSearcher,
+    symbol: &SymbolInfo,
+    new_name: &str,
+) -> Result<Vec<(PathBuf, Vec<TextEdit>)>> {
+    if symbol.id.is_none() {
+        return Err(RefactorError::SymbolNotIndexed { name: symbol.name.clone() }.into());
+    }
+    validate_identifier(new_name)?;
+    let new_name = with_visibility_suffix(&symbol.name, new_name);
+    
+    let mut edits: BTreeMap<PathBuf, Vec<TextEdit>> = BTreeMap::new();
+    edits
+        .entry(PathBuf::from(&symbol.file_path))
+        .or_default()
+        .push(TextEdit { span: symbol.span.clone(), new_text: new_name.clone() });
+    
+    for reference in searcher.find_references(symbol)? {
+        let span = last_segment_span(&reference.span, &symbol.name);
+        edits
+            .entry(PathBuf::from(reference.file_path))
+            .or_default()
+            .push(TextEdit { span, new_text: new_name.clone() });
+    }
+    
+    Ok(edits
+        .into_iter()
+        .map(|(path, mut file_edits)| {
+            file_edits.sort_by_key(|edit| edit.span.start.offset);
+            file_edits.dedup();
+            (path, file_edits)
+        })
+        .collect())
+}
+
+/// Check that `name` can be used as an identifier, either unquoted or quoted
+pub fn validate_identifier(name: &str) -> Result<()> {
+    let invalid = || RefactorError::InvalidIdentifier { name: name.to_string() }.into();
+    
+    if let Some(quoted) = name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        if quoted.is_empty() || quoted.contains('"') || quoted.len() > MAX_IDENTIFIER_LENGTH {
+            return Err(invalid());
+        }
+        return Ok(());
+    }
+    
+    let mut chars = name.chars();
+    let starts_with_letter = chars.next().is_some_and(|c| c.is_ascii_alphabetic());
+    let rest_is_valid = chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '#'));
+    let is_reserved = RESERVED_WORDS.iter().any(|word| word.eq_ignore_ascii_case(name));
+    
+    if !starts_with_letter || !rest_is_valid || is_reserved || name.len() > MAX_IDENTIFIER_LENGTH {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// Carry the old name's trailing underscores (`__` protected, `___` private) over to the new name.
+/// For a quoted new name the suffix goes inside the quotes.
+fn with_visibility_suffix(old_name: &str, new_name: &str) -> String {
+    let old_name = unquote_identifier(old_name);
+    let suffix = &old_name[old_name.trim_end_matches('_').len()..];
+    match new_name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        Some(quoted) if quoted.ends_with('_') => new_name.to_string(),
+        Some(quoted) => format!("\"{}{}\"", quoted, suffix),
+        None if new_name.ends_with('_') => new_name.to_string(),
+        None => format!("{}{}", new_name, suffix),
+    }
+}
+
+/// The name inside `"..."`, or `name` itself when it is not quoted
+fn unquote_identifier(name: &str) -> &str {
+    name.strip_prefix('"').and_then(|n| n.strip_suffix('"')).unwrap_or(name)
+}
+
+/// References may be qualified (`Customer_API.Get_Name`); only the final segment names the symbol.
+/// The range is taken from byte offsets; the column is only adjusted by the name's character count.
+fn last_segment_span(span: &Span, name: &str) -> Span {
+    let start_offset = span.end.offset.saturating_sub(name.len()).max(span.start.offset);
+    let start = if start_offset == span.start.offset {
+        span.start.clone()
+    } else {
+        Position {
+            line: span.end.line,
+            column: span.end.column.saturating_sub(name.chars().count()),
+            offset: start_offset,
+        }
+    };
+    Span { start, end: span.end.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Index;
+    use crate::parser::ast::*;
+    use crate::parser::{parse_source, Language};
+    use crate::refactor::apply_edits;
+    
+    fn span(line: usize, column: usize, offset: usize, len: usize) -> Span {
+        Span {
+            start: Position { line, column, offset },
+            end: Position { line, column: column + len, offset: offset + len },
+        }
+    }
+    
+    fn procedure(name: Identifier, body: Vec<PlSqlStatement>) -> AstNode {
+        AstNode::PlSql(PlSqlNode::Procedure {
+            span: name.span.clone(),
+            name,
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: Vec::new(),
+            body,
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+        })
+    }
+    
+    fn call(name: &str, line: usize, column: usize, offset: usize) -> PlSqlStatement {
+        let name_span = span(line, column, offset, name.len());
+        PlSqlStatement::Call {
+            name: Identifier { name: name.to_string(), span: name_span.clone() },
+            arguments: Vec::new(),
+            span: name_span,
+            trivia: None,
+        }
+    }
+    
+    #[test]
+    fn test_rename_procedure_with_references() {
+        let mut index = Index::in_memory().unwrap();
+        let definition = Identifier { name: "Do_Work__".to_string(), span: span(1, 11, 10, 9) };
+        index.index_file("work.plsql", &procedure(definition, Vec::new())).unwrap();
+        
+        let caller = Identifier { name: "Run".to_string(), span: span(1, 11, 10, 3) };
+        let body = vec![call("Do_Work__", 3, 4, 30), call("do_work__", 4, 4, 44)];
+        index.index_file("run.plsql", &procedure(caller, body)).unwrap();
+        
+        let symbol = index
+            .search_symbols("Do_Work__")
+            .unwrap()
+            .into_iter()
+            .find(|s| s.name == "Do_Work__")
+            .unwrap();
+        let edits = index.rename_symbol(&symbol, "Process_Work").unwrap();
+        
+        let flattened: Vec<(&str, usize, usize, &str)> = edits
+            .iter()
+            .flat_map(|(path, file_edits)| {
+                file_edits.iter().map(move |edit| {
+                    (path.to_str().unwrap(), edit.span.start.offset, edit.span.end.offset, edit.new_text.as_str())
+                })
+            })
+            .collect();
+        assert_eq!(
+            flattened,
+            vec![
+                ("run.plsql", 30, 39, "Process_Work__"),
+                ("run.plsql", 44, 53, "Process_Work__"),
+                ("work.plsql", 10, 19, "Process_Work__"),
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_rename_parsed_source_rewrites_names() {
+        let work = "PACKAGE BODY Work_API IS\n\
+            PROCEDURE Do_Work__ IS\nBEGIN\n   NULL;\nEND;\n\
+            PROCEDURE Retry IS\nBEGIN\n   Do_Work__;\nEND;\n\
+            END Work_API;\n";
+        let run = "PROCEDURE Run IS\nBEGIN\n   Work_API.Do_Work__(1);\nEND;\n";
+        let mut index = Index::in_memory().unwrap();
+        index.index_file("work.plsql", &parse_source(work, Language::PlSql).unwrap()).unwrap();
+        index.index_file("run.plsql", &parse_source(run, Language::PlSql).unwrap()).unwrap();
+        
+        let symbol = index
+            .search_symbols("Do_Work__")
+            .unwrap()
+            .into_iter()
+            .find(|s| s.name == "Do_Work__")
+            .unwrap();
+        let edits: BTreeMap<PathBuf, Vec<TextEdit>> =
+            index.rename_symbol(&symbol, "Process_Work").unwrap().into_iter().collect();
+        
+        assert_eq!(
+            apply_edits(work, &edits[&PathBuf::from("work.plsql")]),
+            "PACKAGE BODY Work_API IS\n\
+             PROCEDURE Process_Work__ IS\nBEGIN\n   NULL;\nEND;\n\
+             PROCEDURE Retry IS\nBEGIN\n   Process_Work__;\nEND;\n\
+             END Work_API;\n"
+        );
+        assert_eq!(
+            apply_edits(run, &edits[&PathBuf::from("run.plsql")]),
+            "PROCEDURE Run IS\nBEGIN\n   Work_API.Process_Work__(1);\nEND;\n"
+        );
+    }
+    
+    #[test]
+    fn test_last_segment_of_qualified_reference() {
+        let qualified = span(2, 4, 20, "Work_API.Do_Work".len());
+        let edited = last_segment_span(&qualified, "Do_Work");
+        assert_eq!(edited.start, Position { line: 2, column: 13, offset: 29 });
+        assert_eq!(edited.end, qualified.end);
+    }
+    
+    #[test]
+    fn test_visibility_suffix_is_preserved_unless_given() {
+        assert_eq!(with_visibility_suffix("Do_Work__", "New_Name"), "New_Name__");
+        assert_eq!(with_visibility_suffix("Do_Work__", "New_Name_"), "New_Name_");
+        assert_eq!(with_visibility_suffix("Do_Work", "New_Name"), "New_Name");
+        assert_eq!(with_visibility_suffix("Do_Work__", "\"New Name\""), "\"New Name__\"");
+        assert_eq!(with_visibility_suffix("\"Do_Work__\"", "New_Name"), "New_Name__");
+    }
+    
+    #[test]
+    fn test_validate_identifier() {
+        assert!(validate_identifier("Customer_API").is_ok());
+        assert!(validate_identifier("\"Mixed Case\"").is_ok());
+        assert!(validate_identifier("1st_name").is_err());
+        assert!(validate_identifier("has space").is_err());
+        assert!(validate_identifier("BEGIN").is_err());
+        assert!(validate_identifier("").is_err());
+    }
+}
@@ -2,7 +2,10 @@
 
 use crate::parser::ast::Span;
 use crate::static_analysis::rules::{RuleViolation, Severity};
+use crate::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 
 /// A diagnostic message from static analysis
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -22,6 +25,48 @@ pub struct DiagnosticRelatedInformation {
     pub message: String,
 }
 
+impl Diagnostic {
+    /// Convert to an LSP `Diagnostic` object for the document at `uri`,
+    /// using `source` to compute 0-based UTF-16 ranges
+    pub fn to_lsp_json(&self, uri: &str, source: &str) -> serde_json::Value {
+        let mut diagnostic = serde_json::json!({
+            "range": self.span.to_lsp_range(source),
+            "severity": self.severity.to_lsp_severity(),
+            "source": self.source,
+            "message": self.message,
+        });
+        if let Some(code) = &self.code {
+            diagnostic["code"] = serde_json::json!(code);
+        }
+        if !self.related_information.is_empty() {
+            let related: Vec<serde_json::Value> = self
+                .related_information
+                .iter()
+                .map(|related| {
+                    serde_json::json!({
+                        "location": { "uri": uri, "range": related.span.to_lsp_range(source) },
+                        "message": related.message,
+                    })
+                })
+                .collect();
+            diagnostic["relatedInformation"] = serde_json::Value::Array(related);
+        }
+        diagnostic
+    }
+}
+
+/// Build an LSP `PublishDiagnosticsParams` object for the document at `uri`
+pub fn publish_diagnostics_params(uri: &str, diagnostics: &[Diagnostic], source: &str) -> serde_json::Value {
+    let diagnostics: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_lsp_json(uri, source))
+        .collect();
+    serde_json::json!({
+        "uri": uri,
+        "diagnostics": diagnostics,
+    })
+}
+
 impl From<RuleViolation> for Diagnostic {
     fn from(violation: RuleViolation) -> Self {
         Self {
@@ -36,9 +81,12 @@ impl From<RuleViolation> for Diagnostic {
 }
 
 /// Collection of diagnostics with utility methods
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct DiagnosticCollection {
     diagnostics: Vec<Diagnostic>,
+    /// Content hash of the analyzed file, used to detect stale cached results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_hash: Option<String>,
 }
 
 impl DiagnosticCollection {
@@ -93,6 +141,47 @@ impl DiagnosticCollection {
         });
     }
     
+    /// Record the content hash of the file these diagnostics belong to
+    pub fn set_file_hash(&mut self, hash: impl Into<String>) {
+        self.file_hash = Some(hash.into());
+    }
+    
+    /// Get the content hash of the analyzed file, if recorded
+    pub fn file_hash(&self) -> Option<&str> {
+        self.file_hash.as_deref()
+    }
+    
+    /// Check whether these diagnostics were produced for content with a different hash
+    pub fn is_stale(&self, current_hash: &str) -> bool {
+        self.file_hash.as_deref() != Some(current_hash)
+    }
+    
+    /// Serialize the collection, including its file hash, to JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+    
+    /// Restore a collection previously written by `to_json`
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+    
+    /// Count diagnostics per severity and per rule code
+    pub fn summary(&self) -> DiagnosticSummary {
+        let mut summary = DiagnosticSummary::default();
+        for diagnostic in &self.diagnostics {
+            match diagnostic.severity {
+                Severity::Error => summary.errors += 1,
+                Severity::Warning => summary.warnings += 1,
+                Severity::Info => summary.info += 1,
+                Severity::Hint => summary.hints += 1,
+            }
+            let code = diagnostic.code.clone().unwrap_or_else(|| "-".to_string());
+            *summary.by_code.entry(code).or_insert(0) += 1;
+        }
+        summary
+    }
+    
     /// Filter diagnostics by a predicate
     pub fn filter<F>(&self, predicate: F) -> Vec<&Diagnostic>
     where
@@ -106,6 +195,7 @@ impl FromIterator<Diagnostic> for DiagnosticCollection {
     fn from_iter<T: IntoIterator<Item = Diagnostic>>(iter: T) -> Self {
         Self {
             diagnostics: iter.into_iter().collect(),
+            file_hash: None,
         }
     }
 }
@@ -119,6 +209,46 @@ impl IntoIterator for DiagnosticCollection {
     }
 }
 
+/// Diagnostic counts per severity and per rule code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiagnosticSummary {
+    pub errors: usize,
+    pub warnings: usize,
+    pub info: usize,
+    pub hints: usize,
+    /// Count per rule code; diagnostics without a code are counted under `-`
+    pub by_code: BTreeMap<String, usize>,
+}
+
+impl DiagnosticSummary {
+    /// Total number of diagnostics
+    pub fn total(&self) -> usize {
+        self.errors + self.warnings + self.info + self.hints
+    }
+}
+
+impl fmt::Display for DiagnosticSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} diagnostics ({} errors, {} warnings, {} info, {} hints)",
+            self.total(),
+            self.errors,
+            self.warnings,
+            self.info,
+            self.hints
+        )?;
+        
+        let mut codes: Vec<(&String, &usize)> = self.by_code.iter().collect();
+        codes.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let width = codes.iter().map(|(code, _)| code.len()).max().unwrap_or(0);
+        for (code, count) in codes {
+            writeln!(f, "  {:<width$}  {:>5}", code, count, width = width)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,4 +297,68 @@ mod tests {
         assert_eq!(diagnostics[1].span.start.line, 2);
         assert_eq!(diagnostics[2].span.start.line, 3);
     }
+    
+    #[test]
+    fn test_json_round_trip() {
+        let mut collection: DiagnosticCollection = vec![
+            create_test_diagnostic(1, Severity::Error),
+            create_test_diagnostic(2, Severity::Hint),
+        ]
+        .into_iter()
+        .collect();
+        collection.set_file_hash("0123456789abcdef");
+        
+        let restored = DiagnosticCollection::from_json(&collection.to_json().unwrap()).unwrap();
+        assert_eq!(restored, collection);
+        assert!(!restored.is_stale("0123456789abcdef"));
+        assert!(restored.is_stale("fedcba9876543210"));
+    }
+    
+    #[test]
+    fn test_summary_counts_by_severity_and_code() {
+        let mut collection = DiagnosticCollection::new();
+        collection.add(create_test_diagnostic(1, Severity::Error));
+        collection.add(create_test_diagnostic(2, Severity::Error));
+        let mut warning = create_test_diagnostic(3, Severity::Warning);
+        warning.code = Some("unreachable-code".to_string());
+        collection.add(warning);
+        
+        let summary = collection.summary();
+        assert_eq!(summary.errors, 2);
+        assert_eq!(summary.warnings, 1);
+        assert_eq!(summary.total(), 3);
+        assert_eq!(summary.by_code.get("test"), Some(&2));
+        assert_eq!(summary.by_code.get("unreachable-code"), Some(&1));
+        
+        let rendered = summary.to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "3 diagnostics (2 errors, 1 warnings, 0 info, 0 hints)");
+        assert_eq!(lines[1], "  test                  2");
+        assert_eq!(lines[2], "  unreachable-code      1");
+    }
+    
+    #[test]
+    fn test_publish_diagnostics_params_uses_lsp_shapes() {
+        let source = "-- größe\nPROCEDURE Größe_Check IS BEGIN NULL; END;\n";
+        let offset = source.find("Check").unwrap();
+        let mut diagnostic = create_test_diagnostic(2, Severity::Warning);
+        diagnostic.span = Span {
+            start: Position { line: 2, column: 17, offset },
+            end: Position { line: 2, column: 22, offset: offset + 5 },
+        };
+        diagnostic.related_information.push(DiagnosticRelatedInformation {
+            span: diagnostic.span.clone(),
+            message: "declared here".to_string(),
+        });
+        
+        let params = publish_diagnostics_params("file:///tmp/check.plsql", &[diagnostic], source);
+        assert_eq!(params["uri"], "file:///tmp/check.plsql");
+        let lsp = &params["diagnostics"][0];
+        assert_eq!(lsp["range"]["start"], serde_json::json!({ "line": 1, "character": 16 }));
+        assert_eq!(lsp["range"]["end"]["character"], 21);
+        assert_eq!(lsp["severity"], 2);
+        assert_eq!(lsp["code"], "test");
+        assert_eq!(lsp["source"], "ifs-parser");
+        assert_eq!(lsp["relatedInformation"][0]["location"]["uri"], "file:///tmp/check.plsql");
+    }
 }
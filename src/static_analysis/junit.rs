@@ -0,0 +1,109 @@
+// JUnit XML reports of analysis results for CI test reporters
+
+use crate::static_analysis::diagnostics::{Diagnostic, DiagnosticCollection};
+use crate::Result;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+
+/// Build a JUnit `<testsuite>` for one analyzed file. Every diagnostic becomes
+/// a failed `<testcase>` named after its rule code and position; a clean file
+/// yields a single passing test case.
+pub fn to_junit(diagnostics: &[Diagnostic], file_path: &Path) -> String {
+    let path = escape_xml(&file_path.to_string_lossy());
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\">",
+        path,
+        diagnostics.len().max(1),
+        diagnostics.len()
+    );
+
+    if diagnostics.is_empty() {
+        let _ = writeln!(xml, "  <testcase name=\"no diagnostics\" classname=\"{}\"/>", path);
+    }
+    for diagnostic in diagnostics {
+        let start = &diagnostic.span.start;
+        let code = diagnostic.code.as_deref().unwrap_or("-");
+        let message = escape_xml(&diagnostic.message);
+        let _ = writeln!(
+            xml,
+            "  <testcase name=\"{} at {}:{}\" classname=\"{}\">",
+            escape_xml(code),
+            start.line,
+            start.column,
+            path
+        );
+        let _ = writeln!(
+            xml,
+            "    <failure message=\"{}\" type=\"{:?}\">{}:{}:{}: {}</failure>",
+            message, diagnostic.severity, path, start.line, start.column, message
+        );
+        xml.push_str("  </testcase>\n");
+    }
+
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+impl DiagnosticCollection {
+    /// Write these diagnostics to `writer` as a JUnit report for `file_path`
+    pub fn write_junit<W: Write>(&self, file_path: &Path, mut writer: W) -> Result<()> {
+        writer.write_all(to_junit(self.all(), file_path).as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Escape text for use in XML attribute values and element content
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::{Position, Span};
+    use crate::static_analysis::rules::Severity;
+
+    #[test]
+    fn test_junit_reports_each_diagnostic_as_failed_testcase() {
+        let diagnostic = Diagnostic {
+            message: "Comparison '= NULL' is never true".to_string(),
+            span: Span {
+                start: Position { line: 3, column: 7, offset: 30 },
+                end: Position { line: 3, column: 15, offset: 38 },
+            },
+            severity: Severity::Warning,
+            code: Some("null-comparison".to_string()),
+            source: "ifs-parser".to_string(),
+            related_information: Vec::new(),
+        };
+
+        let xml = to_junit(&[diagnostic], Path::new("Order.plsql"));
+        assert!(xml.contains("<testsuite name=\"Order.plsql\" tests=\"1\" failures=\"1\""));
+        assert!(xml.contains("<testcase name=\"null-comparison at 3:7\" classname=\"Order.plsql\">"));
+        assert!(xml.contains("<failure message=\"Comparison &apos;= NULL&apos; is never true\" type=\"Warning\">"));
+        assert!(xml.contains("Order.plsql:3:7: Comparison &apos;= NULL&apos; is never true</failure>"));
+
+        let mut clean = Vec::new();
+        DiagnosticCollection::new()
+            .write_junit(Path::new("Clean.plsql"), &mut clean)
+            .unwrap();
+        let clean = String::from_utf8(clean).unwrap();
+        assert!(clean.contains("failures=\"0\""));
+        assert!(clean.contains("<testcase name=\"no diagnostics\" classname=\"Clean.plsql\"/>"));
+        assert!(!clean.contains("<failure"));
+    }
+}
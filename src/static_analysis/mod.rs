@@ -9,14 +9,22 @@
 pub mod rules;
 pub mod analyzer;
 pub mod diagnostics;
+pub mod suppression;
+pub mod render;
+pub mod junit;
 
 pub use rules::*;
 pub use analyzer::*;
 pub use diagnostics::*;
+pub use suppression::*;
+pub use render::*;
+pub use junit::*;
 
+use crate::index::Index;
 use crate::parser::ast::AstNode;
 use crate::Result;
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Configuration for static analysis
 #[derive(Debug, Clone)]
@@ -37,6 +45,7 @@ impl Default for AnalysisConfig {
                 RuleCategory::Performance,
                 RuleCategory::Security,
                 RuleCategory::BestPractices,
+                RuleCategory::Maintainability,
             ],
             rule_config: HashMap::new(),
             max_diagnostics: 100,
@@ -49,3 +58,16 @@ pub fn analyze(ast: &AstNode, config: &AnalysisConfig) -> Result<Vec<Diagnostic>
     let mut analyzer = Analyzer::new(config.clone());
     analyzer.analyze(ast)
 }
+
+/// Run static analysis on an AST node, honouring `-- ifs-parser:ignore` markers in its source
+pub fn analyze_source(ast: &AstNode, source: &str, config: &AnalysisConfig) -> Result<Vec<Diagnostic>> {
+    let mut analyzer = Analyzer::new(config.clone());
+    analyzer.analyze_source(ast, source)
+}
+
+/// Check the `@Override` and `@Overtake` subprograms indexed for `file_path` against the
+/// lower-layer implementations in `index`
+pub fn analyze_overrides(index: &Index, file_path: &Path, config: &AnalysisConfig) -> Result<Vec<Diagnostic>> {
+    let analyzer = Analyzer::new(config.clone());
+    analyzer.analyze_overrides(&index.searcher(), file_path)
+}
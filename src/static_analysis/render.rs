@@ -0,0 +1,89 @@
+// Terminal rendering of diagnostics with source context
+
+use crate::static_analysis::diagnostics::Diagnostic;
+use crate::static_analysis::rules::Severity;
+use annotate_snippets::{Level, Renderer, Snippet};
+
+/// Render a diagnostic the way compilers do: a severity-colored header
+/// followed by the offending source line(s) with the span underlined.
+/// Colors follow the same terminal detection as the rest of the CLI output.
+pub fn render_diagnostic(diagnostic: &Diagnostic, source: &str) -> String {
+    let level = match diagnostic.severity {
+        Severity::Error => Level::Error,
+        Severity::Warning => Level::Warning,
+        Severity::Info => Level::Info,
+        Severity::Hint => Level::Help,
+    };
+
+    let mut message = level.title(&diagnostic.message);
+    if let Some(code) = &diagnostic.code {
+        message = message.id(code);
+    }
+    let message = message.snippet(
+        Snippet::source(source)
+            .line_start(1)
+            .fold(true)
+            .annotation(level.span(byte_range(diagnostic, source))),
+    );
+
+    let renderer = if colored::control::SHOULD_COLORIZE.should_colorize() {
+        Renderer::styled()
+    } else {
+        Renderer::plain()
+    };
+    let rendered = renderer.render(message).to_string();
+    rendered
+}
+
+/// Clamp the diagnostic's span to the source, widening an empty span to
+/// the character it starts at so there is always something to underline
+fn byte_range(diagnostic: &Diagnostic, source: &str) -> std::ops::Range<usize> {
+    let floor = |offset: usize| {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    };
+
+    let start = floor(diagnostic.span.start.offset);
+    let mut end = floor(diagnostic.span.end.offset).max(start);
+    if end == start {
+        end = source[start..]
+            .chars()
+            .next()
+            .map_or(start, |ch| start + ch.len_utf8());
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::{Position, Span};
+
+    #[test]
+    fn test_render_shows_source_line_and_underline() {
+        let source = "PROCEDURE Check IS\nBEGIN\n   IF x_ = NULL THEN\n      NULL;\n   END IF;\nEND;\n";
+        let start = source.find("x_ = NULL").unwrap();
+        let diagnostic = Diagnostic {
+            message: "Comparison with NULL is never true".to_string(),
+            span: Span {
+                start: Position { line: 3, column: 7, offset: start },
+                end: Position { line: 3, column: 16, offset: start + "x_ = NULL".len() },
+            },
+            severity: Severity::Warning,
+            code: Some("null-comparison".to_string()),
+            source: "ifs-parser".to_string(),
+            related_information: Vec::new(),
+        };
+
+        let rendered = render_diagnostic(&diagnostic, source);
+        assert!(rendered.contains("Comparison with NULL is never true"), "{}", rendered);
+        assert!(rendered.contains("null-comparison"), "{}", rendered);
+        assert!(rendered.contains("IF x_ = NULL THEN"), "{}", rendered);
+        // annotate-snippets underlines warnings with `-`, reserving `^` for errors
+        assert!(rendered.contains("   IF x_ = NULL THEN\n  |       ---------"), "{}", rendered);
+        assert!(!rendered.contains("PROCEDURE Check IS"), "{}", rendered);
+    }
+}
@@ -2,9 +2,11 @@
 //
 // This module defines the rules that can be applied during static analysis
 
+use crate::index::SymbolKind;
 use crate::parser::ast::*;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Categories of analysis rules
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -25,6 +27,18 @@ pub enum Severity {
     Hint,
 }
 
+impl Severity {
+    /// The LSP `DiagnosticSeverity` number: 1 error, 2 warning, 3 information, 4 hint
+    pub fn to_lsp_severity(&self) -> u8 {
+        match self {
+            Severity::Error => 1,
+            Severity::Warning => 2,
+            Severity::Info => 3,
+            Severity::Hint => 4,
+        }
+    }
+}
+
 /// A static analysis rule
 #[derive(Debug, Clone)]
 pub struct Rule {
@@ -107,6 +121,60 @@ impl RuleRegistry {
             checker: check_empty_catch_blocks,
         });
         
+        self.register(Rule {
+            id: "unreachable-code".to_string(),
+            name: "Unreachable Code".to_string(),
+            description: "Statements following a RETURN or RAISE in the same block".to_string(),
+            category: RuleCategory::CodeQuality,
+            severity: Severity::Warning,
+            checker: check_unreachable_code,
+        });
+        
+        self.register(Rule {
+            id: "unreachable-when-branch".to_string(),
+            name: "Unreachable WHEN Branch".to_string(),
+            description: "Exception handlers after WHEN OTHERS and duplicate WHEN conditions in exception handlers and CASE".to_string(),
+            category: RuleCategory::CodeQuality,
+            severity: Severity::Warning,
+            checker: check_when_branches,
+        });
+        
+        self.register(Rule {
+            id: "null-comparison".to_string(),
+            name: "Comparison With NULL".to_string(),
+            description: "Comparisons with NULL using = or <>, which are never true".to_string(),
+            category: RuleCategory::CodeQuality,
+            severity: Severity::Warning,
+            checker: check_null_comparisons,
+        });
+        
+        self.register(Rule {
+            id: "goto-undeclared-label".to_string(),
+            name: "GOTO Undeclared Label".to_string(),
+            description: "GOTO statements whose target label is not declared in the enclosing subprogram".to_string(),
+            category: RuleCategory::CodeQuality,
+            severity: Severity::Error,
+            checker: check_goto_labels,
+        });
+        
+        self.register(Rule {
+            id: "end-label-mismatch".to_string(),
+            name: "Mismatched END Label".to_string(),
+            description: "Subprograms whose END label differs from the subprogram name".to_string(),
+            category: RuleCategory::CodeQuality,
+            severity: Severity::Warning,
+            checker: check_end_labels,
+        });
+        
+        self.register(Rule {
+            id: "undeclared-state".to_string(),
+            name: "Transition To Undeclared State".to_string(),
+            description: "Entity state machine transitions whose target state is not declared".to_string(),
+            category: RuleCategory::CodeQuality,
+            severity: Severity::Error,
+            checker: check_state_transitions,
+        });
+        
         // Performance rules
         self.register(Rule {
             id: "inefficient-loop".to_string(),
@@ -136,6 +204,52 @@ impl RuleRegistry {
             severity: Severity::Info,
             checker: check_missing_exception_handling,
         });
+        
+        self.register(Rule {
+            id: "unhandled-select-into".to_string(),
+            name: "Unhandled SELECT INTO".to_string(),
+            description: "SELECT INTO statements without NO_DATA_FOUND/TOO_MANY_ROWS handling".to_string(),
+            category: RuleCategory::BestPractices,
+            severity: Severity::Warning,
+            checker: check_unhandled_select_into,
+        });
+        
+        self.register(Rule {
+            id: "autonomous-transaction".to_string(),
+            name: "Autonomous Transaction".to_string(),
+            description: "Subprograms declaring PRAGMA AUTONOMOUS_TRANSACTION, unless listed under `allow`".to_string(),
+            category: RuleCategory::BestPractices,
+            severity: Severity::Warning,
+            checker: check_autonomous_transactions,
+        });
+        
+        self.register(Rule {
+            id: "select-star".to_string(),
+            name: "Select Star".to_string(),
+            description: "Views and cursors selecting `*` instead of explicit columns".to_string(),
+            category: RuleCategory::BestPractices,
+            severity: Severity::Warning,
+            checker: check_select_star,
+        });
+        
+        self.register(Rule {
+            id: "naming-pattern".to_string(),
+            name: "Naming Pattern".to_string(),
+            description: "Symbol names not matching the allow/deny patterns configured under `naming-patterns`".to_string(),
+            category: RuleCategory::BestPractices,
+            severity: Severity::Warning,
+            checker: check_naming_patterns,
+        });
+        
+        // Maintainability rules
+        self.register(Rule {
+            id: "deep-nesting".to_string(),
+            name: "Deep Nesting".to_string(),
+            description: "Subprogram bodies with deeply nested blocks".to_string(),
+            category: RuleCategory::Maintainability,
+            severity: Severity::Warning,
+            checker: check_deep_nesting,
+        });
     }
 }
 
@@ -172,9 +286,896 @@ fn check_missing_exception_handling(_ast: &AstNode, _config: &HashMap<String, se
     Vec::new()
 }
 
+/// Exceptions a single-row SELECT INTO can raise
+const SELECT_INTO_EXCEPTIONS: [&str; 2] = ["NO_DATA_FOUND", "TOO_MANY_ROWS"];
+
+fn check_unhandled_select_into(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        match node {
+            NodeRef::PlSql(PlSqlNode::Procedure { body, exception_handlers, .. })
+            | NodeRef::PlSql(PlSqlNode::Function { body, exception_handlers, .. }) => {
+                let handlers: Vec<&ExceptionHandler> = exception_handlers.iter().collect();
+                collect_unhandled_select_into(body, &handlers, &mut violations);
+            }
+            _ => {}
+        }
+    }
+    violations
+}
+
+fn collect_unhandled_select_into(
+    statements: &[PlSqlStatement],
+    handlers: &[&ExceptionHandler],
+    violations: &mut Vec<RuleViolation>,
+) {
+    for statement in statements {
+        match statement {
+            PlSqlStatement::SelectInto { span, .. } => {
+                let missing: Vec<&str> = SELECT_INTO_EXCEPTIONS
+                    .iter()
+                    .copied()
+                    .filter(|exception| !handlers.iter().any(|h| h.handles(exception)))
+                    .collect();
+                if !missing.is_empty() {
+                    violations.push(RuleViolation {
+                        rule_id: "unhandled-select-into".to_string(),
+                        message: format!("SELECT INTO may raise {} which is not handled", missing.join(" or ")),
+                        span: span.clone(),
+                        severity: Severity::Warning,
+                        suggestion: Some("Add an exception handler or use a cursor".to_string()),
+                    });
+                }
+            }
+            PlSqlStatement::If { then_branch, else_branch, .. } => {
+                collect_unhandled_select_into(then_branch, handlers, violations);
+                if let Some(else_branch) = else_branch {
+                    collect_unhandled_select_into(else_branch, handlers, violations);
+                }
+            }
+            PlSqlStatement::Loop { body, .. } => {
+                collect_unhandled_select_into(body, handlers, violations);
+            }
+            PlSqlStatement::Block { body, exception_handlers, .. } => {
+                let mut inner = handlers.to_vec();
+                inner.extend(exception_handlers);
+                collect_unhandled_select_into(body, &inner, violations);
+                for handler in exception_handlers {
+                    collect_unhandled_select_into(&handler.body, handlers, violations);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_unreachable_code(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        match node {
+            NodeRef::PlSql(PlSqlNode::Procedure { body, exception_handlers, .. })
+            | NodeRef::PlSql(PlSqlNode::Function { body, exception_handlers, .. }) => {
+                collect_unreachable_code(body, &mut violations);
+                for handler in exception_handlers {
+                    collect_unreachable_code(&handler.body, &mut violations);
+                }
+            }
+            NodeRef::PlSql(PlSqlNode::Package { body: Some(body), .. }) => {
+                collect_unreachable_code(body, &mut violations);
+            }
+            _ => {}
+        }
+    }
+    violations
+}
+
+/// Flag the first statement after an unconditional RETURN/RAISE in each statement list
+fn collect_unreachable_code(statements: &[PlSqlStatement], violations: &mut Vec<RuleViolation>) {
+    for (index, statement) in statements.iter().enumerate() {
+        if statement.is_terminating() {
+            if let Some(unreachable) = statements.get(index + 1) {
+                violations.push(RuleViolation {
+                    rule_id: "unreachable-code".to_string(),
+                    message: "Unreachable code after RETURN or RAISE".to_string(),
+                    span: unreachable.span().clone(),
+                    severity: Severity::Warning,
+                    suggestion: Some("Remove the unreachable statements".to_string()),
+                });
+            }
+            return;
+        }
+        
+        match statement {
+            PlSqlStatement::If { then_branch, else_branch, .. } => {
+                collect_unreachable_code(then_branch, violations);
+                if let Some(else_branch) = else_branch {
+                    collect_unreachable_code(else_branch, violations);
+                }
+            }
+            PlSqlStatement::Loop { body, .. } => {
+                collect_unreachable_code(body, violations);
+            }
+            PlSqlStatement::Block { body, exception_handlers, .. } => {
+                collect_unreachable_code(body, violations);
+                for handler in exception_handlers {
+                    collect_unreachable_code(&handler.body, violations);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_when_branches(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        match node {
+            NodeRef::PlSql(PlSqlNode::Procedure { exception_handlers, .. })
+            | NodeRef::PlSql(PlSqlNode::Function { exception_handlers, .. }) => {
+                collect_handler_conflicts(exception_handlers, &mut violations);
+            }
+            NodeRef::Statement(statement) => {
+                if let PlSqlStatement::Block { exception_handlers, .. } = statement {
+                    collect_handler_conflicts(exception_handlers, &mut violations);
+                }
+                for expression in statement.expressions() {
+                    collect_duplicate_case_branches(expression, &mut violations);
+                }
+            }
+            _ => {}
+        }
+    }
+    violations
+}
+
+/// Flag handlers following WHEN OTHERS and exceptions named by more than one handler
+fn collect_handler_conflicts(handlers: &[ExceptionHandler], violations: &mut Vec<RuleViolation>) {
+    let mut seen = HashSet::new();
+    let mut after_others = false;
+    for handler in handlers {
+        if after_others {
+            violations.push(RuleViolation {
+                rule_id: "unreachable-when-branch".to_string(),
+                message: "Exception handler after WHEN OTHERS is unreachable".to_string(),
+                span: handler.span.clone(),
+                severity: Severity::Warning,
+                suggestion: Some("Move WHEN OTHERS to the last handler".to_string()),
+            });
+        }
+        for exception in &handler.exceptions {
+            if !seen.insert(exception.normalized()) {
+                violations.push(RuleViolation {
+                    rule_id: "unreachable-when-branch".to_string(),
+                    message: format!("Exception '{}' is already handled by an earlier WHEN", exception.name),
+                    span: exception.span.clone(),
+                    severity: Severity::Warning,
+                    suggestion: Some("Remove or merge the duplicate handler".to_string()),
+                });
+            }
+        }
+        after_others |= handler.handles("OTHERS");
+    }
+}
+
+/// Flag CASE branches whose WHEN condition repeats an earlier branch of the same CASE
+fn collect_duplicate_case_branches(expression: &Expression, violations: &mut Vec<RuleViolation>) {
+    if let Expression::Case { branches, span, .. } = expression {
+        let mut seen = HashSet::new();
+        for (condition, _) in branches {
+            let condition = condition.to_string();
+            if !seen.insert(condition.clone()) {
+                violations.push(RuleViolation {
+                    rule_id: "unreachable-when-branch".to_string(),
+                    message: format!("CASE branch WHEN {} repeats an earlier branch and is never taken", condition),
+                    span: span.clone(),
+                    severity: Severity::Warning,
+                    suggestion: Some("Remove the duplicate WHEN branch".to_string()),
+                });
+            }
+        }
+    }
+    
+    for sub_expression in expression.sub_expressions() {
+        collect_duplicate_case_branches(sub_expression, violations);
+    }
+}
+
+fn check_goto_labels(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        let NodeRef::PlSql(subprogram) = node else {
+            continue;
+        };
+        
+        let mut statements = Vec::new();
+        flatten_statements(subprogram.child_statements(), &mut statements);
+        let labels: HashSet<String> = statements
+            .iter()
+            .filter_map(|statement| match statement {
+                PlSqlStatement::Label { name, .. } => Some(name.normalized()),
+                _ => None,
+            })
+            .collect();
+        
+        for statement in statements {
+            if let PlSqlStatement::Goto { label, span, .. } = statement {
+                if !labels.contains(&label.normalized()) {
+                    violations.push(RuleViolation {
+                        rule_id: "goto-undeclared-label".to_string(),
+                        message: format!("GOTO target '{}' is not a label in this subprogram", label.name),
+                        span: span.clone(),
+                        severity: Severity::Error,
+                        suggestion: Some(format!("Declare <<{}>> or correct the label name", label.name)),
+                    });
+                }
+            }
+        }
+    }
+    violations
+}
+
+/// Flag subprograms with `PRAGMA AUTONOMOUS_TRANSACTION`. Subprograms named in
+/// `{"autonomous-transaction": {"allow": ["Write_Log___"]}}` are exempt.
+fn check_autonomous_transactions(ast: &AstNode, config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let allowed: HashSet<String> = config
+        .get("autonomous-transaction")
+        .and_then(|value| value.get("allow"))
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.as_str())
+        .map(normalize_identifier)
+        .collect();
+    
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        let NodeRef::PlSql(PlSqlNode::Procedure { name, pragmas, .. } | PlSqlNode::Function { name, pragmas, .. }) = node
+        else {
+            continue;
+        };
+        if allowed.contains(&name.normalized()) {
+            continue;
+        }
+        
+        for pragma in pragmas.iter().filter(|pragma| pragma.is_autonomous_transaction()) {
+            violations.push(RuleViolation {
+                rule_id: "autonomous-transaction".to_string(),
+                message: format!("'{}' runs in an autonomous transaction", name.name),
+                span: pragma.span.clone(),
+                severity: Severity::Warning,
+                suggestion: Some("Let the caller control the transaction, or allow this subprogram explicitly".to_string()),
+            });
+        }
+    }
+    violations
+}
+
+fn check_select_star(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let violation = |kind: &str, name: &Identifier, span: &Span| RuleViolation {
+        rule_id: "select-star".to_string(),
+        message: format!("{} '{}' selects *", kind, name.name),
+        span: span.clone(),
+        severity: Severity::Warning,
+        suggestion: Some("List the columns explicitly so the query survives column changes".to_string()),
+    };
+    
+    let mut violations = Vec::new();
+    if let AstNode::Views(views) = ast {
+        for view in &views.views {
+            if let Some(item) = view.query.select.iter().find(|item| item.is_star()) {
+                violations.push(violation("View", &view.name, &item.span));
+            }
+        }
+    }
+    for node in ast.descendants() {
+        if let NodeRef::Declaration(PlSqlDeclaration::Cursor { name, query, span, .. }) = node {
+            if query_selects_star(query) {
+                violations.push(violation("Cursor", name, span));
+            }
+        }
+    }
+    violations
+}
+
+/// Whether the select list of a raw `SELECT ... FROM` query contains `*` or `alias.*`
+fn query_selects_star(query: &str) -> bool {
+    let normalized = query.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase();
+    let Some(rest) = normalized.strip_prefix("SELECT ") else {
+        return false;
+    };
+    let select_list = rest.split(" FROM ").next().unwrap_or(rest);
+    select_list
+        .split(',')
+        .map(|item| item.trim().trim_start_matches("DISTINCT").trim())
+        .any(|item| item == "*" || item.ends_with(".*"))
+}
+
+/// Allow and deny patterns for the names of one kind of symbol
+#[derive(Debug, Default)]
+struct NamingPatterns {
+    allow: Vec<Regex>,
+    deny: Vec<Regex>,
+}
+
+/// Read `naming-patterns` from the rule config, e.g.
+/// `{"Function": {"allow": ["^Get_", "^Set_"], "deny": ["Tmp"]}}`.
+/// Unknown symbol kinds and invalid expressions are skipped with a warning.
+fn naming_patterns(config: &HashMap<String, serde_json::Value>) -> HashMap<SymbolKind, NamingPatterns> {
+    let mut patterns = HashMap::new();
+    let Some(kinds) = config.get("naming-patterns").and_then(|value| value.as_object()) else {
+        return patterns;
+    };
+    
+    for (kind_name, kind_config) in kinds {
+        let Ok(kind) = kind_name.parse::<SymbolKind>() else {
+            log::warn!("naming-patterns: unknown symbol kind '{}'", kind_name);
+            continue;
+        };
+        let compile = |key: &str| -> Vec<Regex> {
+            kind_config
+                .get(key)
+                .and_then(|value| value.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|pattern| pattern.as_str())
+                .filter_map(|pattern| match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(e) => {
+                        log::warn!("naming-patterns: invalid {} pattern '{}': {}", kind_name, pattern, e);
+                        None
+                    }
+                })
+                .collect()
+        };
+        patterns.insert(kind, NamingPatterns { allow: compile("allow"), deny: compile("deny") });
+    }
+    patterns
+}
+
+/// Named symbols declared in `ast`, with the kind the indexer would give them
+fn named_symbols(ast: &AstNode) -> Vec<(SymbolKind, &Identifier)> {
+    let mut symbols = Vec::new();
+    match ast {
+        AstNode::Entity(entity) => symbols.push((SymbolKind::Entity, &entity.entity_name)),
+        AstNode::Enumeration(enumeration) => {
+            symbols.push((SymbolKind::Enumeration, &enumeration.enumeration_name));
+            symbols.extend(enumeration.values.iter().map(|value| (SymbolKind::EnumerationValue, &value.name)));
+        }
+        _ => {}
+    }
+    
+    for node in ast.descendants() {
+        let symbol = match node {
+            NodeRef::PlSql(PlSqlNode::Package { name, .. }) => (SymbolKind::Package, name),
+            NodeRef::PlSql(PlSqlNode::Procedure { name, .. }) => (SymbolKind::Procedure, name),
+            NodeRef::PlSql(PlSqlNode::Function { name, .. }) => (SymbolKind::Function, name),
+            NodeRef::Declaration(PlSqlDeclaration::Variable { name, .. }) => (SymbolKind::Variable, name),
+            NodeRef::Declaration(PlSqlDeclaration::Cursor { name, .. }) => (SymbolKind::Cursor, name),
+            NodeRef::Declaration(PlSqlDeclaration::Exception { name, .. }) => (SymbolKind::Exception, name),
+            NodeRef::Declaration(PlSqlDeclaration::SubprogramSpec { name, return_type: Some(_), .. }) => {
+                (SymbolKind::Function, name)
+            }
+            NodeRef::Declaration(PlSqlDeclaration::SubprogramSpec { name, return_type: None, .. }) => {
+                (SymbolKind::Procedure, name)
+            }
+            _ => continue,
+        };
+        symbols.push(symbol);
+    }
+    symbols
+}
+
+fn check_naming_patterns(ast: &AstNode, config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let patterns = naming_patterns(config);
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+    
+    let mut violations = Vec::new();
+    for (kind, name) in named_symbols(ast) {
+        let Some(kind_patterns) = patterns.get(&kind) else {
+            continue;
+        };
+        
+        let violation = |message: String| RuleViolation {
+            rule_id: "naming-pattern".to_string(),
+            message,
+            span: name.span.clone(),
+            severity: Severity::Warning,
+            suggestion: None,
+        };
+        if !kind_patterns.allow.is_empty() && !kind_patterns.allow.iter().any(|regex| regex.is_match(&name.name)) {
+            let allowed: Vec<&str> = kind_patterns.allow.iter().map(Regex::as_str).collect();
+            violations.push(violation(format!(
+                "{} name '{}' does not match any allowed pattern ({})",
+                kind,
+                name.name,
+                allowed.join(", ")
+            )));
+        }
+        if let Some(regex) = kind_patterns.deny.iter().find(|regex| regex.is_match(&name.name)) {
+            violations.push(violation(format!(
+                "{} name '{}' matches denied pattern '{}'",
+                kind,
+                name.name,
+                regex.as_str()
+            )));
+        }
+    }
+    violations
+}
+
+fn check_end_labels(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        let NodeRef::PlSql(subprogram) = node else {
+            continue;
+        };
+        let (PlSqlNode::Procedure { name, end_label: Some(label), .. }
+        | PlSqlNode::Function { name, end_label: Some(label), .. }) = subprogram
+        else {
+            continue;
+        };
+        
+        if label.normalized() != name.normalized() {
+            violations.push(RuleViolation {
+                rule_id: "end-label-mismatch".to_string(),
+                message: format!("END label '{}' does not match subprogram name '{}'", label.name, name.name),
+                span: label.span.clone(),
+                severity: Severity::Warning,
+                suggestion: Some(format!("End the subprogram with END {};", name.name)),
+            });
+        }
+    }
+    violations
+}
+
+fn check_state_transitions(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let AstNode::Entity(entity) = ast else {
+        return Vec::new();
+    };
+    let Some(state_machine) = &entity.state_machine else {
+        return Vec::new();
+    };
+    
+    let states: HashSet<String> = state_machine
+        .states
+        .iter()
+        .map(|state| state.name.normalized())
+        .collect();
+    state_machine
+        .transitions
+        .iter()
+        .filter(|transition| !states.contains(&transition.to_state.normalized()))
+        .map(|transition| RuleViolation {
+            rule_id: "undeclared-state".to_string(),
+            message: format!(
+                "Transition from '{}' targets undeclared state '{}'",
+                transition.from_state.name, transition.to_state.name
+            ),
+            span: transition.to_state.span.clone(),
+            severity: Severity::Error,
+            suggestion: Some(format!("Declare state {} or correct the target name", transition.to_state.name)),
+        })
+        .collect()
+}
+
+/// Collect `statements` and everything nested inside them, in source order
+fn flatten_statements<'a>(statements: Vec<&'a PlSqlStatement>, out: &mut Vec<&'a PlSqlStatement>) {
+    for statement in statements {
+        out.push(statement);
+        flatten_statements(statement.child_statements(), out);
+    }
+}
+
+fn check_null_comparisons(ast: &AstNode, _config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        if let NodeRef::Statement(statement) = node {
+            for expression in statement.expressions() {
+                collect_null_comparisons(expression, &mut violations);
+            }
+        }
+    }
+    violations
+}
+
+fn collect_null_comparisons(expression: &Expression, violations: &mut Vec<RuleViolation>) {
+    if let Expression::Binary { left, operator, right, span } = expression {
+        if operator.precedence() == BinaryOperator::Equal.precedence()
+            && !matches!(operator, BinaryOperator::Like | BinaryOperator::In)
+            && (left.is_null_literal() || right.is_null_literal())
+        {
+            let suggestion = match operator {
+                BinaryOperator::Equal => "Use IS NULL instead",
+                BinaryOperator::NotEqual => "Use IS NOT NULL instead",
+                _ => "Test for NULL with IS NULL or use NVL",
+            };
+            violations.push(RuleViolation {
+                rule_id: "null-comparison".to_string(),
+                message: format!("Comparison '{} NULL' is never true", operator),
+                span: span.clone(),
+                severity: Severity::Warning,
+                suggestion: Some(suggestion.to_string()),
+            });
+        }
+    }
+    
+    for sub_expression in expression.sub_expressions() {
+        collect_null_comparisons(sub_expression, violations);
+    }
+}
+
+/// Default maximum line length for the line-length rule
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 120;
+
+/// Flag every line longer than `max_line_length` characters (`max-line-length` is accepted
+/// too), pointing at the first character past the limit. Runs on source text, so it is not
+/// part of the AST registry.
+pub fn check_line_length(source: &str, config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let max_length = config
+        .get("max_line_length")
+        .or_else(|| config.get("max-line-length"))
+        .and_then(|value| value.as_u64())
+        .map(|value| value as usize)
+        .unwrap_or(DEFAULT_MAX_LINE_LENGTH);
+    
+    let mut violations = Vec::new();
+    let mut line_offset = 0;
+    for (index, raw_line) in source.split('\n').enumerate() {
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        let length = line.chars().count();
+        if length > max_length {
+            let overflow_byte = line.char_indices().nth(max_length).map_or(line.len(), |(i, _)| i);
+            violations.push(RuleViolation {
+                rule_id: "line-length".to_string(),
+                message: format!("Line is {} characters long (maximum is {})", length, max_length),
+                span: Span {
+                    start: Position {
+                        line: index + 1,
+                        column: max_length + 1,
+                        offset: line_offset + overflow_byte,
+                    },
+                    end: Position {
+                        line: index + 1,
+                        column: length + 1,
+                        offset: line_offset + line.len(),
+                    },
+                },
+                severity: Severity::Info,
+                suggestion: Some("Break the line".to_string()),
+            });
+        }
+        line_offset += raw_line.len() + 1;
+    }
+    violations
+}
+
+/// Default maximum block nesting depth for the deep-nesting rule
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 5;
+
+fn check_deep_nesting(ast: &AstNode, config: &HashMap<String, serde_json::Value>) -> Vec<RuleViolation> {
+    let max_depth = config
+        .get("max-nesting-depth")
+        .and_then(|value| value.as_u64())
+        .map(|value| value as usize)
+        .unwrap_or(DEFAULT_MAX_NESTING_DEPTH);
+    
+    let mut violations = Vec::new();
+    for node in ast.descendants() {
+        let (name, body) = match node {
+            NodeRef::PlSql(PlSqlNode::Procedure { name, body, .. })
+            | NodeRef::PlSql(PlSqlNode::Function { name, body, .. }) => (name, body.as_slice()),
+            NodeRef::PlSql(PlSqlNode::Package { name, body: Some(body), .. }) => (name, body.as_slice()),
+            _ => continue,
+        };
+        
+        if let Some((depth, statement)) = deepest_block(body, 0).filter(|(depth, _)| *depth > max_depth) {
+            violations.push(RuleViolation {
+                rule_id: "deep-nesting".to_string(),
+                message: format!(
+                    "'{}' nests blocks {} levels deep (maximum is {})",
+                    name.name, depth, max_depth
+                ),
+                span: statement.span().clone(),
+                severity: Severity::Warning,
+                suggestion: Some("Extract nested logic into separate subprograms".to_string()),
+            });
+        }
+    }
+    violations
+}
+
+/// Find the most deeply nested IF/LOOP block and its nesting depth
+fn deepest_block(statements: &[PlSqlStatement], depth: usize) -> Option<(usize, &PlSqlStatement)> {
+    let mut deepest: Option<(usize, &PlSqlStatement)> = None;
+    
+    for statement in statements {
+        let candidate = match statement {
+            PlSqlStatement::If { then_branch, else_branch, .. } => {
+                let nested = deepest_block(then_branch, depth + 1);
+                let nested_else = else_branch
+                    .as_deref()
+                    .and_then(|branch| deepest_block(branch, depth + 1));
+                deeper(deeper(Some((depth + 1, statement)), nested), nested_else)
+            }
+            PlSqlStatement::Loop { body, .. } => {
+                deeper(Some((depth + 1, statement)), deepest_block(body, depth + 1))
+            }
+            PlSqlStatement::Block { body, exception_handlers, .. } => {
+                let nested = exception_handlers
+                    .iter()
+                    .map(|handler| deepest_block(&handler.body, depth + 1))
+                    .fold(deepest_block(body, depth + 1), deeper);
+                deeper(Some((depth + 1, statement)), nested)
+            }
+            _ => None,
+        };
+        deepest = deeper(deepest, candidate);
+    }
+    
+    deepest
+}
+
+fn deeper<'a>(
+    a: Option<(usize, &'a PlSqlStatement)>,
+    b: Option<(usize, &'a PlSqlStatement)>,
+) -> Option<(usize, &'a PlSqlStatement)> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.0 > a.0 { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    
+    fn span_at(line: usize) -> Span {
+        Span {
+            start: Position { line, column: 1, offset: 0 },
+            end: Position { line, column: 10, offset: 0 },
+        }
+    }
+    
+    fn nested_ifs(levels: usize) -> AstNode {
+        let mut body = vec![PlSqlStatement::Return { value: None, span: span_at(levels + 1), trivia: None }];
+        for level in (1..=levels).rev() {
+            body = vec![PlSqlStatement::If {
+                condition: Expression::Literal { value: "TRUE".to_string(), span: span_at(level) },
+                then_branch: body,
+                else_branch: None,
+                span: span_at(level),
+                trivia: None,
+            }];
+        }
+        AstNode::PlSql(PlSqlNode::Procedure {
+            name: Identifier { name: "Nested_Proc".to_string(), span: span_at(1) },
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: Vec::new(),
+            body,
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+            span: span_at(1),
+        })
+    }
+    
+    fn procedure_with(body: Vec<PlSqlStatement>) -> AstNode {
+        AstNode::PlSql(PlSqlNode::Procedure {
+            name: Identifier { name: "Fetch_Proc".to_string(), span: span_at(1) },
+            visibility: ProcedureVisibility::Public,
+            annotations: Vec::new(),
+            parameters: Vec::new(),
+            body,
+            exception_handlers: Vec::new(),
+            end_label: None,
+            pragmas: Vec::new(),
+            declarations: Vec::new(),
+            span: span_at(1),
+        })
+    }
+    
+    fn select_into(line: usize) -> PlSqlStatement {
+        PlSqlStatement::SelectInto {
+            into: vec![Identifier { name: "v".to_string(), span: span_at(line) }],
+            query: "SELECT x INTO v FROM t".to_string(),
+            span: span_at(line),
+            trivia: None,
+        }
+    }
+    
+    #[test]
+    fn test_unhandled_select_into_is_flagged() {
+        let violations = check_unhandled_select_into(&procedure_with(vec![select_into(2)]), &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "unhandled-select-into");
+        assert_eq!(violations[0].span.start.line, 2);
+    }
+    
+    #[test]
+    fn test_select_into_inside_handled_block_is_not_flagged() {
+        let block = PlSqlStatement::Block {
+            declarations: Vec::new(),
+            body: vec![select_into(3)],
+            exception_handlers: vec![ExceptionHandler {
+                exceptions: vec![Identifier { name: "OTHERS".to_string(), span: span_at(4) }],
+                body: Vec::new(),
+                span: span_at(4),
+            }],
+            span: span_at(2),
+            trivia: None,
+        };
+        assert!(check_unhandled_select_into(&procedure_with(vec![block]), &HashMap::new()).is_empty());
+    }
+    
+    #[test]
+    fn test_unhandled_select_into_in_package_body_subprogram_is_flagged() {
+        let ast = crate::parser::parse_source(
+            "PACKAGE BODY Fetch_API IS\n   PROCEDURE Fetch___ IS\n   BEGIN\n      SELECT x INTO v_ FROM t;\n   END Fetch___;\nEND Fetch_API;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        
+        let violations = check_unhandled_select_into(&ast, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].span.start.line, 4);
+    }
+    
+    fn call(line: usize) -> PlSqlStatement {
+        PlSqlStatement::Call {
+            name: Identifier { name: "Do_It".to_string(), span: span_at(line) },
+            arguments: Vec::new(),
+            span: span_at(line),
+            trivia: None,
+        }
+    }
+    
+    #[test]
+    fn test_statement_after_return_is_unreachable() {
+        let body = vec![PlSqlStatement::Return { value: None, span: span_at(2), trivia: None }, call(3), call(4)];
+        let violations = check_unreachable_code(&procedure_with(body), &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "unreachable-code");
+        assert_eq!(violations[0].span.start.line, 3);
+    }
+    
+    #[test]
+    fn test_statement_after_branch_return_is_reachable() {
+        let branch = PlSqlStatement::If {
+            condition: Expression::Literal { value: "TRUE".to_string(), span: span_at(2) },
+            then_branch: vec![PlSqlStatement::Return { value: None, span: span_at(3), trivia: None }],
+            else_branch: None,
+            span: span_at(2),
+            trivia: None,
+        };
+        let body = vec![branch, call(5)];
+        assert!(check_unreachable_code(&procedure_with(body), &HashMap::new()).is_empty());
+    }
+    
+    #[test]
+    fn test_unreachable_code_in_package_body_subprogram_is_flagged() {
+        let ast = crate::parser::parse_source(
+            "PACKAGE BODY Fetch_API IS\n   PROCEDURE Fetch___ IS\n   BEGIN\n      RETURN;\n      Do_It;\n   END Fetch___;\nEND Fetch_API;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        
+        let violations = check_unreachable_code(&ast, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].span.start.line, 5);
+    }
+    
+    #[test]
+    fn test_null_comparison_is_flagged() {
+        let ast = crate::parser::parse_source(
+            "PROCEDURE Check_Null IS\nBEGIN\n   IF x_ = NULL THEN\n      NULL;\n   END IF;\n   IF y_ IS NULL AND z_ <> NULL THEN\n      NULL;\n   END IF;\nEND;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        
+        let violations = check_null_comparisons(&ast, &HashMap::new());
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].span.start.line, 3);
+        assert_eq!(violations[0].suggestion.as_deref(), Some("Use IS NULL instead"));
+        assert_eq!(violations[1].span.start.line, 6);
+        assert_eq!(violations[1].suggestion.as_deref(), Some("Use IS NOT NULL instead"));
+    }
+    
+    #[test]
+    fn test_is_null_is_not_flagged() {
+        let ast = crate::parser::parse_source(
+            "PROCEDURE Check_Null IS\nBEGIN\n   IF x_ IS NULL THEN\n      RETURN;\n   END IF;\nEND;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        assert!(check_null_comparisons(&ast, &HashMap::new()).is_empty());
+    }
+    
+    #[test]
+    fn test_goto_to_undeclared_label_is_flagged() {
+        let parse = |source: &str| {
+            crate::parser::parse_source(source, crate::parser::Language::PlSql).unwrap()
+        };
+        
+        let valid = parse("PROCEDURE Retry_Once IS\nBEGIN\n   <<retry>>\n   Do_Work;\n   IF failed_ THEN\n      GOTO retry;\n   END IF;\nEND;");
+        assert!(check_goto_labels(&valid, &HashMap::new()).is_empty());
+        
+        let invalid = parse("PROCEDURE Retry_Once IS\nBEGIN\n   <<retry>>\n   Do_Work;\n   IF failed_ THEN\n      GOTO restart;\n   END IF;\nEND;");
+        let violations = check_goto_labels(&invalid, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].span.start.line, 6);
+        assert!(violations[0].message.contains("restart"));
+    }
+    
+    #[test]
+    fn test_line_length_points_at_overflow_column() {
+        let mut config = HashMap::new();
+        config.insert("max_line_length".to_string(), serde_json::json!(20));
+        let source = "BEGIN\n   Customer_API.Get_Name(id_);\nEND;";
+        
+        let violations = check_line_length(source, &config);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "line-length");
+        assert_eq!(violations[0].span.start.line, 2);
+        assert_eq!(violations[0].span.start.column, 21);
+        assert_eq!(&source[violations[0].span.start.offset..violations[0].span.end.offset], "Name(id_);");
+    }
+    
+    #[test]
+    fn test_line_length_accepts_kebab_case_key() {
+        let mut config = HashMap::new();
+        config.insert("max-line-length".to_string(), serde_json::json!(20));
+        let violations = check_line_length("BEGIN\n   Customer_API.Get_Name(id_);\nEND;", &config);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].span.start.column, 21);
+    }
+    
+    #[test]
+    fn test_line_length_defaults_to_120() {
+        let source = format!("{}\n{}", "x".repeat(120), "y".repeat(121));
+        let violations = check_line_length(&source, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].span.start.line, 2);
+    }
+    
+    #[test]
+    fn test_deep_nesting_flags_six_levels() {
+        let violations = check_deep_nesting(&nested_ifs(6), &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "deep-nesting");
+        assert_eq!(violations[0].span.start.line, 6);
+    }
+    
+    #[test]
+    fn test_deep_nesting_in_package_body_subprogram_is_flagged() {
+        let ast = crate::parser::parse_source(
+            "PACKAGE BODY Nest_API IS\n   PROCEDURE Nest___ IS\n   BEGIN\n      LOOP\n         LOOP\n            LOOP\n               NULL;\n            END LOOP;\n         END LOOP;\n      END LOOP;\n   END Nest___;\nEND Nest_API;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        
+        let mut config = HashMap::new();
+        config.insert("max-nesting-depth".to_string(), serde_json::json!(2));
+        let violations = check_deep_nesting(&ast, &config);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.starts_with("'Nest___' nests blocks 3 levels deep"));
+        assert_eq!(violations[0].span.start.line, 6);
+    }
+    
+    #[test]
+    fn test_deep_nesting_allows_three_levels() {
+        assert!(check_deep_nesting(&nested_ifs(3), &HashMap::new()).is_empty());
+    }
+    
+    #[test]
+    fn test_deep_nesting_threshold_is_configurable() {
+        let mut config = HashMap::new();
+        config.insert("max-nesting-depth".to_string(), serde_json::json!(2));
+        assert_eq!(check_deep_nesting(&nested_ifs(3), &config).len(), 1);
+    }
 
     #[test]
     fn test_rule_registry() {
@@ -186,4 +1187,142 @@ mod tests {
         let quality_rules = registry.get_rules_by_category(&RuleCategory::CodeQuality);
         assert!(!quality_rules.is_empty());
     }
+    
+    #[test]
+    fn test_transition_to_undeclared_state_is_flagged() {
+        let parse = |source: &str| {
+            crate::parser::parse_source(source, crate::parser::Language::Entity).unwrap()
+        };
+        
+        let valid = parse("entityname ShopOrder;\nstatemachine {\n   initial state Planned {\n      event Release to Released;\n   }\n   final state Released;\n}\n");
+        assert!(check_state_transitions(&valid, &HashMap::new()).is_empty());
+        
+        let invalid = parse("entityname ShopOrder;\nstatemachine {\n   initial state Planned {\n      event Release to Relased;\n   }\n   final state Released;\n}\n");
+        let violations = check_state_transitions(&invalid, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "undeclared-state");
+        assert_eq!(violations[0].span.start.line, 4);
+        assert!(violations[0].message.contains("Relased"));
+    }
+    
+    #[test]
+    fn test_mismatched_end_label_is_flagged() {
+        let parse = |source: &str| {
+            crate::parser::parse_source(source, crate::parser::Language::PlSql).unwrap()
+        };
+        
+        assert!(check_end_labels(&parse("PROCEDURE Foo IS BEGIN NULL; END Foo;"), &HashMap::new()).is_empty());
+        assert!(check_end_labels(&parse("PROCEDURE Foo IS BEGIN NULL; END;"), &HashMap::new()).is_empty());
+        
+        let violations = check_end_labels(&parse("PROCEDURE Foo IS BEGIN NULL; END Bar;"), &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "end-label-mismatch");
+        assert!(violations[0].message.contains("'Bar'"));
+    }
+    
+    #[test]
+    fn test_naming_patterns_flag_non_conforming_functions() {
+        let ast = crate::parser::parse_source(
+            "PACKAGE BODY Order_API IS\n   FUNCTION Get_X RETURN NUMBER IS BEGIN RETURN 1; END Get_X;\n   FUNCTION Compute_X RETURN NUMBER IS BEGIN RETURN 2; END Compute_X;\n   FUNCTION Get_Tmp_Y RETURN NUMBER IS BEGIN RETURN 3; END Get_Tmp_Y;\nEND Order_API;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        let mut config = HashMap::new();
+        config.insert(
+            "naming-patterns".to_string(),
+            serde_json::json!({ "Function": { "allow": ["^Get_|^Set_"], "deny": ["_Tmp_"] } }),
+        );
+        
+        let violations = check_naming_patterns(&ast, &config);
+        let flagged: Vec<&str> = violations
+            .iter()
+            .map(|violation| violation.message.split('\'').nth(1).unwrap())
+            .collect();
+        assert_eq!(flagged, vec!["Compute_X", "Get_Tmp_Y"]);
+        assert!(violations[0].message.contains("does not match any allowed pattern (^Get_|^Set_)"));
+        assert!(violations[1].message.contains("denied pattern '_Tmp_'"));
+        assert!(check_naming_patterns(&ast, &HashMap::new()).is_empty());
+    }
+    
+    #[test]
+    fn test_autonomous_transaction_is_flagged_unless_allowed() {
+        let parse = |source: &str| {
+            crate::parser::parse_source(source, crate::parser::Language::PlSql).unwrap()
+        };
+        let autonomous = parse("PROCEDURE Write_Log___ IS\n   PRAGMA AUTONOMOUS_TRANSACTION;\nBEGIN\n   COMMIT;\nEND Write_Log___;");
+        let ordinary = parse("PROCEDURE Write_Log___ IS\nBEGIN\n   NULL;\nEND Write_Log___;");
+        
+        let violations = check_autonomous_transactions(&autonomous, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "autonomous-transaction");
+        assert_eq!(violations[0].span.start.line, 2);
+        assert!(check_autonomous_transactions(&ordinary, &HashMap::new()).is_empty());
+        
+        let mut config = HashMap::new();
+        config.insert("autonomous-transaction".to_string(), serde_json::json!({ "allow": ["write_log___"] }));
+        assert!(check_autonomous_transactions(&autonomous, &config).is_empty());
+    }
+    
+    #[test]
+    fn test_select_star_in_views_and_cursors() {
+        let parse = |source: &str, language| crate::parser::parse_source(source, language).unwrap();
+        let star_view = parse(
+            "VIEW Customer_All IS\nSELECT *\nFROM   customer_tab;\n",
+            crate::parser::Language::Views,
+        );
+        let explicit_view = parse(
+            "VIEW Customer_Pub IS\nSELECT customer_no customer_no,\n       name        name\nFROM   customer_tab;\n",
+            crate::parser::Language::Views,
+        );
+        
+        let violations = check_select_star(&star_view, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_id, "select-star");
+        assert!(violations[0].message.contains("Customer_All"));
+        assert!(check_select_star(&explicit_view, &HashMap::new()).is_empty());
+        
+        let cursors = parse(
+            "PACKAGE BODY Customer_API IS\n   CURSOR get_all IS\n      SELECT c.*\n      FROM customer_tab c;\n   CURSOR get_names IS\n      SELECT name FROM customer_tab;\nEND Customer_API;",
+            crate::parser::Language::PlSql,
+        );
+        let violations = check_select_star(&cursors, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("get_all"));
+    }
+    
+    #[test]
+    fn test_when_others_before_other_handlers_is_flagged() {
+        let ast = crate::parser::parse_source(
+            "PROCEDURE Fetch_Name IS\nBEGIN\n   NULL;\nEXCEPTION\n   WHEN OTHERS THEN\n      NULL;\n   WHEN NO_DATA_FOUND THEN\n      NULL;\n   WHEN NO_DATA_FOUND THEN\n      NULL;\nEND;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        let violations = check_when_branches(&ast, &HashMap::new());
+        assert_eq!(violations.len(), 3);
+        assert!(violations.iter().all(|v| v.rule_id == "unreachable-when-branch"));
+        assert_eq!(violations[0].span.start.line, 7);
+        assert!(violations.iter().any(|v| v.message.contains("already handled")));
+    }
+    
+    #[test]
+    fn test_when_others_last_is_not_flagged() {
+        let ast = crate::parser::parse_source(
+            "PROCEDURE Fetch_Name IS\nBEGIN\n   x_ := CASE y_ WHEN 1 THEN 'A' WHEN 2 THEN 'B' END;\nEXCEPTION\n   WHEN NO_DATA_FOUND THEN\n      NULL;\n   WHEN OTHERS THEN\n      NULL;\nEND;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        assert!(check_when_branches(&ast, &HashMap::new()).is_empty());
+    }
+    
+    #[test]
+    fn test_duplicate_case_branch_is_flagged() {
+        let ast = crate::parser::parse_source(
+            "PROCEDURE Map_Code IS\nBEGIN\n   x_ := CASE y_ WHEN 1 THEN 'A' WHEN 1 THEN 'B' ELSE 'C' END;\nEND;",
+            crate::parser::Language::PlSql,
+        )
+        .unwrap();
+        let violations = check_when_branches(&ast, &HashMap::new());
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("WHEN 1"), "{}", violations[0].message);
+    }
 }
@@ -0,0 +1,147 @@
+// Inline suppression of diagnostics
+//
+// A comment of the form `-- ifs-parser:ignore` silences every diagnostic on
+// its own line and on the line below it. `-- ifs-parser:ignore[rule-a, rule-b]`
+// limits the suppression to the listed rule ids.
+
+use crate::parser::lexer::Lexer;
+use crate::parser::Language;
+use crate::static_analysis::diagnostics::Diagnostic;
+
+/// Marker text that starts an ignore comment
+pub const IGNORE_MARKER: &str = "ifs-parser:ignore";
+
+/// A single ignore comment found in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnoreMarker {
+    /// Line the comment appears on
+    pub line: usize,
+    /// Rule ids to suppress, or `None` to suppress every rule
+    pub rules: Option<Vec<String>>,
+}
+
+impl IgnoreMarker {
+    /// Parse an ignore marker from the text of a line comment
+    fn parse(comment: &str, line: usize) -> Option<Self> {
+        let text = comment.strip_prefix("--")?.trim();
+        let rest = text.strip_prefix(IGNORE_MARKER)?;
+        
+        let rules = if let Some(list) = rest.strip_prefix('[') {
+            let list = &list[..list.find(']')?];
+            Some(
+                list.split(',')
+                    .map(|rule| rule.trim().to_string())
+                    .filter(|rule| !rule.is_empty())
+                    .collect(),
+            )
+        } else if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+            None
+        } else {
+            return None;
+        };
+        
+        Some(Self { line, rules })
+    }
+    
+    /// Check whether this marker silences the given diagnostic
+    pub fn suppresses(&self, diagnostic: &Diagnostic) -> bool {
+        let line = diagnostic.span.start.line;
+        if line != self.line && line != self.line + 1 {
+            return false;
+        }
+        
+        match (&self.rules, &diagnostic.code) {
+            (None, _) => true,
+            (Some(rules), Some(code)) => rules.iter().any(|rule| rule == code),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// All ignore markers in a source file
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreMarkers {
+    markers: Vec<IgnoreMarker>,
+}
+
+impl IgnoreMarkers {
+    /// Collect the ignore markers from the comments in `source`
+    pub fn from_source(source: &str) -> Self {
+        let markers = Lexer::new(source.to_string(), Language::PlSql)
+            .comments()
+            .iter()
+            .filter_map(|comment| IgnoreMarker::parse(&comment.value, comment.position.line))
+            .collect();
+        Self { markers }
+    }
+    
+    /// Get the markers found in the source
+    pub fn markers(&self) -> &[IgnoreMarker] {
+        &self.markers
+    }
+    
+    /// Check whether any marker silences the given diagnostic
+    pub fn suppresses(&self, diagnostic: &Diagnostic) -> bool {
+        self.markers.iter().any(|marker| marker.suppresses(diagnostic))
+    }
+    
+    /// Drop every diagnostic silenced by a marker
+    pub fn filter(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        if self.markers.is_empty() {
+            return diagnostics;
+        }
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| !self.suppresses(diagnostic))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::{Position, Span};
+    use crate::static_analysis::rules::Severity;
+    
+    fn diagnostic_on_line(line: usize, code: &str) -> Diagnostic {
+        Diagnostic {
+            message: "test".to_string(),
+            span: Span {
+                start: Position { line, column: 1, offset: 0 },
+                end: Position { line, column: 2, offset: 1 },
+            },
+            severity: Severity::Warning,
+            code: Some(code.to_string()),
+            source: "ifs-parser".to_string(),
+            related_information: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_parse_ignore_markers() {
+        let source = "-- ifs-parser:ignore\nx := 1; -- ifs-parser:ignore[rule-a, rule-b]\n-- ifs-parser:ignored\n";
+        let markers = IgnoreMarkers::from_source(source);
+        
+        assert_eq!(
+            markers.markers(),
+            &[
+                IgnoreMarker { line: 1, rules: None },
+                IgnoreMarker {
+                    line: 2,
+                    rules: Some(vec!["rule-a".to_string(), "rule-b".to_string()]),
+                },
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_marker_covers_same_and_next_line_only() {
+        let markers = IgnoreMarkers::from_source("\n-- ifs-parser:ignore[rule-a]\n");
+        
+        assert!(markers.suppresses(&diagnostic_on_line(2, "rule-a")));
+        assert!(markers.suppresses(&diagnostic_on_line(3, "rule-a")));
+        assert!(!markers.suppresses(&diagnostic_on_line(1, "rule-a")));
+        assert!(!markers.suppresses(&diagnostic_on_line(4, "rule-a")));
+        assert!(!markers.suppresses(&diagnostic_on_line(3, "rule-b")));
+    }
+}
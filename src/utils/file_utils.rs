@@ -1,8 +1,13 @@
 // File utility functions
 
 use crate::Result;
+use encoding_rs::WINDOWS_1252;
+use serde::Serialize;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 /// Recursively find files matching a pattern
 pub fn find_files<P: AsRef<Path>>(
@@ -40,18 +45,82 @@ fn find_files_recursive(
     Ok(())
 }
 
+/// List files under `dir` that differ from `git_ref` in the working tree, using
+/// `git diff --name-only`, together with untracked files that are not ignored.
+/// Deleted files are left out.
+pub fn changed_files_since<P: AsRef<Path>>(dir: P, git_ref: &str) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let changed = git_output(dir, &["diff", "--name-only", "--relative", git_ref, "--"])
+        .map_err(|e| anyhow::anyhow!("git diff against '{}' failed in {}: {}", git_ref, dir.display(), e))?;
+    let untracked = git_output(dir, &["ls-files", "--others", "--exclude-standard"])
+        .map_err(|e| anyhow::anyhow!("git ls-files failed in {}: {}", dir.display(), e))?;
+    
+    let paths: std::collections::BTreeSet<PathBuf> = changed
+        .lines()
+        .chain(untracked.lines())
+        .map(|line| dir.join(line))
+        .filter(|path| path.is_file())
+        .collect();
+    Ok(paths.into_iter().collect())
+}
+
+/// Run git in `dir` and return its standard output, or its standard error on failure
+fn git_output(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 /// Read file contents as string
 pub fn read_file_string<P: AsRef<Path>>(path: P) -> Result<String> {
     let content = fs::read_to_string(path)?;
     Ok(content)
 }
 
+/// Read a source file, falling back to Windows-1252 when it is not valid UTF-8.
+/// Spans produced from the result are offsets into the decoded UTF-8 text,
+/// which differ from raw file offsets after any non-ASCII cp1252 byte.
+pub fn read_source<P: AsRef<Path>>(path: P) -> Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(decode_source(bytes))
+}
+
+/// Decode raw source bytes as UTF-8, or as Windows-1252 if that fails
+pub fn decode_source(bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(e) => {
+            let (content, _) = WINDOWS_1252.decode_without_bom_handling(e.as_bytes());
+            content.into_owned()
+        }
+    }
+}
+
 /// Get file size in bytes
 pub fn get_file_size<P: AsRef<Path>>(path: P) -> Result<u64> {
     let metadata = fs::metadata(path)?;
     Ok(metadata.len())
 }
 
+/// Get file modification time in nanoseconds since the Unix epoch, if available
+pub fn get_modified_nanos<P: AsRef<Path>>(path: P) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    i64::try_from(since_epoch.as_nanos()).ok()
+}
+
+/// Stable 64-bit FNV-1a hash of file contents, as hex
+pub fn content_hash(content: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
 /// Check if a file exists
 pub fn file_exists<P: AsRef<Path>>(path: P) -> bool {
     path.as_ref().exists() && path.as_ref().is_file()
@@ -178,11 +247,71 @@ impl Iterator for FileWalker {
     }
 }
 
+/// CSV writer that serializes records as they arrive instead of buffering them.
+/// Safe to share across threads; each `write` takes the lock for one row.
+pub struct CsvSink<W: Write> {
+    writer: Mutex<csv::Writer<W>>,
+    rows: AtomicUsize,
+}
+
+impl CsvSink<fs::File> {
+    /// Create a sink writing to a new file at `path`
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        ensure_parent_dir(&path)?;
+        Ok(Self::new(fs::File::create(path)?))
+    }
+}
+
+impl<W: Write> CsvSink<W> {
+    /// Create a sink over any writer; the header is taken from the first record
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(csv::Writer::from_writer(writer)),
+            rows: AtomicUsize::new(0),
+        }
+    }
+
+    /// Serialize one record as a row
+    pub fn write<T: Serialize>(&self, record: &T) -> Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow::anyhow!("CSV sink lock poisoned"))?;
+        writer.serialize(record)?;
+        self.rows.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Number of rows written so far, excluding the header
+    pub fn rows(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    /// Flush buffered rows to the underlying writer
+    pub fn flush(&self) -> Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow::anyhow!("CSV sink lock poisoned"))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Flush and return the underlying writer
+    pub fn into_inner(self) -> Result<W> {
+        let writer = self
+            .writer
+            .into_inner()
+            .map_err(|_| anyhow::anyhow!("CSV sink lock poisoned"))?;
+        writer.into_inner().map_err(|e| anyhow::anyhow!("{}", e.error()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rayon::prelude::*;
     use std::fs::File;
-    use std::io::Write;
     use tempfile::TempDir;
 
     #[test]
@@ -212,6 +341,39 @@ mod tests {
         assert_eq!(get_stem(&file_path), Some("test".to_string()));
     }
     
+    #[test]
+    fn test_read_source_windows_1252() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("legacy.plsql");
+        fs::write(&file_path, b"-- Caf\xE9 handling\n").unwrap();
+        
+        assert!(read_file_string(&file_path).is_err());
+        assert_eq!(read_source(&file_path).unwrap(), "-- Caf\u{e9} handling\n");
+    }
+    
+    #[test]
+    fn test_windows_1252_source_parses_after_decoding() {
+        use crate::parser::{parse_source, AstNode, Expression, Language, PlSqlNode, PlSqlStatement};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("legacy.plsql");
+        fs::write(
+            &file_path,
+            b"PROCEDURE Set_Name IS\n   -- Caf\xE9 handling\nBEGIN\n   name_ := 'Caf\xE9';\nEND Set_Name;\n",
+        )
+        .unwrap();
+        
+        let content = read_source(&file_path).unwrap();
+        let ast = parse_source(&content, Language::PlSql).unwrap();
+        let AstNode::PlSql(PlSqlNode::Procedure { body, .. }) = ast else {
+            panic!("Expected procedure node, got {:?}", ast);
+        };
+        let [PlSqlStatement::Assignment { value: Expression::Literal { value, .. }, .. }] = body.as_slice() else {
+            panic!("Expected a single literal assignment, got {:?}", body);
+        };
+        assert_eq!(value, "'Caf\u{e9}'");
+    }
+    
     #[test]
     fn test_find_files() {
         let temp_dir = TempDir::new().unwrap();
@@ -247,4 +409,24 @@ mod tests {
         assert_eq!(files.len(), 1);
         assert!(files[0].file_name().unwrap().to_str().unwrap().contains("test1.txt"));
     }
+    
+    #[test]
+    fn test_csv_sink_writes_rows_incrementally() {
+        #[derive(Serialize)]
+        struct Row {
+            id: usize,
+            name: String,
+        }
+        
+        let sink = CsvSink::new(Vec::new());
+        (0..1000).into_par_iter().for_each(|id| {
+            sink.write(&Row { id, name: format!("file_{}.plsql", id) }).unwrap();
+        });
+        assert_eq!(sink.rows(), 1000);
+        
+        let output = String::from_utf8(sink.into_inner().unwrap()).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("id,name"));
+        assert_eq!(lines.count(), 1000);
+    }
 }
@@ -0,0 +1,99 @@
+// Shrinking of failing parser inputs
+//
+// When a file from a full codebase run fails to parse, `minimize_failure`
+// removes lines from it for as long as the parse keeps failing with the same
+// error, leaving a small reproduction for grammar bug triage. `minimize_lines`
+// does the shrinking for any other notion of failure, such as a panic.
+
+use crate::parser::{parse_source, Language, ParseError};
+
+/// Remove lines from `source` while it keeps failing to parse with the same error,
+/// returning the smallest failing snippet found. Sources that parse are returned as is.
+pub fn minimize_failure(source: &str, language: Language) -> String {
+    let Some(failure) = failure_signature(source, language) else {
+        return source.to_string();
+    };
+    minimize_lines(source, |candidate| {
+        failure_signature(candidate, language).as_ref() == Some(&failure)
+    })
+}
+
+/// Remove runs of lines from `source` for as long as `still_fails` holds for what is
+/// left, returning the smallest non-empty snippet found
+pub fn minimize_lines<F: Fn(&str) -> bool>(source: &str, still_fails: F) -> String {
+    let mut lines: Vec<&str> = source.lines().collect();
+    let mut chunk = lines.len() / 2;
+    while chunk > 0 {
+        // Try dropping each run of `chunk` lines, keeping every removal that still fails
+        let mut start = 0;
+        let mut removed_any = false;
+        while start < lines.len() {
+            let end = (start + chunk).min(lines.len());
+            let candidate: Vec<&str> = lines[..start].iter().chain(&lines[end..]).copied().collect();
+            if !candidate.is_empty() && still_fails(&candidate.join("\n")) {
+                lines = candidate;
+                removed_any = true;
+            } else {
+                start = end;
+            }
+        }
+        // Retry the same size after a removal, since earlier runs may now be removable
+        if !removed_any {
+            chunk /= 2;
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// What makes two failures the same: the expected and found tokens of an unexpected
+/// token error, ignoring where it occurred, or the message of any other error
+fn failure_signature(source: &str, language: Language) -> Option<String> {
+    let error = parse_source(source, language).err()?;
+    Some(match error.downcast_ref::<ParseError>() {
+        Some(ParseError::UnexpectedToken { expected, found, .. }) => format!("{} / {}", expected, found),
+        _ => error.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimize_failure_keeps_failing_and_shrinks() {
+        let source = "\
+PROCEDURE Broken_ IS
+BEGIN
+   a_ := 1;
+   b_ := 2;
+   Customer_API.Refresh(a_, b_);
+   IF a_ = b_ THEN
+      NULL;
+   END;
+   c_ := 3;
+END Broken_;";
+        assert!(parse_source(source, Language::PlSql).is_err());
+
+        let minimized = minimize_failure(source, Language::PlSql);
+        assert!(minimized.lines().count() < source.lines().count(), "{}", minimized);
+        assert_eq!(
+            failure_signature(&minimized, Language::PlSql),
+            failure_signature(source, Language::PlSql)
+        );
+        assert!(!minimized.contains("Customer_API"), "{}", minimized);
+    }
+
+    #[test]
+    fn test_minimize_lines_keeps_what_the_predicate_needs() {
+        let source = "first\nkeep one\nmiddle\nkeep two\nlast";
+        let minimized = minimize_lines(source, |candidate| candidate.contains("one") && candidate.contains("two"));
+        assert_eq!(minimized, "keep one\nkeep two");
+    }
+
+    #[test]
+    fn test_minimize_failure_returns_parsing_source_unchanged() {
+        let source = "PROCEDURE Fine_ IS\nBEGIN\n   NULL;\nEND Fine_;";
+        assert_eq!(minimize_failure(source, Language::PlSql), source);
+    }
+}
@@ -3,6 +3,7 @@
 pub mod file_utils;
 pub mod performance;
 pub mod logging;
+pub mod minimize;
 
 pub use file_utils::*;
 pub use performance::*;
@@ -166,6 +166,26 @@ impl PerformanceMonitor {
         
         report
     }
+    
+    /// Generate the performance report as JSON, keyed by counter name, with times in milliseconds
+    pub fn report_json(&self) -> serde_json::Value {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let counters: serde_json::Map<String, serde_json::Value> = self
+            .get_counters()
+            .into_iter()
+            .map(|(name, counter)| {
+                let value = serde_json::json!({
+                    "count": counter.count(),
+                    "total_ms": millis(counter.total()),
+                    "avg_ms": millis(counter.average()),
+                    "min_ms": millis(counter.min()),
+                    "max_ms": millis(counter.max()),
+                });
+                (name, value)
+            })
+            .collect();
+        serde_json::Value::Object(counters)
+    }
 }
 
 impl Default for PerformanceMonitor {
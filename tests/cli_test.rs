@@ -0,0 +1,242 @@
+// Integration tests for the ifs-parser command line interface
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Run the CLI and parse the JSON document from its output
+fn run_json(args: &[&str]) -> serde_json::Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_ifs-parser"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run ifs-parser");
+    assert!(output.status.success(), "ifs-parser exited with {}", output.status);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json_start = stdout.find("\n{").map(|i| i + 1).unwrap_or(0);
+    serde_json::from_str(&stdout[json_start..]).expect("output is not valid JSON")
+}
+
+#[test]
+fn test_analyze_json_includes_naming_diagnostic() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("naming.plsql");
+    fs::write(&file_path, "PROCEDURE lower_case_proc IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+
+    let result = run_json(&["-f", file_path.to_str().unwrap(), "-o", "json", "--analyze"]);
+
+    let diagnostics = result["diagnostics"].as_array().unwrap();
+    let naming = diagnostics
+        .iter()
+        .find(|d| d["code"] == "procedure-naming")
+        .expect("missing procedure-naming diagnostic");
+    assert_eq!(naming["severity"], "Info");
+    assert_eq!(naming["span"]["start"]["line"], 1);
+    assert_eq!(result["diagnostic_summary"]["info"], 1);
+}
+
+#[test]
+fn test_json_omits_diagnostics_without_analyze() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("naming.plsql");
+    fs::write(&file_path, "PROCEDURE lower_case_proc IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+
+    let result = run_json(&["-f", file_path.to_str().unwrap(), "-o", "json"]);
+    assert!(result.get("diagnostics").is_none());
+}
+
+#[test]
+fn test_analyze_honours_ignore_comment() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("ignored.plsql");
+    fs::write(
+        &file_path,
+        "-- ifs-parser:ignore[procedure-naming]\nPROCEDURE lower_case_proc IS\nBEGIN\n   NULL;\nEND;\n",
+    )
+    .unwrap();
+
+    let result = run_json(&["-f", file_path.to_str().unwrap(), "-o", "json", "--analyze"]);
+
+    let diagnostics = result["diagnostics"].as_array().unwrap();
+    assert!(diagnostics.iter().all(|d| d["code"] != "procedure-naming"));
+}
+
+#[test]
+fn test_tokens_output_lists_token_types() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("tokens.plsql");
+    fs::write(&file_path, "FUNCTION F RETURN NUMBER").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ifs-parser"))
+        .args(["-f", file_path.to_str().unwrap(), "-o", "tokens"])
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run ifs-parser");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().any(|line| line.contains("Function") && line.contains("\"FUNCTION\"")));
+    assert!(stdout.lines().any(|line| line.contains("Return") && line.contains("\"RETURN\"")));
+
+    let result = run_json(&["-f", file_path.to_str().unwrap(), "-o", "tokens", "--json"]);
+    let types: Vec<&str> = result["tokens"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|token| token["token_type"].as_str().unwrap())
+        .collect();
+    assert!(types.contains(&"Function"));
+    assert!(types.contains(&"Return"));
+}
+
+/// Run git in `dir`, panicking if it fails
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .expect("failed to run git");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn test_since_only_processes_changed_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path();
+    git(repo, &["init", "-q"]);
+    git(repo, &["config", "user.email", "test@example.com"]);
+    git(repo, &["config", "user.name", "Test"]);
+    fs::write(repo.join("committed.plsql"), "PROCEDURE Committed IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+    fs::write(repo.join("modified.plsql"), "PROCEDURE Modified IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "initial"]);
+    fs::write(repo.join("modified.plsql"), "PROCEDURE modified IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+    fs::write(repo.join("Untracked.PLSQL"), "PROCEDURE Untracked IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+    fs::write(repo.join("notes.txt"), "not source").unwrap();
+    fs::write(repo.join(".gitignore"), "ignored.plsql\n").unwrap();
+    fs::write(repo.join("ignored.plsql"), "PROCEDURE Ignored IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+
+    let result = run_json(&["-d", repo.to_str().unwrap(), "--since", "HEAD", "-o", "json", "--analyze"]);
+
+    assert_eq!(result["summary"]["total_files"], 2);
+    let files = result["files"].as_array().unwrap();
+    let mut paths: Vec<&str> = files.iter().map(|file| file["file_path"].as_str().unwrap()).collect();
+    paths.sort();
+    assert_eq!(paths.len(), 2);
+    assert!(paths[0].ends_with("Untracked.PLSQL"), "{:?}", paths);
+    assert!(paths[1].ends_with("modified.plsql"), "{:?}", paths);
+}
+
+#[test]
+fn test_since_outside_git_repository_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("file.plsql"), "PROCEDURE P IS BEGIN NULL; END;").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ifs-parser"))
+        .args(["-d", temp_dir.path().to_str().unwrap(), "--since", "HEAD"])
+        .env("NO_COLOR", "1")
+        .env("GIT_CEILING_DIRECTORIES", temp_dir.path().parent().unwrap())
+        .output()
+        .expect("failed to run ifs-parser");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("git diff"));
+}
+
+#[test]
+fn test_profile_reports_phases() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("profiled.plsql");
+    fs::write(&file_path, "PROCEDURE Profiled IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+
+    let result = run_json(&["-f", file_path.to_str().unwrap(), "-o", "json", "--analyze", "--profile"]);
+    for phase in ["read", "parse", "analyze"] {
+        assert_eq!(result["profile"][phase]["count"], 1, "missing phase {}", phase);
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ifs-parser"))
+        .args(["-f", file_path.to_str().unwrap(), "--analyze", "--profile"])
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run ifs-parser");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Performance Report"));
+    for phase in ["read", "parse", "analyze"] {
+        assert!(stdout.lines().any(|line| line.starts_with(phase)), "missing phase {}", phase);
+    }
+}
+
+#[test]
+fn test_analyze_lsp_output_is_publish_diagnostics_params() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("naming.plsql");
+    fs::write(&file_path, "PROCEDURE lower_case_proc IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+
+    let result = run_json(&["-f", file_path.to_str().unwrap(), "-o", "lsp", "--analyze"]);
+
+    let uri = result["uri"].as_str().unwrap();
+    assert!(uri.starts_with("file://") && uri.ends_with("/naming.plsql"), "unexpected uri {}", uri);
+    let diagnostics = result["diagnostics"].as_array().unwrap();
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics[0]["range"]["start"]["line"].is_u64());
+    assert!(diagnostics[0]["severity"].is_u64());
+    let naming = diagnostics
+        .iter()
+        .find(|d| d["code"] == "procedure-naming")
+        .expect("missing procedure-naming diagnostic");
+    assert_eq!(naming["severity"], 3);
+    assert_eq!(naming["range"]["start"]["line"], 0);
+    assert_eq!(naming["source"], "ifs-parser");
+}
+
+#[test]
+fn test_index_dry_run_prints_plan_without_indexing() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("new.plsql"), "PROCEDURE New_Proc IS\nBEGIN\n   NULL;\nEND;\n").unwrap();
+    let db_path = temp_dir.path().join("index.db");
+
+    let run = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ifs-parser"))
+            .args(["-d", source_dir.to_str().unwrap(), "--index", db_path.to_str().unwrap()])
+            .args(extra)
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("failed to run ifs-parser");
+        assert!(output.status.success(), "ifs-parser exited with {}", output.status);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let plan = run(&["--dry-run"]);
+    assert!(plan.contains("Added: 1 file(s)"), "{}", plan);
+    assert!(plan.contains("new.plsql"), "{}", plan);
+    assert!(!db_path.exists(), "dry run created the index file");
+
+    // The dry run did not write anything, so the file is still new
+    assert!(run(&["--dry-run"]).contains("Added: 1 file(s)"));
+    assert!(run(&[]).contains("1 indexed"));
+    assert!(run(&["--dry-run"]).contains("Unchanged: 1 file(s)"));
+}
+
+#[test]
+fn test_check_format_reports_badly_indented_procedure() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("indent.plsql");
+    let source = "PROCEDURE Check_Indent IS\nBEGIN\n  IF x_ = 1 THEN\n        NULL;\n  END IF;\nEND Check_Indent;\n";
+    fs::write(&file_path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ifs-parser"))
+        .args(["-f", file_path.to_str().unwrap(), "--check-format"])
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run ifs-parser");
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("indent.plsql:4\n-        NULL;\n+      NULL;"), "{}", stdout);
+    assert!(stdout.contains("needs formatting (3 lines)"), "{}", stdout);
+
+    // The check never rewrites the file
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), source);
+}
@@ -1,10 +1,14 @@
 use csv::Writer;
-use ifs_parser::parser::tree_sitter_simple::IfsPlsqlParser;
+use ifs_parser::parser::tree_sitter_simple::{ErrorRange, IfsPlsqlParser};
+use ifs_parser::parser::{parse_source, Language};
+use ifs_parser::utils::file_utils::{read_source, CsvSink};
+use ifs_parser::utils::minimize::minimize_lines;
 use rayon::prelude::*;
 use serde::Serialize;
 use std::fs;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Serialize, Clone)]
@@ -15,6 +19,8 @@ struct ParseResult {
     line_count: usize,
     file_size: u64,
     parse_success: bool,
+    panicked: bool,
+    error_ranges: usize,
     error_message: String,
     parse_time_ms: u64,
 }
@@ -23,7 +29,9 @@ struct ParseResult {
 struct SummaryStats {
     total_files: usize,
     successful_parses: usize,
+    parsed_with_errors: usize,
     failed_parses: usize,
+    panicked_parses: usize,
     success_rate: f64,
     total_lines: usize,
     total_size_mb: f64,
@@ -116,13 +124,15 @@ fn parse_single_file(file_path: &Path) -> ParseResult {
                 line_count: 0,
                 file_size: 0,
                 parse_success: false,
+                panicked: false,
+                error_ranges: 0,
                 error_message: format!("Failed to create parser: {}", e),
                 parse_time_ms: 0,
             };
         }
     };
 
-    let content = match fs::read_to_string(file_path) {
+    let content = match read_source(file_path) {
         Ok(c) => c,
         Err(e) => {
             return ParseResult {
@@ -136,6 +146,8 @@ fn parse_single_file(file_path: &Path) -> ParseResult {
                 line_count: 0,
                 file_size: 0,
                 parse_success: false,
+                panicked: false,
+                error_ranges: 0,
                 error_message: format!("Failed to read file: {}", e),
                 parse_time_ms: 0,
             };
@@ -145,9 +157,13 @@ fn parse_single_file(file_path: &Path) -> ParseResult {
     let line_count = content.lines().count();
     let file_size = content.len() as u64;
 
-    let (parse_success, error_message) = match parser.parse(&content) {
-        Ok(_) => (true, String::new()),
-        Err(e) => (false, format!("{:?}", e)),
+    let (parse_success, panicked, error_ranges, error_message) = match guarded_parse(&mut parser, &content) {
+        ParseOutcome::Parsed => (true, false, 0, String::new()),
+        ParseOutcome::ParsedWithErrors(ranges) => {
+            (true, false, ranges.len(), format!("first error at line {}", ranges[0].span.start.line))
+        }
+        ParseOutcome::Failed(e) => (false, false, 0, e),
+        ParseOutcome::Panicked(message) => (false, true, 0, format!("panic: {}", message)),
     };
 
     let parse_time = start_time.elapsed();
@@ -163,23 +179,77 @@ fn parse_single_file(file_path: &Path) -> ParseResult {
         line_count,
         file_size,
         parse_success,
+        panicked,
+        error_ranges,
         error_message,
         parse_time_ms: parse_time.as_millis() as u64,
     }
 }
 
-fn write_results_to_csv(
-    results: &[ParseResult],
-    output_file: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut wtr = Writer::from_path(output_file)?;
+/// Outcome of a single parse, keeping panics apart from ordinary parse errors
+#[derive(Debug, PartialEq)]
+enum ParseOutcome {
+    Parsed,
+    /// Tree-sitter recovered, but parts of the file ended up in error nodes
+    ParsedWithErrors(Vec<ErrorRange>),
+    Failed(String),
+    Panicked(String),
+}
 
-    for result in results {
-        wtr.serialize(result)?;
+/// Parse with both the tree-sitter parser and the hand-written lexer/parser,
+/// catching any panic instead of letting it abort the run
+fn guarded_parse(parser: &mut IfsPlsqlParser, content: &str) -> ParseOutcome {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        // Only panics matter for the hand-written parser; its errors are expected
+        let _ = parse_source(content, Language::PlSql);
+        parser
+            .parse_with_errors(content)
+            .map(|(_, errors)| errors)
+            .map_err(|e| format!("{:?}", e))
+    }));
+
+    match result {
+        Ok(Ok(errors)) if errors.is_empty() => ParseOutcome::Parsed,
+        Ok(Ok(errors)) => ParseOutcome::ParsedWithErrors(errors),
+        Ok(Err(e)) => ParseOutcome::Failed(e),
+        Err(payload) => ParseOutcome::Panicked(panic_message(payload.as_ref())),
     }
+}
 
-    wtr.flush()?;
-    println!("📄 Results written to: {}", output_file);
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Check whether creating a parser or parsing the given source panics
+fn panics_on(content: &str) -> bool {
+    let outcome = panic::catch_unwind(|| {
+        let mut parser = IfsPlsqlParser::new().expect("failed to create the PL/SQL parser");
+        guarded_parse(&mut parser, content)
+    });
+    matches!(outcome, Err(_) | Ok(ParseOutcome::Panicked(_)))
+}
+
+/// Shrink a panicking input line by line while it keeps panicking,
+/// producing a minimized reproduction
+fn minimize_panicking_input<F: Fn(&str) -> bool>(content: &str, still_panics: F) -> String {
+    minimize_lines(content, still_panics)
+}
+
+/// Write a minimized reproduction for each file that panicked
+fn write_panic_reproductions(results: &[ParseResult], timestamp: &str) -> Result<(), Box<dyn std::error::Error>> {
+    for result in results.iter().filter(|r| r.panicked) {
+        let content = read_source(&result.file_path)?;
+        let minimized = minimize_panicking_input(&content, panics_on);
+        let output = format!("panic_repro_{}_{}", timestamp, result.file_name);
+        fs::write(&output, minimized)?;
+        println!("💥 Minimized reproduction for {} written to: {}", result.file_path, output);
+    }
     Ok(())
 }
 
@@ -194,40 +264,77 @@ fn write_summary_to_csv(
     Ok(())
 }
 
-fn calculate_summary(results: &[ParseResult]) -> SummaryStats {
-    let total_files = results.len();
-    let successful_parses = results.iter().filter(|r| r.parse_success).count();
-    let failed_parses = total_files - successful_parses;
-    let success_rate = if total_files > 0 {
-        (successful_parses as f64 / total_files as f64) * 100.0
-    } else {
-        0.0
-    };
+/// Running totals over all parse results, so individual results can be
+/// streamed to CSV and dropped instead of kept for the summary
+#[derive(Debug, Default)]
+struct RunTotals {
+    total_files: usize,
+    successful_parses: usize,
+    parsed_with_errors: usize,
+    total_lines: usize,
+    total_size: u64,
+    total_parse_time_ms: u64,
+    /// Per-module (total, successful) file counts
+    modules: std::collections::HashMap<String, (usize, usize)>,
+    /// Panicked results are rare and needed afterwards for reproductions
+    panicked: Vec<ParseResult>,
+}
 
-    let total_lines: usize = results.iter().map(|r| r.line_count).sum();
-    let total_size_mb = results.iter().map(|r| r.file_size as f64).sum::<f64>() / (1024.0 * 1024.0);
-    let total_parse_time_ms: u64 = results.iter().map(|r| r.parse_time_ms).sum();
-    let average_parse_time_ms = if total_files > 0 {
-        total_parse_time_ms as f64 / total_files as f64
-    } else {
-        0.0
-    };
-    let files_per_second = if total_parse_time_ms > 0 {
-        (total_files as f64) / (total_parse_time_ms as f64 / 1000.0)
-    } else {
-        0.0
-    };
+impl RunTotals {
+    fn record(&mut self, result: &ParseResult) {
+        self.total_files += 1;
+        if result.parse_success {
+            self.successful_parses += 1;
+        }
+        if result.error_ranges > 0 {
+            self.parsed_with_errors += 1;
+        }
+        if result.panicked {
+            self.panicked.push(result.clone());
+        }
+        self.total_lines += result.line_count;
+        self.total_size += result.file_size;
+        self.total_parse_time_ms += result.parse_time_ms;
 
-    SummaryStats {
-        total_files,
-        successful_parses,
-        failed_parses,
-        success_rate,
-        total_lines,
-        total_size_mb,
-        total_parse_time_ms,
-        average_parse_time_ms,
-        files_per_second,
+        let module = self.modules.entry(result.module.clone()).or_insert((0, 0));
+        module.0 += 1;
+        if result.parse_success {
+            module.1 += 1;
+        }
+    }
+
+    fn summary(&self) -> SummaryStats {
+        let total_files = self.total_files;
+        let successful_parses = self.successful_parses;
+        let success_rate = if total_files > 0 {
+            (successful_parses as f64 / total_files as f64) * 100.0
+        } else {
+            0.0
+        };
+        let average_parse_time_ms = if total_files > 0 {
+            self.total_parse_time_ms as f64 / total_files as f64
+        } else {
+            0.0
+        };
+        let files_per_second = if self.total_parse_time_ms > 0 {
+            (total_files as f64) / (self.total_parse_time_ms as f64 / 1000.0)
+        } else {
+            0.0
+        };
+
+        SummaryStats {
+            total_files,
+            successful_parses,
+            parsed_with_errors: self.parsed_with_errors,
+            failed_parses: total_files - successful_parses,
+            panicked_parses: self.panicked.len(),
+            success_rate,
+            total_lines: self.total_lines,
+            total_size_mb: self.total_size as f64 / (1024.0 * 1024.0),
+            total_parse_time_ms: self.total_parse_time_ms,
+            average_parse_time_ms,
+            files_per_second,
+        }
     }
 }
 
@@ -295,50 +402,41 @@ fn test_full_ifs_codebase_parsing() -> Result<(), Box<dyn std::error::Error>> {
     println!("📁 Found {} .plsql files to process", files.len());
     println!("🔧 Starting parallel processing...");
 
-    // Create shared progress tracking
-    let processed_count = Arc::new(Mutex::new(0usize));
-    let success_count = Arc::new(Mutex::new(0usize));
+    // Results are streamed to CSV as they complete; only running totals stay in memory
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let detailed_output = format!("ifs_parsing_results_{}.csv", timestamp);
+    let summary_output = format!("ifs_parsing_summary_{}.csv", timestamp);
+    let sink = CsvSink::create(&detailed_output)?;
+    let totals = Mutex::new(RunTotals::default());
     let total_files = files.len();
 
     // Process files in parallel using rayon
-    let results: Vec<ParseResult> = files
-        .par_iter()
-        .map(|file_path| {
-            let result = parse_single_file(file_path);
-
-            // Update progress (with occasional printing to avoid spam)
-            {
-                let mut processed = processed_count.lock().unwrap();
-                *processed += 1;
-
-                if result.parse_success {
-                    let mut success = success_count.lock().unwrap();
-                    *success += 1;
-                }
-
-                // Print progress every 100 files or at milestones
-                if *processed % 100 == 0 || *processed == total_files {
-                    let success = *success_count.lock().unwrap();
-                    print_progress(*processed, total_files, success, start_time);
-                }
-            }
+    files.par_iter().try_for_each(|file_path| {
+        let result = parse_single_file(file_path);
+        sink.write(&result)?;
 
-            result
-        })
-        .collect();
+        // Update progress (with occasional printing to avoid spam)
+        let mut totals = totals.lock().unwrap();
+        totals.record(&result);
+
+        // Print progress every 100 files or at milestones
+        if totals.total_files % 100 == 0 || totals.total_files == total_files {
+            print_progress(totals.total_files, total_files, totals.successful_parses, start_time);
+        }
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    sink.flush()?;
+    println!("📄 {} results written to: {}", sink.rows(), detailed_output);
 
     let total_time = start_time.elapsed();
 
     // Calculate summary statistics
-    let summary = calculate_summary(&results);
-
-    // Write detailed results to CSV
-    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-    let detailed_output = format!("ifs_parsing_results_{}.csv", timestamp);
-    let summary_output = format!("ifs_parsing_summary_{}.csv", timestamp);
+    let totals = totals.into_inner().unwrap();
+    let summary = totals.summary();
 
-    write_results_to_csv(&results, &detailed_output)?;
     write_summary_to_csv(&summary, &summary_output)?;
+    write_panic_reproductions(&totals.panicked, &timestamp.to_string())?;
 
     // Print final summary
     println!("\n🎉 Full IFS Codebase Parsing Complete!");
@@ -349,7 +447,9 @@ fn test_full_ifs_codebase_parsing() -> Result<(), Box<dyn std::error::Error>> {
         "   ✅ Successful: {} ({:.2}%)",
         summary.successful_parses, summary.success_rate
     );
+    println!("   ⚠️  Parsed with errors: {}", summary.parsed_with_errors);
     println!("   ❌ Failed: {}", summary.failed_parses);
+    println!("   💥 Panicked: {}", summary.panicked_parses);
     println!("   📝 Total lines: {}", summary.total_lines);
     println!("   💾 Total size: {:.2} MB", summary.total_size_mb);
     println!(
@@ -362,18 +462,8 @@ fn test_full_ifs_codebase_parsing() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Print module breakdown
-    let mut module_stats: std::collections::HashMap<String, (usize, usize)> =
-        std::collections::HashMap::new();
-    for result in &results {
-        let entry = module_stats.entry(result.module.clone()).or_insert((0, 0));
-        entry.0 += 1; // total files
-        if result.parse_success {
-            entry.1 += 1; // successful files
-        }
-    }
-
     println!("\n📋 Module Breakdown:");
-    let mut module_vec: Vec<_> = module_stats.iter().collect();
+    let mut module_vec: Vec<_> = totals.modules.iter().collect();
     module_vec.sort_by_key(|(module, _)| module.as_str());
 
     for (module, (total, success)) in module_vec {
@@ -406,5 +496,78 @@ fn test_full_ifs_codebase_parsing() -> Result<(), Box<dyn std::error::Error>> {
         );
     }
 
+    let panicked_files: Vec<&str> = totals
+        .panicked
+        .iter()
+        .map(|r| r.file_path.as_str())
+        .collect();
+    assert!(
+        panicked_files.is_empty(),
+        "Parser panicked on {} file(s): {:?}",
+        panicked_files.len(),
+        panicked_files
+    );
+
     Ok(())
 }
+
+/// Pieces of PL/SQL, including multibyte text in strings, comments and names,
+/// that `generate_input` strings together into mostly-broken sources
+const FUZZ_FRAGMENTS: &[&str] = &[
+    "PROCEDURE", "FUNCTION", "PACKAGE", "BODY", "IS", "AS", "BEGIN", "END", "END;", "RETURN",
+    "IF", "THEN", "ELSE", "LOOP", "SELECT", "INTO", "FROM", "WHERE", "RAISE", "EXCEPTION",
+    "Test_Proc", "Customer_API.Get_Name", "x_", "NULL", "42", "3.14", ";", ",", ".", "(", ")",
+    ":=", "=>", "||", "%TYPE", "/", "\n/\n", "'", "''", "\"", "'ÅÄÖåäö'", "'é'", "'unterminated",
+    "-- Ändrad av Åsa\n", "-- Café", "/* 注释 */", "/* open", "*/", "q'[ø]'", "q'{", "\"Tëst\"",
+    "é", "€", "😀", "Ω", "&AO.", "@Override", "<<lbl>>", "\n", "\t", "\r\n",
+];
+
+/// Build a pseudo-random source from `FUZZ_FRAGMENTS`
+fn generate_input(rng: &mut fastrand::Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..rng.usize(1..40) {
+        input.push_str(FUZZ_FRAGMENTS[rng.usize(..FUZZ_FRAGMENTS.len())]);
+        if rng.bool() {
+            input.push(' ');
+        }
+    }
+    input
+}
+
+#[test]
+fn test_parser_does_not_panic_on_generated_inputs() {
+    let mut rng = fastrand::Rng::with_seed(1395);
+    for _ in 0..500 {
+        let input = generate_input(&mut rng);
+        if panics_on(&input) {
+            panic!("Parser panicked; minimized input: {:?}", minimize_panicking_input(&input, panics_on));
+        }
+    }
+}
+
+#[test]
+fn test_parser_does_not_panic_on_truncated_multibyte_source() {
+    let source = "-- Ändrad av Åsa\nPROCEDURE Tëst (name_ IN VARCHAR2) IS\n   /* 注释 */\n   x_ VARCHAR2(20) := 'ÅÄÖåäö';\nBEGIN\n   x_ := q'[ø€]' || 'é😀';\nEND Tëst;\n/\n";
+    for (end, _) in source.char_indices() {
+        let input = &source[..end];
+        assert!(!panics_on(input), "Parser panicked on input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_minimize_panicking_input() {
+    let content = "PROCEDURE A IS\nBEGIN\n  x := 1;\n  boom;\n  y := 2;\nEND;";
+    let minimized = minimize_panicking_input(content, |candidate| candidate.contains("boom"));
+    assert_eq!(minimized, "  boom;");
+}
+
+#[test]
+fn test_guarded_parse_distinguishes_parsed_with_errors() {
+    let mut parser = IfsPlsqlParser::new().unwrap();
+
+    let clean = guarded_parse(&mut parser, "PROCEDURE Valid IS\nBEGIN\n   NULL;\nEND Valid;\n");
+    assert_eq!(clean, ParseOutcome::Parsed);
+
+    let broken = guarded_parse(&mut parser, "PROCEDURE Broken IS\nBEGIN\n   x_ := ;\nEND;\n");
+    assert!(matches!(broken, ParseOutcome::ParsedWithErrors(ranges) if !ranges.is_empty()));
+}